@@ -0,0 +1,1170 @@
+// src/alto.rs
+// Úprava ALTO z Tesseractu (xxxx.ocr.xml) před předáním do ProArc:
+// doplní sourceImageInformation (název a BLAKE3 zdroje), blok zpracování
+// (Tesseract + LazyProArcConvert), sjednotí ID prvků layoutu (P0001_TB0001, …),
+// volitelně převede souřadnice na mm10 / inch1200 a výsledek zkontroluje
+// proti struktuře schématu požadované verze ALTO. `convert` převádí ALTO
+// mezi verzemi 2.x, 3.x a 4.x (čerstvý výstup i příkaz `alto-convert`).
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Local, SecondsFormat};
+use clap::ValueEnum;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use serde::{Deserialize, Serialize};
+
+/// Jednotka souřadnic v ALTO (MeasurementUnit)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AltoUnit {
+    #[default]
+    Pixel,
+    /// Desetiny milimetru
+    Mm10,
+    /// 1/1200 palce
+    Inch1200,
+}
+
+impl AltoUnit {
+    pub fn name(self) -> &'static str {
+        match self {
+            AltoUnit::Pixel => "pixel",
+            AltoUnit::Mm10 => "mm10",
+            AltoUnit::Inch1200 => "inch1200",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            "pixel" => Some(AltoUnit::Pixel),
+            "mm10" => Some(AltoUnit::Mm10),
+            "inch1200" => Some(AltoUnit::Inch1200),
+            _ => None,
+        }
+    }
+
+    /// Počet jednotek na palec; u pixelů podle DPI obrázku
+    fn per_inch(self, dpi: f64) -> f64 {
+        match self {
+            AltoUnit::Pixel => dpi,
+            AltoUnit::Mm10 => 254.0,
+            AltoUnit::Inch1200 => 1200.0,
+        }
+    }
+}
+
+/// Verze ALTO, pro kterou umíme zapsat hlavičku a kontrolovat strukturu (2.0–4.4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AltoVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl AltoVersion {
+    pub fn parse(text: &str) -> Result<Self> {
+        let (major, minor) = text
+            .trim()
+            .split_once('.')
+            .and_then(|(a, b)| Some((a.parse::<u8>().ok()?, b.parse::<u8>().ok()?)))
+            .ok_or_else(|| anyhow!("`{text}` není verze ALTO"))?;
+        match (major, minor) {
+            (2, 0..=1) | (3, 0..=1) | (4, 0..=4) => Ok(Self { major, minor }),
+            _ => bail!("ALTO {major}.{minor} není podporováno (2.0–4.4)"),
+        }
+    }
+
+    pub fn namespace(self) -> &'static str {
+        match self.major {
+            2 => "http://www.loc.gov/standards/alto/ns-v2#",
+            3 => "http://www.loc.gov/standards/alto/ns-v3#",
+            _ => "http://www.loc.gov/standards/alto/ns-v4#",
+        }
+    }
+
+    pub fn schema_location(self) -> String {
+        let xsd = match (self.major, self.minor) {
+            (2, 0) => "http://www.loc.gov/standards/alto/alto-v2.0.xsd".to_string(),
+            (2, minor) => format!("http://www.loc.gov/standards/alto/v2/alto-2-{minor}.xsd"),
+            (3, minor) => format!("http://www.loc.gov/alto/v3/alto-3-{minor}.xsd"),
+            (_, minor) => format!("http://www.loc.gov/standards/alto/v4/alto-4-{minor}.xsd"),
+        };
+        format!("{} {xsd}", self.namespace())
+    }
+
+    /// sourceImageInformation/fileIdentifier (od 2.1)
+    fn has_file_identifier(self) -> bool {
+        self >= Self { major: 2, minor: 1 }
+    }
+
+    /// Description/Processing (od 3.0, dřív jen OCRProcessing)
+    fn has_processing(self) -> bool {
+        self.major >= 3
+    }
+
+    /// processingCategory (od 4.0)
+    fn has_processing_category(self) -> bool {
+        self.major >= 4
+    }
+}
+
+/// Verze ALTO z příkazové řádky (--alto-version, alto-convert --to)
+pub fn parse_version_arg(s: &str) -> Result<String, String> {
+    AltoVersion::parse(s)
+        .map(|v| v.to_string())
+        .map_err(|e| e.to_string())
+}
+
+impl fmt::Display for AltoVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Vstupy pro úpravu ALTO jedné stránky
+pub struct PostProcess<'a> {
+    pub version: AltoVersion,
+    /// Index stránky ("0001") – základ ID a PHYSICAL_IMG_NR
+    pub index: &'a str,
+    /// Název zdrojového souboru stránky (bez cesty)
+    pub source_name: String,
+    pub source_blake3: String,
+    pub unit: AltoUnit,
+    /// Rozlišení obrázku (x, y); nutné pro převod z pixelů
+    pub dpi: Option<(f64, f64)>,
+    /// Nastavení OCR do processingStepSettings
+    pub ocr_settings: String,
+}
+
+/// Upraví ALTO soubor na místě. Pokud výsledek neprojde kontrolou
+/// struktury, soubor se nepřepíše a vrátí se chyba.
+pub fn postprocess_file(path: &Path, pp: &PostProcess) -> Result<()> {
+    let xml = fs::read_to_string(path)
+        .with_context(|| format!("Nelze načíst ALTO `{}`", path.display()))?;
+    let output = postprocess(&xml, pp)
+        .with_context(|| format!("Úprava ALTO `{}` selhala", path.display()))?;
+    let problems = validate(&output, pp.version)?;
+    if !problems.is_empty() {
+        bail!(
+            "ALTO `{}` neodpovídá schématu {}: {}",
+            path.display(),
+            pp.version,
+            summarize(&problems)
+        );
+    }
+    fs::write(path, output).with_context(|| format!("Nelze zapsat ALTO `{}`", path.display()))
+}
+
+/// Prvních pár problémů kontroly do jednoho řádku
+pub fn summarize(problems: &[String]) -> String {
+    const SHOWN: usize = 5;
+    let mut text = problems.iter().take(SHOWN).cloned().collect::<Vec<_>>().join("; ");
+    if problems.len() > SHOWN {
+        text.push_str(&format!(" … a {} dalších", problems.len() - SHOWN));
+    }
+    text
+}
+
+/// Prefix ID pro prvky layoutu, které dostávají sjednocená ID
+fn id_prefix(name: &[u8]) -> Option<&'static str> {
+    Some(match name {
+        b"Page" => "",
+        b"PrintSpace" => "PS",
+        b"TopMargin" => "TM",
+        b"LeftMargin" => "LM",
+        b"RightMargin" => "RM",
+        b"BottomMargin" => "BM",
+        b"ComposedBlock" => "CB",
+        b"TextBlock" => "TB",
+        b"Illustration" => "IL",
+        b"GraphicalElement" => "GE",
+        b"TextLine" => "TL",
+        b"String" => "ST",
+        _ => return None,
+    })
+}
+
+/// Atributy odkazující na ID (IDREF / IDREFS)
+const REF_ATTRS: [&[u8]; 4] = [b"IDNEXT", b"STYLEREFS", b"TAGREFS", b"PROCESSINGREFS"];
+
+/// Údaje z původního ALTO potřebné pro přepis
+struct Scan {
+    unit: AltoUnit,
+    /// softwareName (+ softwareVersion) z Tesseractu
+    software: Option<String>,
+    /// Nová ID prvků layoutu v pořadí dokumentu
+    ids: Vec<String>,
+    /// Původní ID → nové
+    renamed: HashMap<String, String>,
+}
+
+fn scan(xml: &str, index: &str) -> Result<Scan> {
+    let mut reader = Reader::from_str(xml);
+    let mut result = Scan {
+        unit: AltoUnit::Pixel,
+        software: None,
+        ids: Vec::new(),
+        renamed: HashMap::new(),
+    };
+    let mut counters: HashMap<&str, usize> = HashMap::new();
+    let mut in_layout = false;
+    let mut current = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        match read(&mut reader)? {
+            Event::Start(e) | Event::Empty(e) if in_layout => {
+                let Some(prefix) = id_prefix(e.local_name().as_ref()) else {
+                    continue;
+                };
+                let n = counters.entry(prefix).or_default();
+                *n += 1;
+                let id = if prefix.is_empty() {
+                    format!("P{index}")
+                } else {
+                    format!("P{index}_{prefix}{n:04}")
+                };
+                if let Some(old) = attr(&e, b"ID")? {
+                    result.renamed.insert(old, id.clone());
+                }
+                result.ids.push(id);
+            }
+            Event::Start(e) => {
+                if e.local_name().as_ref() == b"Layout" {
+                    in_layout = true;
+                }
+                current = e.local_name().as_ref().to_vec();
+                text.clear();
+            }
+            Event::Text(t) => text.push_str(&t.decode()?),
+            Event::End(_) => {
+                let value = text.trim();
+                match current.as_slice() {
+                    b"MeasurementUnit" => {
+                        result.unit = AltoUnit::parse(value)
+                            .ok_or_else(|| anyhow!("Neznámá MeasurementUnit `{value}`"))?;
+                    }
+                    b"softwareName" if !value.is_empty() => {
+                        result.software = Some(value.to_string());
+                    }
+                    b"softwareVersion" if !value.is_empty() => {
+                        if let Some(ref mut software) = result.software {
+                            software.push(' ');
+                            software.push_str(value);
+                        }
+                    }
+                    _ => {}
+                }
+                current.clear();
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+/// Přepíše ALTO podle `pp`; vrací nový obsah souboru
+pub fn postprocess(xml: &str, pp: &PostProcess) -> Result<String> {
+    let converted = convert(xml, pp.version)?;
+    let xml = converted.as_str();
+    let scan = scan(xml, pp.index)?;
+
+    // Převod souřadnic (x, y) ze zdrojové jednotky na požadovanou
+    let factors = if scan.unit == pp.unit {
+        None
+    } else {
+        let (dx, dy) = match pp.dpi {
+            Some(dpi) => dpi,
+            None if scan.unit == AltoUnit::Pixel || pp.unit == AltoUnit::Pixel => bail!(
+                "Pro převod souřadnic na {} chybí DPI obrázku (nastav DPI v OCR)",
+                pp.unit.name()
+            ),
+            None => (1.0, 1.0),
+        };
+        Some((
+            pp.unit.per_inch(dx) / scan.unit.per_inch(dx),
+            pp.unit.per_inch(dy) / scan.unit.per_inch(dy),
+        ))
+    };
+
+    let description = description(pp, scan.software.as_deref());
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+    let mut ids = scan.ids.into_iter();
+    let mut depth = 0usize;
+    let mut skip = 0usize;
+    let mut described = false;
+    let mut in_layout = false;
+
+    loop {
+        let event = read(&mut reader)?;
+        // Původní Description se zahodí a nahradí novým
+        if skip > 0 {
+            match event {
+                Event::Start(_) => skip += 1,
+                Event::End(_) => {
+                    skip -= 1;
+                    if skip == 0 {
+                        depth -= 1;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_start = matches!(event, Event::Start(_));
+                let name = e.local_name().as_ref().to_vec();
+                if depth == 0 {
+                    writer.write_event(root_event(e, pp.version, is_start)?)?;
+                } else if depth == 1 && name == b"Description" {
+                    writer.get_mut().extend_from_slice(description.as_bytes());
+                    described = true;
+                    if is_start {
+                        skip = 1;
+                    }
+                } else {
+                    if depth == 1 && !described {
+                        writer.get_mut().extend_from_slice(description.as_bytes());
+                        writer.get_mut().extend_from_slice(b"\n\t");
+                        described = true;
+                    }
+                    if name == b"Layout" {
+                        in_layout = is_start;
+                    }
+                    let elem = if in_layout {
+                        layout_element(e, &mut ids, &scan.renamed, factors, pp.index)?
+                    } else {
+                        e.to_owned()
+                    };
+                    writer.write_event(if is_start {
+                        Event::Start(elem)
+                    } else {
+                        Event::Empty(elem)
+                    })?;
+                }
+                if is_start {
+                    depth += 1;
+                }
+            }
+            Event::End(ref e) => {
+                depth = depth.saturating_sub(1);
+                if e.local_name().as_ref() == b"Layout" {
+                    in_layout = false;
+                }
+                writer.write_event(event)?;
+            }
+            Event::Eof => break,
+            other => writer.write_event(other)?,
+        }
+    }
+
+    String::from_utf8(writer.into_inner()).context("Výsledné ALTO není UTF-8")
+}
+
+/// Převede ALTO soubor na místě na verzi `target`. Pokud výsledek
+/// neprojde kontrolou schématu, soubor se nepřepíše a vrátí se chyba.
+pub fn convert_file(path: &Path, target: AltoVersion) -> Result<()> {
+    let xml = fs::read_to_string(path)
+        .with_context(|| format!("Nelze načíst ALTO `{}`", path.display()))?;
+    let output = convert(&xml, target)
+        .with_context(|| format!("Převod ALTO `{}` selhal", path.display()))?;
+    let problems = validate(&output, target)?;
+    if !problems.is_empty() {
+        bail!(
+            "ALTO `{}` po převodu neodpovídá schématu {target}: {}",
+            path.display(),
+            summarize(&problems)
+        );
+    }
+    fs::write(path, output).with_context(|| format!("Nelze zapsat ALTO `{}`", path.display()))
+}
+
+/// Krok zpracování z ALTO 3.x/4.x čekající na zápis jako OCRProcessing (2.x)
+struct PendingStep {
+    id: String,
+    category: String,
+    events: Vec<Event<'static>>,
+}
+
+/// Převede ALTO na verzi `target`: jmenný prostor a schemaLocation,
+/// Processing → OCRProcessing (2.x), processingCategory (jen 4.x),
+/// fileIdentifier / documentIdentifier (od 2.1), LANG ↔ language (2.x)
+/// a BASELINE jako seznam bodů (od 4.2)
+pub fn convert(xml: &str, target: AltoVersion) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+    let mut depth = 0usize;
+    let mut skip = 0usize;
+    // Bílé znaky před prvkem se zapíší, jen pokud se prvek nezahodí
+    let mut pending: Option<Event<'static>> = None;
+    // Rozpracovaný Processing pro ALTO 2.x a hloubka, kde začal
+    let mut capture: Option<(PendingStep, usize)> = None;
+    let mut in_category = false;
+    let mut steps: Vec<PendingStep> = Vec::new();
+
+    loop {
+        let event = read(&mut reader)?.into_owned();
+        if skip > 0 {
+            match event {
+                Event::Start(_) => skip += 1,
+                Event::End(_) => {
+                    skip -= 1;
+                    if skip == 0 {
+                        in_category = false;
+                    }
+                }
+                Event::Text(ref t) if in_category => {
+                    if let Some((ref mut step, _)) = capture {
+                        step.category.push_str(t.decode()?.trim());
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Text(ref t) if t.iter().all(u8::is_ascii_whitespace) => {
+                if let Some(p) = pending.replace(event) {
+                    put(&mut writer, &mut capture, p)?;
+                }
+            }
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_start = matches!(event, Event::Start(_));
+                let name = e.local_name().as_ref().to_vec();
+                let dropped = match name.as_slice() {
+                    b"processingCategory" => {
+                        in_category = capture.is_some();
+                        !target.has_processing_category() || in_category
+                    }
+                    b"fileIdentifier" | b"documentIdentifier" => !target.has_file_identifier(),
+                    _ => false,
+                };
+                if dropped {
+                    pending = None;
+                    if is_start {
+                        skip = 1;
+                    } else {
+                        in_category = false;
+                    }
+                    continue;
+                }
+
+                if depth == 0 {
+                    flush(&mut writer, &mut capture, &mut pending)?;
+                    writer.write_event(root_event(e, target, is_start)?)?;
+                } else if name == b"Processing" && !target.has_processing() && capture.is_none() {
+                    pending = None;
+                    let step = PendingStep {
+                        id: attr(e, b"ID")?.unwrap_or_else(|| format!("PROC_{}", steps.len())),
+                        category: String::new(),
+                        events: Vec::new(),
+                    };
+                    if is_start {
+                        capture = Some((step, depth));
+                    } else {
+                        steps.push(step);
+                    }
+                } else {
+                    flush(&mut writer, &mut capture, &mut pending)?;
+                    let elem = version_attributes(e, target)?;
+                    put(
+                        &mut writer,
+                        &mut capture,
+                        if is_start { Event::Start(elem) } else { Event::Empty(elem) },
+                    )?;
+                }
+                if is_start {
+                    depth += 1;
+                }
+            }
+            Event::End(ref e) => {
+                depth = depth.saturating_sub(1);
+                if let Some((_, start)) = capture
+                    && start == depth
+                {
+                    flush(&mut writer, &mut capture, &mut pending)?;
+                    if let Some((step, _)) = capture.take() {
+                        steps.push(step);
+                    }
+                    continue;
+                }
+                if e.local_name().as_ref() == b"Description" && !steps.is_empty() {
+                    write_ocr_processing(&mut writer, std::mem::take(&mut steps))?;
+                }
+                flush(&mut writer, &mut capture, &mut pending)?;
+                put(&mut writer, &mut capture, event)?;
+            }
+            Event::Eof => break,
+            other => {
+                flush(&mut writer, &mut capture, &mut pending)?;
+                put(&mut writer, &mut capture, other)?;
+            }
+        }
+    }
+    flush(&mut writer, &mut capture, &mut pending)?;
+
+    String::from_utf8(writer.into_inner()).context("Výsledné ALTO není UTF-8")
+}
+
+/// Zapíše událost do výstupu, nebo do rozpracovaného Processing
+fn put(
+    writer: &mut Writer<Vec<u8>>,
+    capture: &mut Option<(PendingStep, usize)>,
+    event: Event<'static>,
+) -> Result<()> {
+    match capture {
+        Some((step, _)) => step.events.push(event),
+        None => writer.write_event(event)?,
+    }
+    Ok(())
+}
+
+fn flush(
+    writer: &mut Writer<Vec<u8>>,
+    capture: &mut Option<(PendingStep, usize)>,
+    pending: &mut Option<Event<'static>>,
+) -> Result<()> {
+    match pending.take() {
+        Some(event) => put(writer, capture, event),
+        None => Ok(()),
+    }
+}
+
+/// Kroky z Processing jako jeden OCRProcessing (ALTO 2.x): preOperation
+/// před OCR, první contentGeneration (jinak první krok) jako ocrProcessingStep,
+/// ostatní po něm
+fn write_ocr_processing(writer: &mut Writer<Vec<u8>>, mut steps: Vec<PendingStep>) -> Result<()> {
+    let ocr = steps
+        .iter()
+        .position(|s| s.category == "contentGeneration")
+        .or_else(|| steps.iter().position(|s| s.category != "preOperation"))
+        .unwrap_or(0);
+    let ocr_step = steps.remove(ocr);
+    let (pre, post): (Vec<_>, Vec<_>) = steps.into_iter().partition(|s| s.category == "preOperation");
+
+    let indent = |writer: &mut Writer<Vec<u8>>, n: usize| {
+        writer.get_mut().push(b'\n');
+        writer.get_mut().extend(std::iter::repeat_n(b'\t', n));
+    };
+    indent(writer, 2);
+    let mut block = BytesStart::new("OCRProcessing");
+    block.push_attribute(("ID", ocr_step.id.as_str()));
+    writer.write_event(Event::Start(block))?;
+    let ordered = pre
+        .into_iter()
+        .map(|s| ("preProcessingStep", s))
+        .chain(std::iter::once(("ocrProcessingStep", ocr_step)))
+        .chain(post.into_iter().map(|s| ("postProcessingStep", s)));
+    for (name, step) in ordered {
+        indent(writer, 3);
+        writer.write_event(Event::Start(BytesStart::new(name)))?;
+        for event in step.events {
+            match event {
+                // Obsah kroku je o úroveň hlouběji než v Processing
+                Event::Text(ref t) if t.iter().all(u8::is_ascii_whitespace) => {
+                    let text = String::from_utf8_lossy(t).replace('\n', "\n\t");
+                    writer.get_mut().extend_from_slice(text.as_bytes());
+                }
+                other => writer.write_event(other)?,
+            }
+        }
+        writer.write_event(Event::End(BytesEnd::new(name)))?;
+    }
+    indent(writer, 2);
+    writer.write_event(Event::End(BytesEnd::new("OCRProcessing")))?;
+    Ok(())
+}
+
+/// Atributy, které se mezi verzemi liší: LANG (3.x+) ↔ language (2.x),
+/// BASELINE jako body až od 4.2 (dřív jen svislá poloha)
+fn version_attributes(e: &BytesStart, target: AltoVersion) -> Result<BytesStart<'static>> {
+    let points_baseline = target >= AltoVersion { major: 4, minor: 2 };
+    let mut elem = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+    for a in e.attributes() {
+        let a = a?;
+        match a.key.as_ref() {
+            b"LANG" if target.major < 3 => {
+                elem.push_attribute(("language", a.unescape_value()?.as_ref()));
+            }
+            b"language" if target.major >= 3 => {
+                elem.push_attribute(("LANG", a.unescape_value()?.as_ref()));
+            }
+            b"BASELINE" if !points_baseline => {
+                let value = a.unescape_value()?;
+                // Průměrná svislá poloha bodů "x,y x,y …" / "x y x y …"
+                let numbers: Vec<f64> = value
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|t| !t.is_empty())
+                    .filter_map(|t| t.parse().ok())
+                    .collect();
+                if numbers.len() >= 2 {
+                    let ys: Vec<f64> = numbers.iter().skip(1).step_by(2).copied().collect();
+                    let y = ys.iter().sum::<f64>() / ys.len() as f64;
+                    elem.push_attribute(("BASELINE", format!("{}", y.round() as i64).as_str()));
+                } else {
+                    elem.push_attribute(a);
+                }
+            }
+            _ => elem.push_attribute(a),
+        }
+    }
+    Ok(elem)
+}
+
+fn read<'a>(reader: &mut Reader<&'a [u8]>) -> Result<Event<'a>> {
+    reader
+        .read_event()
+        .map_err(|e| anyhow!("Chybné XML na pozici {}: {e}", reader.error_position()))
+}
+
+/// Hodnota atributu (bez escapování)
+fn attr(e: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for a in e.attributes() {
+        let a = a?;
+        if a.key.local_name().as_ref() == name {
+            return Ok(Some(a.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Kořen `alto` s jmenným prostorem a schemaLocation požadované verze
+fn root_event(e: &BytesStart, version: AltoVersion, is_start: bool) -> Result<Event<'static>> {
+    let mut root = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+    root.push_attribute(("xmlns", version.namespace()));
+    let mut has_xsi = false;
+    for a in e.attributes() {
+        let a = a?;
+        match a.key.as_ref() {
+            b"xmlns" | b"xsi:schemaLocation" => {}
+            b"xmlns:xsi" => {
+                has_xsi = true;
+                root.push_attribute(a);
+            }
+            _ => root.push_attribute(a),
+        }
+    }
+    if !has_xsi {
+        root.push_attribute(("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"));
+    }
+    root.push_attribute(("xsi:schemaLocation", version.schema_location().as_str()));
+    Ok(if is_start {
+        Event::Start(root)
+    } else {
+        Event::Empty(root)
+    })
+}
+
+/// Prvek layoutu: nové ID, přepsané odkazy, převedené souřadnice
+fn layout_element(
+    e: &BytesStart,
+    ids: &mut impl Iterator<Item = String>,
+    renamed: &HashMap<String, String>,
+    factors: Option<(f64, f64)>,
+    index: &str,
+) -> Result<BytesStart<'static>> {
+    let local = e.local_name().as_ref().to_vec();
+    let new_id = match id_prefix(&local) {
+        Some(_) => Some(ids.next().context("Nesouhlasí počet prvků layoutu")?),
+        None => None,
+    };
+
+    let mut elem = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+    if let Some(ref id) = new_id {
+        elem.push_attribute(("ID", id.as_str()));
+    }
+    for a in e.attributes() {
+        let a = a?;
+        let key = a.key.as_ref();
+        if key == b"ID" && new_id.is_some() {
+            continue;
+        }
+        if key == b"PHYSICAL_IMG_NR" && local == b"Page" {
+            let nr = index.trim_start_matches('0');
+            elem.push_attribute(("PHYSICAL_IMG_NR", if nr.is_empty() { "0" } else { nr }));
+            continue;
+        }
+        if REF_ATTRS.contains(&key) {
+            let value = a.unescape_value()?;
+            let refs = value
+                .split_whitespace()
+                .map(|r| renamed.get(r).map_or(r, String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ");
+            elem.push_attribute((String::from_utf8_lossy(key).as_ref(), refs.as_str()));
+            continue;
+        }
+        if let Some((fx, fy)) = factors
+            && let Some(converted) = convert_coordinate(key, &a.unescape_value()?, fx, fy)
+        {
+            elem.push_attribute((String::from_utf8_lossy(key).as_ref(), converted.as_str()));
+            continue;
+        }
+        elem.push_attribute(a);
+    }
+    Ok(elem)
+}
+
+/// Převede souřadnicový atribut; None = atribut není souřadnice nebo není číslo
+fn convert_coordinate(key: &[u8], value: &str, fx: f64, fy: f64) -> Option<String> {
+    let scale = |v: &str, f: f64| -> Option<String> {
+        let v: f64 = v.trim().parse().ok()?;
+        Some(format!("{}", (v * f).round() as i64))
+    };
+    match key {
+        b"HPOS" | b"WIDTH" | b"HLENGTH" | b"RADIUS" => scale(value, fx),
+        b"VPOS" | b"HEIGHT" | b"VLENGTH" => scale(value, fy),
+        b"BASELINE" if !value.contains([',', ' ']) => scale(value, fy),
+        b"POINTS" | b"BASELINE" => {
+            // "x,y x,y …" nebo "x y x y …"
+            let mut out = Vec::new();
+            for (i, token) in value.split_whitespace().enumerate() {
+                match token.split_once(',') {
+                    Some((x, y)) => out.push(format!("{},{}", scale(x, fx)?, scale(y, fy)?)),
+                    None => out.push(scale(token, if i % 2 == 0 { fx } else { fy })?),
+                }
+            }
+            Some(out.join(" "))
+        }
+        _ => None,
+    }
+}
+
+/// Nový blok Description (odsazení tabulátory jako v Tesseractu)
+fn description(pp: &PostProcess, ocr_software: Option<&str>) -> String {
+    let now = Local::now().to_rfc3339_opts(SecondsFormat::Secs, false);
+    let (ocr_name, ocr_version) = match ocr_software {
+        Some(s) => match s.split_once(' ') {
+            Some((name, version)) => (name.to_string(), version.trim().to_string()),
+            None => (s.to_string(), String::new()),
+        },
+        None => ("tesseract".to_string(), String::new()),
+    };
+    let post_settings = format!(
+        "MeasurementUnit={}; ID=P{}_*; PHYSICAL_IMG_NR",
+        pp.unit.name(),
+        pp.index
+    );
+    let software = |indent: &str, name: &str, version: &str| {
+        let mut s = format!("{indent}<processingSoftware>\n");
+        s.push_str(&format!("{indent}\t<softwareName>{}</softwareName>\n", escape(name)));
+        if !version.is_empty() {
+            s.push_str(&format!("{indent}\t<softwareVersion>{}</softwareVersion>\n", escape(version)));
+        }
+        s.push_str(&format!("{indent}</processingSoftware>\n"));
+        s
+    };
+    // Obsah kroku zpracování (processingStepType); category jen v ALTO 4
+    let step = |indent: &str, category: &str, description: &str, settings: &str, name: &str, version: &str| {
+        let mut s = String::new();
+        if pp.version.has_processing_category() {
+            s.push_str(&format!("{indent}<processingCategory>{category}</processingCategory>\n"));
+        }
+        s.push_str(&format!("{indent}<processingDateTime>{now}</processingDateTime>\n"));
+        s.push_str(&format!(
+            "{indent}<processingStepDescription>{}</processingStepDescription>\n",
+            escape(description)
+        ));
+        s.push_str(&format!(
+            "{indent}<processingStepSettings>{}</processingStepSettings>\n",
+            escape(settings)
+        ));
+        s.push_str(&software(indent, name, version));
+        s
+    };
+    let post_name = "LazyProArcConvert";
+    let post_version = env!("CARGO_PKG_VERSION");
+
+    let mut s = String::from("<Description>\n");
+    s.push_str(&format!("\t\t<MeasurementUnit>{}</MeasurementUnit>\n", pp.unit.name()));
+    s.push_str("\t\t<sourceImageInformation>\n");
+    s.push_str(&format!("\t\t\t<fileName>{}</fileName>\n", escape(&pp.source_name)));
+    if pp.version.has_file_identifier() {
+        s.push_str(&format!(
+            "\t\t\t<fileIdentifier fileIdentifierLocation=\"BLAKE3\">{}</fileIdentifier>\n",
+            pp.source_blake3
+        ));
+    }
+    s.push_str("\t\t</sourceImageInformation>\n");
+    if pp.version.has_processing() {
+        s.push_str(&format!("\t\t<Processing ID=\"OCR_{}\">\n", pp.index));
+        s.push_str(&step("\t\t\t", "contentGeneration", "OCR", &pp.ocr_settings, &ocr_name, &ocr_version));
+        s.push_str("\t\t</Processing>\n");
+        s.push_str(&format!("\t\t<Processing ID=\"POST_{}\">\n", pp.index));
+        s.push_str(&step("\t\t\t", "postOperation", "ALTO post-processing", &post_settings, post_name, post_version));
+        s.push_str("\t\t</Processing>\n");
+    } else {
+        s.push_str(&format!("\t\t<OCRProcessing ID=\"OCR_{}\">\n", pp.index));
+        s.push_str("\t\t\t<ocrProcessingStep>\n");
+        s.push_str(&step("\t\t\t\t", "", "OCR", &pp.ocr_settings, &ocr_name, &ocr_version));
+        s.push_str("\t\t\t</ocrProcessingStep>\n");
+        s.push_str("\t\t\t<postProcessingStep>\n");
+        s.push_str(&step("\t\t\t\t", "", "ALTO post-processing", &post_settings, post_name, post_version));
+        s.push_str("\t\t\t</postProcessingStep>\n");
+        s.push_str("\t\t</OCRProcessing>\n");
+    }
+    s.push_str("\t</Description>");
+    s
+}
+
+/// Povolení potomci prvků layoutu
+fn layout_children(parent: &str) -> Option<&'static [&'static str]> {
+    const BLOCKS: &[&str] = &["TextBlock", "Illustration", "GraphicalElement", "ComposedBlock", "Shape"];
+    Some(match parent {
+        "Layout" => &["Page"],
+        "Page" => &["TopMargin", "LeftMargin", "RightMargin", "BottomMargin", "PrintSpace"],
+        "PrintSpace" | "TopMargin" | "LeftMargin" | "RightMargin" | "BottomMargin" | "ComposedBlock" => BLOCKS,
+        "TextBlock" => &["Shape", "TextLine"],
+        "TextLine" => &["Shape", "String", "SP", "HYP"],
+        "String" => &["Shape", "ALTERNATIVE", "Glyph"],
+        _ => return None,
+    })
+}
+
+/// Pořadí potomků Description podle schématu
+fn description_rank(name: &str) -> Option<usize> {
+    ["MeasurementUnit", "sourceImageInformation", "OCRProcessing", "Processing"]
+        .iter()
+        .position(|n| *n == name)
+}
+
+fn is_ncname(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Kontrola ALTO proti struktuře schématu dané verze (jmenný prostor,
+/// pořadí a vnoření prvků, povinné a číselné atributy, jedinečná ID
+/// a odkazy). Vrací seznam problémů; chyba = nejde o platné XML.
+pub fn validate(xml: &str, version: AltoVersion) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut problems = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut ids = HashSet::new();
+    let mut refs: Vec<(String, String)> = Vec::new();
+    let mut text = String::new();
+    let mut description_last = 0;
+    let mut root_children: Vec<String> = Vec::new();
+
+    loop {
+        let event = read(&mut reader)?;
+        let (e, is_start) = match event {
+            Event::Start(ref e) => (e, true),
+            Event::Empty(ref e) => (e, false),
+            Event::Text(ref t) => {
+                text.push_str(&t.decode()?);
+                continue;
+            }
+            Event::End(_) => {
+                if let Some(name) = stack.pop()
+                    && name == "MeasurementUnit"
+                    && AltoUnit::parse(&text).is_none()
+                {
+                    problems.push(format!("neplatná MeasurementUnit `{}`", text.trim()));
+                }
+                text.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        text.clear();
+
+        let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+        let mut attrs: HashMap<String, String> = HashMap::new();
+        for a in e.attributes() {
+            let a = a?;
+            attrs.insert(
+                String::from_utf8_lossy(a.key.as_ref()).into_owned(),
+                a.unescape_value()?.into_owned(),
+            );
+        }
+        let label = match attrs.get("ID") {
+            Some(id) => format!("{name} {id}"),
+            None => name.clone(),
+        };
+
+        match stack.last().map(String::as_str) {
+            None => {
+                if name != "alto" {
+                    problems.push(format!("kořenový prvek je `{name}`, ne `alto`"));
+                }
+                match attrs.get("xmlns") {
+                    Some(ns) if ns == version.namespace() => {}
+                    Some(ns) => problems.push(format!(
+                        "jmenný prostor `{ns}` neodpovídá ALTO {version} (`{}`)",
+                        version.namespace()
+                    )),
+                    None => problems.push("chybí jmenný prostor ALTO".to_string()),
+                }
+            }
+            Some("alto") => {
+                if !matches!(name.as_str(), "Description" | "Styles" | "Tags" | "Layout") {
+                    problems.push(format!("`{name}` nesmí být uvnitř `alto`"));
+                }
+                root_children.push(name.clone());
+            }
+            Some("Description") => match description_rank(&name) {
+                Some(rank) if rank < description_last => {
+                    problems.push(format!("`{name}` je v Description na špatném místě"))
+                }
+                Some(3) if !version.has_processing() => {
+                    problems.push(format!("Processing není v ALTO {version}"))
+                }
+                Some(rank) => description_last = rank,
+                None => problems.push(format!("`{name}` nesmí být uvnitř Description")),
+            },
+            Some("sourceImageInformation") => match name.as_str() {
+                "fileName" => {}
+                "fileIdentifier" | "documentIdentifier" if version.has_file_identifier() => {}
+                _ => problems.push(format!("`{name}` není v sourceImageInformation ALTO {version}")),
+            },
+            Some(parent) => {
+                if let Some(allowed) = layout_children(parent)
+                    && !allowed.contains(&name.as_str())
+                {
+                    problems.push(format!("`{name}` nesmí být uvnitř `{parent}`"));
+                }
+            }
+        }
+
+        if name == "processingCategory" && !version.has_processing_category() {
+            problems.push(format!("processingCategory není v ALTO {version}"));
+        }
+        if matches!(name.as_str(), "OCRProcessing" | "Processing") && !attrs.contains_key("ID") {
+            problems.push(format!("{name}: chybí ID"));
+        }
+        if name == "Page" {
+            match attrs.get("PHYSICAL_IMG_NR") {
+                Some(nr) if nr.parse::<u32>().is_ok() => {}
+                Some(nr) => problems.push(format!("{label}: PHYSICAL_IMG_NR `{nr}` není číslo")),
+                None => problems.push(format!("{label}: chybí PHYSICAL_IMG_NR")),
+            }
+            if !attrs.contains_key("ID") {
+                problems.push("Page: chybí ID".to_string());
+            }
+        }
+        if name == "String" && !attrs.contains_key("CONTENT") {
+            problems.push(format!("{label}: chybí CONTENT"));
+        }
+
+        for (key, value) in &attrs {
+            match key.as_str() {
+                "ID" => {
+                    if !is_ncname(value) {
+                        problems.push(format!("{label}: ID `{value}` není platné NCName"));
+                    } else if !ids.insert(value.clone()) {
+                        problems.push(format!("{label}: duplicitní ID"));
+                    }
+                }
+                "HPOS" | "VPOS" | "WIDTH" | "HEIGHT"
+                    if !value.trim().parse::<f64>().is_ok_and(|v| v.is_finite() && v >= 0.0) =>
+                {
+                    problems.push(format!("{label}: {key} `{value}` není nezáporné číslo"))
+                }
+                "WC" if !value.trim().parse::<f64>().is_ok_and(|v| (0.0..=1.0).contains(&v)) => {
+                    problems.push(format!("{label}: WC `{value}` není v rozsahu 0–1"))
+                }
+                "LANG" if version.major < 3 => {
+                    problems.push(format!("{label}: atribut LANG není v ALTO {version}"))
+                }
+                "language" if version.major >= 3 => {
+                    problems.push(format!("{label}: atribut language není v ALTO {version}"))
+                }
+                _ if REF_ATTRS.contains(&key.as_bytes()) => {
+                    for r in value.split_whitespace() {
+                        refs.push((format!("{label}: {key}"), r.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if is_start {
+            stack.push(name);
+        }
+    }
+
+    if root_children.first().map(String::as_str) != Some("Description") {
+        problems.push("Description musí být první prvek `alto`".to_string());
+    }
+    if !root_children.iter().any(|n| n == "Layout") {
+        problems.push("chybí Layout".to_string());
+    }
+    for (origin, id) in refs {
+        if !ids.contains(&id) {
+            problems.push(format!("{origin} odkazuje na neexistující ID `{id}`"));
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Zkrácený výstup Tesseractu (ALTO 3.0)
+    const TESSERACT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<alto xmlns="http://www.loc.gov/standards/alto/ns-v3#" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://www.loc.gov/standards/alto/ns-v3# http://www.loc.gov/alto/v3/alto-3-0.xsd">
+	<Description>
+		<MeasurementUnit>pixel</MeasurementUnit>
+		<sourceImageInformation>
+			<fileName>/tmp/page.tif</fileName>
+		</sourceImageInformation>
+		<OCRProcessing ID="OCR_0">
+			<ocrProcessingStep>
+				<processingSoftware>
+					<softwareName>tesseract</softwareName>
+					<softwareVersion>5.3.0</softwareVersion>
+				</processingSoftware>
+			</ocrProcessingStep>
+		</OCRProcessing>
+	</Description>
+	<Layout>
+		<Page WIDTH="1000" HEIGHT="2000" PHYSICAL_IMG_NR="0" ID="page_0">
+			<PrintSpace HPOS="0" VPOS="0" WIDTH="1000" HEIGHT="2000">
+				<TextBlock ID="block_0" HPOS="100" VPOS="200" WIDTH="300" HEIGHT="40" IDNEXT="block_1">
+					<TextLine ID="line_0" HPOS="100" VPOS="200" WIDTH="300" HEIGHT="40" LANG="ces">
+						<String ID="string_0" HPOS="100" VPOS="200" WIDTH="120" HEIGHT="40" WC="0.96" CONTENT="Kůň"/><SP WIDTH="10" VPOS="200" HPOS="220"/>
+						<String ID="string_1" HPOS="230" VPOS="200" WIDTH="170" HEIGHT="40" WC="0.91" CONTENT="&amp;c."/>
+					</TextLine>
+				</TextBlock>
+				<TextBlock ID="block_1" HPOS="100" VPOS="300" WIDTH="300" HEIGHT="40"/>
+			</PrintSpace>
+		</Page>
+	</Layout>
+</alto>
+"#;
+
+    fn version(text: &str) -> AltoVersion {
+        AltoVersion::parse(text).unwrap()
+    }
+
+    fn pp(target: &str, unit: AltoUnit, dpi: Option<(f64, f64)>) -> PostProcess<'static> {
+        PostProcess {
+            version: version(target),
+            index: "0003",
+            source_name: "page & co.tif".to_string(),
+            source_blake3: "ab".repeat(32),
+            unit,
+            dpi,
+            ocr_settings: "lang=ces".to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(version(" 4.4 "), AltoVersion { major: 4, minor: 4 });
+        assert_eq!(version("2.0").to_string(), "2.0");
+        for bad in ["4.5", "1.0", "5.0", "3", "x.y", "", "256.0"] {
+            assert!(AltoVersion::parse(bad).is_err(), "{bad}");
+        }
+        assert!(parse_version_arg("3.1").is_ok());
+    }
+
+    #[test]
+    fn postprocess_renames_ids_and_describes_source() {
+        for target in ["2.0", "2.1", "3.1", "4.4"] {
+            let out = postprocess(TESSERACT, &pp(target, AltoUnit::Pixel, None)).unwrap();
+            assert_eq!(validate(&out, version(target)).unwrap(), Vec::<String>::new(), "{target}");
+            assert!(out.contains(r#"<Page ID="P0003" WIDTH="1000" HEIGHT="2000" PHYSICAL_IMG_NR="3">"#));
+            assert!(out.contains(r#"ID="P0003_TB0001""#));
+            assert!(out.contains(r#"IDNEXT="P0003_TB0002""#));
+            assert!(out.contains(r#"ID="P0003_ST0002""#));
+            assert!(out.contains("<fileName>page &amp; co.tif</fileName>"));
+            assert!(out.contains("<softwareVersion>5.3.0</softwareVersion>"));
+            assert!(!out.contains("/tmp/page.tif"));
+            assert_eq!(out.contains("BLAKE3"), target != "2.0");
+            assert_eq!(out.contains("<Processing "), target.starts_with(['3', '4']));
+            assert_eq!(out.contains("<processingCategory>"), target.starts_with('4'));
+        }
+    }
+
+    #[test]
+    fn postprocess_converts_units() {
+        let out = postprocess(TESSERACT, &pp("4.4", AltoUnit::Mm10, Some((127.0, 254.0)))).unwrap();
+        assert!(out.contains("<MeasurementUnit>mm10</MeasurementUnit>"));
+        // 254 mm10 na palec: x ×2, y ×1
+        assert!(out.contains(r#"HPOS="200" VPOS="200" WIDTH="600" HEIGHT="40""#));
+        assert!(validate(&out, version("4.4")).unwrap().is_empty());
+
+        // Bez DPI nejde z pixelů převést
+        assert!(postprocess(TESSERACT, &pp("4.4", AltoUnit::Inch1200, None)).is_err());
+    }
+
+    #[test]
+    fn convert_between_versions() {
+        let v4 = postprocess(TESSERACT, &pp("4.4", AltoUnit::Pixel, None)).unwrap();
+
+        let v2 = convert(&v4, version("2.0")).unwrap();
+        assert!(validate(&v2, version("2.0")).unwrap().is_empty());
+        assert!(v2.contains("<OCRProcessing ID=\"OCR_0003\">"));
+        assert!(v2.contains("<postProcessingStep>"));
+        assert!(!v2.contains("<Processing ") && !v2.contains("processingCategory"));
+        assert!(!v2.contains("fileIdentifier"));
+        assert!(v2.contains(r#"language="ces""#) && !v2.contains("LANG="));
+
+        let back = convert(&v2, version("4.4")).unwrap();
+        assert!(validate(&back, version("4.4")).unwrap().is_empty());
+        assert!(back.contains(r#"LANG="ces""#));
+
+        // BASELINE jako body → svislá poloha (před 4.2)
+        let points = v4.replace(r#"LANG="ces""#, r#"BASELINE="100,230 400,240""#);
+        let v3 = convert(&points, version("3.1")).unwrap();
+        assert!(v3.contains(r#"BASELINE="235""#));
+        assert!(convert(&points, version("4.2")).unwrap().contains(r#"BASELINE="100,230 400,240""#));
+    }
+
+    #[test]
+    fn malformed_xml_is_an_error() {
+        let broken = TESSERACT.replace("</TextLine>", "</TextBlock>");
+        assert!(convert(&broken, version("4.4")).is_err());
+        assert!(validate(&broken, version("3.0")).is_err());
+        assert!(postprocess(&broken, &pp("4.4", AltoUnit::Pixel, None)).is_err());
+        assert!(validate("<alto><String CONTENT=\"a></alto>", version("3.0")).is_err());
+        let unit = TESSERACT.replace(">pixel<", ">furlong<");
+        assert!(postprocess(&unit, &pp("4.4", AltoUnit::Pixel, None)).is_err());
+    }
+
+    #[test]
+    fn validate_reports_problems() {
+        let v3 = version("3.0");
+        assert!(validate(TESSERACT, v3).unwrap().is_empty());
+        assert!(!validate(TESSERACT, version("4.0")).unwrap().is_empty());
+
+        let has = |xml: &str, needle: &str| {
+            let problems = validate(xml, v3).unwrap();
+            assert!(problems.iter().any(|p| p.contains(needle)), "{needle}: {problems:?}");
+        };
+        has(&TESSERACT.replace(r#"ID="line_0""#, r#"ID="block_1""#), "duplicitní ID");
+        has(&TESSERACT.replace(r#"WC="0.96""#, r#"WC="1.5""#), "WC `1.5`");
+        has(&TESSERACT.replace(r#"IDNEXT="block_1""#, r#"IDNEXT="block_9""#), "neexistující ID `block_9`");
+        has(&TESSERACT.replace(r#" CONTENT="Kůň""#, ""), "chybí CONTENT");
+        has(&TESSERACT.replace(r#"HPOS="230""#, r#"HPOS="-5""#), "HPOS `-5`");
+        has(&TESSERACT.replace(r#"ID="string_0""#, r#"ID="0bad""#), "NCName");
+        has(&TESSERACT.replace(r#"PHYSICAL_IMG_NR="0""#, r#"PHYSICAL_IMG_NR="x""#), "PHYSICAL_IMG_NR");
+        has(&TESSERACT.replace(">pixel<", ">furlong<"), "MeasurementUnit");
+        has(&TESSERACT.replace("<SP ", "<TextBlock "), "nesmí být uvnitř `TextLine`");
+        let start = TESSERACT.find("<Layout>").unwrap();
+        let end = TESSERACT.find("</Layout>").unwrap() + "</Layout>".len();
+        has(&format!("{}{}", &TESSERACT[..start], &TESSERACT[end..]), "chybí Layout");
+    }
+
+    #[test]
+    fn summarize_truncates() {
+        let problems: Vec<String> = (1..=7).map(|i| i.to_string()).collect();
+        assert_eq!(summarize(&problems[..2]), "1; 2");
+        assert_eq!(summarize(&problems), "1; 2; 3; 4; 5 … a 2 dalších");
+    }
+}
@@ -1,16 +1,30 @@
 use anyhow::{Context, Result};
+use chrono::Local;
 use clap::Parser;
 use std::{
+    collections::BTreeMap,
     fs,
     io::{Read, Write},
     net::{TcpListener, TcpStream},
     path::{Path, PathBuf},
     process::Command,
+    sync::Mutex,
     thread,
 };
 
-/// Malý statický HTTP server pro prohlížení HTML reportů (index.html)
-/// a souvisejících souborů (ALTO, TXT, WebP, JP2).
+use lazyproarcconvert::hashing::{compute_digests, with_blake3, HashAlgorithm};
+use lazyproarcconvert::outputs::find_logs_dirs;
+
+/// Největší přijaté tělo požadavku (upravené TXT / ALTO stránky)
+const MAX_BODY: usize = 64 * 1024 * 1024;
+
+/// Ukládání je sériové – manifest dávky se nesmí přepsat dvěma vlákny naráz
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Malý HTTP server pro prohlížení HTML reportů (index.html)
+/// a souvisejících souborů (ALTO, TXT, WebP, JP2). Z localhostu přijímá
+/// PUT/POST upravených `.ocr.txt` a `.ocr.xml`: původní verzi zálohuje
+/// do `*_logs/backup` a přepočítá hashe v manifest.json a checksums souborech.
 #[derive(Parser, Debug)]
 struct Args {
     /// Port, na kterém bude server poslouchat
@@ -22,6 +36,19 @@ struct Args {
     /// a pokud neexistuje, použije se adresář s binárkou.
     #[arg(long)]
     root: Option<String>,
+
+    /// Práh konfidence OCR (0–1) pro stránky ke kontrole; bez zadání
+    /// platí práh uložený v manifestu dávky (`--review-threshold` konvertoru)
+    #[arg(long, value_parser = parse_threshold)]
+    review_threshold: Option<f64>,
+}
+
+/// Práh 0–1 (stejná kontrola jako `quality::parse_threshold` konvertoru)
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(v) if (0.0..=1.0).contains(&v) => Ok(v),
+        _ => Err(format!("`{s}` není práh konfidence 0–1")),
+    }
 }
 
 fn main() -> Result<()> {
@@ -48,8 +75,10 @@ fn main() -> Result<()> {
         match stream {
             Ok(stream) => {
                 let root = root_dir.clone();
+                let threshold = args.review_threshold;
+                let port = args.port;
                 thread::spawn(move || {
-                    if let Err(e) = handle_client(stream, &root) {
+                    if let Err(e) = handle_client(stream, &root, threshold, port) {
                         eprintln!("Chyba při obsluze spojení: {e}");
                     }
                 });
@@ -89,35 +118,109 @@ fn resolve_root_dir(root_arg: Option<&str>) -> Result<PathBuf> {
     }
 }
 
-fn handle_client(mut stream: TcpStream, root_dir: &Path) -> Result<()> {
-    let mut buf = [0u8; 4096];
-    let n = stream.read(&mut buf)?;
-    if n == 0 {
-        return Ok(());
+/// Přijatý HTTP požadavek
+struct Request {
+    method: String,
+    path: String,
+    /// Hlavičky s názvy malými písmeny
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
     }
+}
 
-    let request = String::from_utf8_lossy(&buf[..n]);
-    let mut lines = request.lines();
+/// Načte hlavičky požadavku. None = spojení bez požadavku.
+fn read_request(stream: &mut TcpStream) -> Result<Option<Request>> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+    let header_end = loop {
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if data.len() > 64 * 1024 {
+            anyhow::bail!("Příliš dlouhé hlavičky požadavku");
+        }
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            if data.is_empty() {
+                return Ok(None);
+            }
+            break data.len();
+        }
+        data.extend_from_slice(&buf[..n]);
+    };
 
-    let first_line = match lines.next() {
-        Some(l) => l,
-        None => return Ok(()),
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut lines = head.lines();
+    let Some(first_line) = lines.next() else {
+        return Ok(None);
     };
 
     // Očekáváme něco jako: GET /cesta HTTP/1.1
     let mut parts = first_line.split_whitespace();
-    let method = parts.next().unwrap_or("");
-    let path = parts.next().unwrap_or("/");
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(n, v)| (n.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+
+    // Zbytek dat za hlavičkami je začátek těla, dočte ho až `read_body`
+    let body = data.split_off((header_end + 4).min(data.len()));
+
+    Ok(Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    }))
+}
 
-    if method != "GET" {
-        write_response(
-            &mut stream,
-            405,
-            "Method Not Allowed",
-            "text/plain; charset=utf-8",
-            b"Only GET is supported",
-        )?;
+/// Dočte tělo na `length` bajtů. false = klient ukončil spojení dřív.
+fn read_body(stream: &mut TcpStream, body: &mut Vec<u8>, length: usize) -> Result<bool> {
+    let mut buf = [0u8; 8192];
+    while body.len() < length {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            return Ok(false);
+        }
+        body.extend_from_slice(&buf[..n]);
+    }
+    body.truncate(length);
+    Ok(true)
+}
+
+fn handle_client(
+    mut stream: TcpStream,
+    root_dir: &Path,
+    threshold: Option<f64>,
+    port: u16,
+) -> Result<()> {
+    let Some(mut request) = read_request(&mut stream)? else {
         return Ok(());
+    };
+    let path = request.path.as_str();
+
+    match request.method.as_str() {
+        "GET" => {}
+        "PUT" | "POST" => return handle_save(&mut stream, root_dir, port, &mut request),
+        _ => {
+            write_response(
+                &mut stream,
+                405,
+                "Method Not Allowed",
+                "text/plain; charset=utf-8",
+                b"Only GET, PUT and POST are supported",
+            )?;
+            return Ok(());
+        }
     }
 
     // Jednoduchá ochrana proti .. v cestě
@@ -146,13 +249,13 @@ fn handle_client(mut stream: TcpStream, root_dir: &Path) -> Result<()> {
 
     // Pokud se jde na kořen "/", ukážeme stylizovaný index s *_logs adresáři
     let rel_path = if path == "/" {
-        return serve_root_index(&mut stream, root_dir);
+        return serve_root_index(&mut stream, root_dir, threshold);
     } else {
         &path[1..] // odřízneme počáteční '/'
     };
 
     // Převedeme URL na cestu v FS (forward slashes -> platform separator)
-    let rel_for_fs = rel_path.replace('/', &std::path::MAIN_SEPARATOR.to_string());
+    let rel_for_fs = rel_path.replace('/', std::path::MAIN_SEPARATOR_STR);
     let fs_path = root_dir.join(rel_for_fs);
 
     if fs_path.is_dir() {
@@ -181,8 +284,391 @@ fn handle_client(mut stream: TcpStream, root_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Uloží upravený `.ocr.txt` / `.ocr.xml` z editoru. Jen z localhostu
+/// (adresa klienta, Host i Origin), jen existující soubor uvnitř rootu,
+/// který patří dávce s manifestem.
+fn handle_save(stream: &mut TcpStream, root_dir: &Path, port: u16, request: &mut Request) -> Result<()> {
+    let forbidden = |stream: &mut TcpStream, message: &str| {
+        write_response(stream, 403, "Forbidden", "text/plain; charset=utf-8", message.as_bytes())
+    };
+
+    // Tělo musí mít přesně danou délku – chunked ani neúplný obsah se neukládá
+    if request.header("transfer-encoding").is_some() {
+        return write_response(
+            stream,
+            501,
+            "Not Implemented",
+            "text/plain; charset=utf-8",
+            "Transfer-Encoding není podporováno".as_bytes(),
+        );
+    }
+    let Some(length) = request.header("content-length") else {
+        return write_response(
+            stream,
+            411,
+            "Length Required",
+            "text/plain; charset=utf-8",
+            "Chybí Content-Length".as_bytes(),
+        );
+    };
+    let Ok(length) = length.parse::<usize>() else {
+        return write_response(
+            stream,
+            400,
+            "Bad Request",
+            "text/plain; charset=utf-8",
+            "Neplatný Content-Length".as_bytes(),
+        );
+    };
+    if length > MAX_BODY {
+        return write_response(
+            stream,
+            413,
+            "Payload Too Large",
+            "text/plain; charset=utf-8",
+            format!("Tělo požadavku je větší než {MAX_BODY} B").as_bytes(),
+        );
+    }
+    if !read_body(stream, &mut request.body, length)? {
+        return write_response(
+            stream,
+            400,
+            "Bad Request",
+            "text/plain; charset=utf-8",
+            "Neúplné tělo požadavku".as_bytes(),
+        );
+    }
+
+    if !stream.peer_addr()?.ip().is_loopback() {
+        return forbidden(stream, "Ukládat lze jen z localhostu");
+    }
+    // Host i Origin musí být tento server – jinak by mohla zapisovat cizí stránka
+    // otevřená v prohlížeči (CSRF, DNS rebinding)
+    let allowed_hosts = [
+        format!("localhost:{port}"),
+        format!("127.0.0.1:{port}"),
+        format!("[::1]:{port}"),
+    ];
+    let host_ok = request
+        .header("host")
+        .is_some_and(|h| allowed_hosts.iter().any(|a| a == h));
+    let origin_ok = request
+        .header("origin")
+        .is_none_or(|o| allowed_hosts.iter().any(|a| o == format!("http://{a}")));
+    if !host_ok || !origin_ok {
+        return forbidden(stream, "Požadavek nepochází ze stránky tohoto serveru");
+    }
+
+    let path = request.path.split('?').next().unwrap_or("");
+    if path.contains("..") || !(path.ends_with(".ocr.txt") || path.ends_with(".ocr.xml")) {
+        return forbidden(stream, "Ukládat lze jen soubory .ocr.txt a .ocr.xml");
+    }
+    let rel_for_fs = path.trim_start_matches('/').replace('/', std::path::MAIN_SEPARATOR_STR);
+    let fs_path = root_dir.join(rel_for_fs);
+    // Odkazy mimo root se nepřipouští
+    let inside_root = match (fs_path.canonicalize(), root_dir.canonicalize()) {
+        (Ok(file), Ok(root)) => file.starts_with(root) && file.is_file(),
+        _ => false,
+    };
+    if !inside_root {
+        write_response(
+            stream,
+            404,
+            "Not Found",
+            "text/plain; charset=utf-8",
+            b"Soubor neexistuje",
+        )?;
+        return Ok(());
+    }
+
+    if let Err(e) = check_content(&fs_path, &request.body) {
+        write_response(
+            stream,
+            422,
+            "Unprocessable Entity",
+            "text/plain; charset=utf-8",
+            format!("{e:#}").as_bytes(),
+        )?;
+        return Ok(());
+    }
+
+    let _guard = SAVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let Some((logs_dir, recorded)) = find_manifest_entry(&fs_path) else {
+        write_response(
+            stream,
+            409,
+            "Conflict",
+            "text/plain; charset=utf-8",
+            "Soubor nepatří žádné dávce s manifest.json".as_bytes(),
+        )?;
+        return Ok(());
+    };
+
+    match save_file(&fs_path, &logs_dir, &recorded, &request.body) {
+        Ok((blake3, backup)) => {
+            println!("Uloženo: {} (záloha {})", fs_path.display(), backup.display());
+            let body = serde_json::json!({
+                "saved": path,
+                "blake3": blake3,
+                "backup": backup.to_string_lossy(),
+            });
+            write_response(
+                stream,
+                200,
+                "OK",
+                "application/json; charset=utf-8",
+                body.to_string().as_bytes(),
+            )
+        }
+        Err(e) => {
+            eprintln!("Chyba při ukládání `{}`: {e:#}", fs_path.display());
+            write_response(
+                stream,
+                500,
+                "Internal Server Error",
+                "text/plain; charset=utf-8",
+                format!("{e:#}").as_bytes(),
+            )
+        }
+    }
+}
+
+/// TXT musí být UTF-8, ALTO navíc well-formed XML
+fn check_content(path: &Path, body: &[u8]) -> Result<()> {
+    let text = std::str::from_utf8(body).context("Text není v UTF-8")?;
+    if path.extension().is_some_and(|e| e == "xml") {
+        let mut reader = quick_xml::Reader::from_str(text);
+        loop {
+            match reader.read_event() {
+                Ok(quick_xml::events::Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => anyhow::bail!("Chybné XML na pozici {}: {e}", reader.error_position()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `*_logs` adresář dávky, jejíž manifest obsahuje `file`, a cesta souboru
+/// zapsaná v manifestu. Logy leží vedle výstupního adresáře (`x` → `x_logs`),
+/// u dávky v kořeni vstupu uvnitř něj.
+fn find_manifest_entry(file: &Path) -> Option<(PathBuf, String)> {
+    let dir = file.parent()?;
+    let name = file.file_name()?;
+    let dir_canonical = dir.canonicalize().ok()?;
+
+    let mut candidates = Vec::new();
+    if let (Some(parent), Some(dir_name)) = (dir.parent(), dir.file_name()) {
+        candidates.push(parent.join(format!("{}_logs", dir_name.to_string_lossy())));
+    }
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().ends_with("_logs") {
+                candidates.push(entry.path());
+            }
+        }
+    }
+
+    for logs_dir in candidates {
+        let Ok(text) = fs::read_to_string(logs_dir.join("manifest.json")) else {
+            continue;
+        };
+        let Ok(manifest) = serde_json::from_str::<serde_json::Value>(&text) else {
+            continue;
+        };
+        // Výstupní adresář v manifestu přeneseme k současnému umístění logů
+        // (output root mohl být mezitím přesunut)
+        let output_dir = Path::new(manifest["output_dir"].as_str().unwrap_or(""));
+        let original_root = Path::new(manifest["logs_dir"].as_str().unwrap_or(""))
+            .parent()
+            .unwrap_or(Path::new(""));
+        let current_root = logs_dir.parent().unwrap_or(Path::new(""));
+        let output_dir = match output_dir.strip_prefix(original_root) {
+            Ok(rel) => current_root.join(rel),
+            Err(_) => output_dir.to_path_buf(),
+        };
+        if output_dir.canonicalize().ok().as_ref() != Some(&dir_canonical) {
+            continue;
+        }
+
+        let recorded = manifest["pages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|page| [&page["txt"], &page["alto"]])
+            .filter_map(|f| f["path"].as_str())
+            .find(|p| Path::new(p).file_name() == Some(name));
+        if let Some(recorded) = recorded {
+            return Some((logs_dir, recorded.to_string()));
+        }
+    }
+    None
+}
+
+/// Zálohuje původní soubor, zapíše nový a přepočítá jeho hashe v manifest.json
+/// a checksums souborech; zastaralou kvalitu stránky z manifestu i reportu
+/// odebere. Vrací nový BLAKE3 a cestu zálohy.
+fn save_file(path: &Path, logs_dir: &Path, recorded: &str, body: &[u8]) -> Result<(String, PathBuf)> {
+    let backup_dir = logs_dir.join("backup");
+    fs::create_dir_all(&backup_dir)
+        .with_context(|| format!("Nelze vytvořit `{}`", backup_dir.display()))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let backup = backup_dir.join(format!("{name}.{}", Local::now().format("%Y%m%d-%H%M%S%.3f")));
+    fs::copy(path, &backup)
+        .with_context(|| format!("Nelze zálohovat `{}`", path.display()))?;
+
+    write_replace(path, body)?;
+
+    let manifest_path = logs_dir.join("manifest.json");
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Nelze načíst `{}`", manifest_path.display()))?;
+    let mut manifest: serde_json::Value = serde_json::from_str(&text)
+        .with_context(|| format!("Nelze parsovat `{}`", manifest_path.display()))?;
+    let selected: Vec<HashAlgorithm> =
+        serde_json::from_value(manifest["checksum_algorithms"].clone()).unwrap_or_default();
+    let algorithms = with_blake3(&selected);
+    let digests = compute_digests(path, &algorithms)?;
+    let blake3 = digests[&HashAlgorithm::Blake3].clone();
+
+    let size = fs::metadata(path)?.len();
+    update_file_info(&mut manifest, recorded, size, &digests);
+    // Kvalitu spočítanou z původního souboru nelze přepočítat (bez slovníků
+    // a Tesseractu), proto ji u stránky zahodíme
+    let stale = if path.extension().is_some_and(|e| e == "xml") {
+        ("alto", "ocr_confidence", "confidence")
+    } else {
+        ("txt", "dictionary", "dictionary")
+    };
+    for page in manifest["pages"].as_array_mut().into_iter().flatten() {
+        if page[stale.0]["path"].as_str() == Some(recorded) {
+            page[stale.1] = serde_json::Value::Null;
+        }
+    }
+    write_replace(&manifest_path, serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    for algorithm in algorithms {
+        let checksums_path = logs_dir.join(algorithm.checksum_file_name());
+        let Ok(text) = fs::read_to_string(&checksums_path) else {
+            continue;
+        };
+        let mut checksums = String::new();
+        for line in text.lines() {
+            match line.split_once("  ") {
+                Some((_, file)) if file.trim() == recorded => {
+                    checksums.push_str(&format!("{}  {file}\n", digests[&algorithm]));
+                }
+                _ => {
+                    checksums.push_str(line);
+                    checksums.push('\n');
+                }
+            }
+        }
+        write_replace(&checksums_path, checksums.as_bytes())?;
+    }
+
+    let index_path = logs_dir.join("index.html");
+    if let Ok(html) = fs::read_to_string(&index_path) {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned());
+        if let Some(html) = drop_report_quality(&html, file_name.as_deref().unwrap_or(""), stale.2) {
+            write_replace(&index_path, html.as_bytes())?;
+        }
+    }
+
+    Ok((blake3, backup))
+}
+
+/// Vynuluje `key` u stránky s upraveným souborem v MANIFEST vloženém do
+/// index.html. None = report manifest neobsahuje nebo se nic nezměnilo.
+fn drop_report_quality(html: &str, file_name: &str, key: &str) -> Option<String> {
+    const PREFIX: &str = "const MANIFEST = ";
+    let start = html.find(PREFIX)? + PREFIX.len();
+    let end = start + html[start..].find(";\n")?;
+    let mut manifest: serde_json::Value = serde_json::from_str(&html[start..end]).ok()?;
+    let mut changed = false;
+    for page in manifest["pages"].as_array_mut()? {
+        let names = [&page["txtName"], &page["altoName"]];
+        if names.iter().any(|n| n.as_str() == Some(file_name)) && !page[key].is_null() {
+            page[key] = serde_json::Value::Null;
+            changed = true;
+        }
+    }
+    changed.then(|| format!("{}{}{}", &html[..start], manifest, &html[end..]))
+}
+
+/// Nejprve dočasný soubor, pak přejmenování – rozepsaný soubor nikdy nezůstane
+fn write_replace(path: &Path, contents: &[u8]) -> Result<()> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!("{name}.tmp"));
+    fs::write(&tmp, contents).with_context(|| format!("Nelze zapsat `{}`", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Nelze přepsat `{}`", path.display()))
+}
+
+/// Přepíše velikost a hashe v záznamech souboru (objekt s `path` a `blake3`)
+fn update_file_info(
+    value: &mut serde_json::Value,
+    recorded: &str,
+    size: u64,
+    digests: &BTreeMap<HashAlgorithm, String>,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.contains_key("blake3") && map.get("path").and_then(|p| p.as_str()) == Some(recorded) {
+                map.insert("size".into(), size.into());
+                map.insert("blake3".into(), digests[&HashAlgorithm::Blake3].clone().into());
+                if map.contains_key("digests") {
+                    map.insert("digests".into(), serde_json::json!(digests));
+                }
+                return;
+            }
+            for v in map.values_mut() {
+                update_file_info(v, recorded, size, digests);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for v in items {
+                update_file_info(v, recorded, size, digests);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Stránky dávky k ruční kontrole podle konfidence OCR v manifest.json:
+/// (index, průměrná konfidence). Bez `threshold` rozhoduje příznak
+/// `needs_review` uložený konvertorem.
+fn review_pages(logs_dir: &Path, threshold: Option<f64>) -> Vec<(String, f64)> {
+    let Ok(text) = fs::read_to_string(logs_dir.join("manifest.json")) else {
+        return Vec::new();
+    };
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return Vec::new();
+    };
+    let Some(pages) = manifest["pages"].as_array() else {
+        return Vec::new();
+    };
+    pages
+        .iter()
+        .filter_map(|page| {
+            let confidence = page.get("ocr_confidence")?;
+            let mean = confidence["mean"].as_f64()?;
+            let words = confidence["words"].as_u64().unwrap_or(0);
+            let flagged = match threshold {
+                Some(t) => words > 0 && mean < t,
+                None => confidence["needs_review"].as_bool().unwrap_or(false),
+            };
+            flagged.then(|| (page["index"].as_str().unwrap_or("?").to_string(), mean))
+        })
+        .collect()
+}
+
 /// Stylizovaná homepage na / s výčtem *_logs adresářů
-fn serve_root_index(stream: &mut TcpStream, root_dir: &Path) -> Result<()> {
+fn serve_root_index(stream: &mut TcpStream, root_dir: &Path, threshold: Option<f64>) -> Result<()> {
     let mut html = String::new();
 
     html.push_str(
@@ -334,6 +820,31 @@ fn serve_root_index(stream: &mut TcpStream, root_dir: &Path) -> Result<()> {
       color: var(--accent);
     }
 
+    .badge.warn {
+      background: rgba(255,107,107,0.08);
+      border-color: rgba(255,107,107,0.4);
+      color: var(--danger);
+      margin-top: 4px;
+    }
+
+    .review-pages {
+      display: flex;
+      flex-wrap: wrap;
+      gap: 4px;
+      margin-top: 4px;
+      font-size: 11px;
+    }
+
+    .review-pages a {
+      color: var(--danger);
+      text-decoration: none;
+      font-variant-numeric: tabular-nums;
+    }
+
+    .review-pages a:hover {
+      text-decoration: underline;
+    }
+
     .no-logs {
       font-size: 13px;
       color: var(--text-muted);
@@ -369,29 +880,26 @@ fn serve_root_index(stream: &mut TcpStream, root_dir: &Path) -> Result<()> {
     <p class="intro">
       Vyber dávku (<code>*_logs</code>) pro otevření interaktivního ALTO náhledu
       a editoru. Stránka s náhledem je generovaná pro každý batch jako <code>index.html</code>.
+      Uložené úpravy TXT a ALTO se zapíší zpět do výstupního adresáře, původní verze
+      se zálohuje do <code>*_logs/backup</code>.
     </p>
 "#,
     );
 
-    // seznam *_logs
+    // seznam *_logs (i vnořených dávek titul/rok/číslo), cesty od rootu s `/`
     let mut any_logs = false;
 
     html.push_str(r#"<ul class="logs-list">"#);
-    if let Ok(entries) = fs::read_dir(root_dir) {
-        let mut names: Vec<String> = Vec::new();
-        for entry in entries.flatten() {
-            if let Ok(ft) = entry.file_type() {
-                if ft.is_dir() {
-                    let name = entry.file_name().to_string_lossy().to_string();
-                    if name.ends_with("_logs") {
-                        names.push(name);
-                    }
-                }
-            }
-        }
-        names.sort();
-
-        for name in names {
+    if let Ok(logs_dirs) = find_logs_dirs(root_dir) {
+        for logs_dir in logs_dirs {
+            let Ok(rel) = logs_dir.strip_prefix(root_dir) else {
+                continue;
+            };
+            let name = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
             any_logs = true;
             // odřízneme suffix _logs pro "batch" jméno
             let batch_label = name.trim_end_matches("_logs");
@@ -403,7 +911,31 @@ fn serve_root_index(stream: &mut TcpStream, root_dir: &Path) -> Result<()> {
             html.push_str(r#"</div>"#);
             html.push_str(r#"<div class="logs-item-sub">"#);
             html.push_str(&html_escape(&name));
-            html.push_str(r#"</div></a></li>"#);
+            html.push_str(r#"</div>"#);
+
+            // Stránky s nízkou konfidencí OCR k ruční kontrole
+            let review = review_pages(&logs_dir, threshold);
+            if !review.is_empty() {
+                html.push_str(&format!(
+                    r#"<span class="badge warn">{} stránek ke kontrole</span>"#,
+                    review.len()
+                ));
+            }
+            html.push_str(r#"</a>"#);
+            if !review.is_empty() {
+                html.push_str(r#"<div class="review-pages">"#);
+                for (index, mean) in &review {
+                    html.push_str(&format!(
+                        r#"<a href="/{}/index.html#page={}" title="průměrná konfidence {:.1} %">{}</a>"#,
+                        html_escape(&name),
+                        html_escape(index),
+                        mean * 100.0,
+                        html_escape(index)
+                    ));
+                }
+                html.push_str(r#"</div>"#);
+            }
+            html.push_str(r#"</li>"#);
         }
     }
     html.push_str("</ul>");
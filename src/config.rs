@@ -0,0 +1,365 @@
+// src/config.rs
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+
+use crate::alto::{AltoUnit, AltoVersion};
+use crate::hashing::HashAlgorithm;
+use crate::numbering::NumberingMode;
+use crate::preflight::PreflightRules;
+use crate::Args;
+
+/// Konfigurace projektu v pracovním adresáři
+pub const WORKSPACE_CONFIG_FILE: &str = "lazyproarcconvert.json";
+
+const USER_CONFIG_DIR: &str = "lazyproarcconvert";
+const USER_CONFIG_FILE: &str = "config.json";
+
+/// Uložené nastavení. Chybějící položka = výchozí hodnota / hodnota
+/// z konfigurace s nižší prioritou.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numbering: Option<NumberingMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digits: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alto_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alto_unit: Option<AltoUnit>,
+    /// Výchozí nastavení Tesseractu (dávka může mít vlastní v ocr_settings.json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub psm: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oem: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_words: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_patterns: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tess_vars: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_master: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_user: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_txt: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_alto: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_hocr: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_pdf: Option<bool>,
+    /// Spojit PDF stránek do jednoho PDF za dávku
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_pdf: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grok_bin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tess_bin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tessdata_dir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub force_local_tess: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grok_jobs: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tess_jobs: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksums: Option<Vec<HashAlgorithm>>,
+    /// Práh konfidence OCR pro ruční kontrolu stránek (0–1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_threshold: Option<f64>,
+    /// Práh shody se slovníkem pro filtr stránek v TUI (0–1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_threshold: Option<f64>,
+    /// Slovníky pro odhad kvality OCR: kód jazyka Tesseractu → soubor se slovy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlists: Option<BTreeMap<String, PathBuf>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preflight: Option<PreflightRules>,
+}
+
+/// Načtená konfigurace + odkud pochází a kam se ukládá
+pub struct LoadedConfig {
+    pub config: Config,
+    pub sources: Vec<PathBuf>,
+    pub save_path: PathBuf,
+}
+
+/// Uživatelská konfigurace: %APPDATA%\lazyproarcconvert\config.json,
+/// jinak $XDG_CONFIG_HOME nebo ~/.config
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = env::var_os("APPDATA")
+        .or_else(|| env::var_os("XDG_CONFIG_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join(USER_CONFIG_DIR).join(USER_CONFIG_FILE))
+}
+
+/// Načte uživatelskou a projektovou konfiguraci (projektová má přednost).
+/// `explicit` (--config) nahrazuje projektovou konfiguraci a je i cílem uložení.
+pub fn load_config(explicit: Option<&Path>) -> Result<LoadedConfig> {
+    let save_path = explicit
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(WORKSPACE_CONFIG_FILE));
+    if explicit.is_some() && !save_path.exists() {
+        bail!("Konfigurační soubor `{}` neexistuje", save_path.display());
+    }
+    load_layers(user_config_path(), save_path)
+}
+
+/// Sloučí uživatelskou konfiguraci s konfigurací projektu `save_path`
+/// (chybějící soubor se přeskočí)
+fn load_layers(user_path: Option<PathBuf>, save_path: PathBuf) -> Result<LoadedConfig> {
+    let mut config = Config::default();
+    let mut sources = Vec::new();
+
+    if let Some(user_path) = user_path
+        && user_path.exists()
+    {
+        config.merge(Config::load(&user_path)?);
+        sources.push(user_path);
+    }
+
+    if save_path.exists() {
+        config.merge(Config::load(&save_path)?);
+        sources.push(save_path.clone());
+    }
+
+    Ok(LoadedConfig {
+        config,
+        sources,
+        save_path,
+    })
+}
+
+/// Pro každé `pole: převod` přepíše `args.pole` převedenou hodnotou
+/// z konfigurace, pokud ji nezadala příkazová řádka (id volby = název pole)
+macro_rules! apply_fields {
+    ($config:expr, $args:expr, $matches:expr; $($field:ident: $convert:expr),* $(,)?) => {
+        $(
+            if let Some(v) = &$config.$field
+                && $matches.value_source(stringify!($field)) != Some(ValueSource::CommandLine)
+            {
+                $args.$field = ($convert)(v)?;
+            }
+        )*
+    };
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Nelze načíst konfiguraci `{}`", path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("Neplatná konfigurace `{}`", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+            .with_context(|| format!("Nelze zapsat konfiguraci `{}`", path.display()))
+    }
+
+    /// Překryje hodnoty tohoto nastavení hodnotami z `other`
+    fn merge(&mut self, other: Config) {
+        overlay(&mut self.input, other.input);
+        overlay(&mut self.output, other.output);
+        overlay(&mut self.recursive, other.recursive);
+        overlay(&mut self.max_depth, other.max_depth);
+        overlay(&mut self.start_index, other.start_index);
+        overlay(&mut self.numbering, other.numbering);
+        overlay(&mut self.digits, other.digits);
+        overlay(&mut self.lang, other.lang);
+        overlay(&mut self.alto_version, other.alto_version);
+        overlay(&mut self.alto_unit, other.alto_unit);
+        overlay(&mut self.psm, other.psm);
+        overlay(&mut self.oem, other.oem);
+        overlay(&mut self.dpi, other.dpi);
+        overlay(&mut self.user_words, other.user_words);
+        overlay(&mut self.user_patterns, other.user_patterns);
+        overlay(&mut self.tess_vars, other.tess_vars);
+        overlay(&mut self.do_master, other.do_master);
+        overlay(&mut self.do_user, other.do_user);
+        overlay(&mut self.do_txt, other.do_txt);
+        overlay(&mut self.do_alto, other.do_alto);
+        overlay(&mut self.do_hocr, other.do_hocr);
+        overlay(&mut self.do_pdf, other.do_pdf);
+        overlay(&mut self.merge_pdf, other.merge_pdf);
+        overlay(&mut self.grok_bin, other.grok_bin);
+        overlay(&mut self.tess_bin, other.tess_bin);
+        overlay(&mut self.tessdata_dir, other.tessdata_dir);
+        overlay(&mut self.force_local_tess, other.force_local_tess);
+        overlay(&mut self.jobs, other.jobs);
+        overlay(&mut self.grok_jobs, other.grok_jobs);
+        overlay(&mut self.tess_jobs, other.tess_jobs);
+        overlay(&mut self.profiles, other.profiles);
+        overlay(&mut self.profile, other.profile);
+        overlay(&mut self.checksums, other.checksums);
+        overlay(&mut self.review_threshold, other.review_threshold);
+        overlay(&mut self.dictionary_threshold, other.dictionary_threshold);
+        overlay(&mut self.wordlists, other.wordlists);
+        overlay(&mut self.preflight, other.preflight);
+    }
+
+    /// Přenese nastavení do `args`, pokud hodnota nebyla zadána na příkazové řádce
+    pub fn apply_to_args(&self, args: &mut Args, matches: &ArgMatches) -> Result<()> {
+        apply_fields!(self, args, matches;
+            input: some,
+            output: some,
+            recursive: same,
+            max_depth: some,
+            start_index: same,
+            numbering: same,
+            digits: same,
+            lang: same,
+            alto_version: checked_alto_version,
+            alto_unit: some,
+            psm: some,
+            oem: some,
+            dpi: some,
+            user_words: some,
+            user_patterns: some,
+            tess_vars: pairs,
+            grok_bin: same,
+            tess_bin: same,
+            tessdata_dir: some,
+            force_local_tess: same,
+            jobs: some,
+            grok_jobs: some,
+            tess_jobs: some,
+            profiles: some,
+            profile: same,
+            checksums: same,
+            review_threshold: |v: &f64| unit_interval("review_threshold", *v),
+            dictionary_threshold: |v: &f64| unit_interval("dictionary_threshold", *v),
+            wordlists: pairs,
+        );
+        // Pravidla kontroly TIFFů nemají volbu na příkazové řádce
+        if let Some(ref v) = self.preflight {
+            args.preflight = v.clone();
+        }
+        Ok(())
+    }
+}
+
+fn same<T: Clone>(v: &T) -> Result<T> {
+    Ok(v.clone())
+}
+
+fn some<T: Clone>(v: &T) -> Result<Option<T>> {
+    Ok(Some(v.clone()))
+}
+
+fn pairs<V: Clone>(v: &BTreeMap<String, V>) -> Result<Vec<(String, V)>> {
+    Ok(v.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+}
+
+fn checked_alto_version(v: &str) -> Result<String> {
+    AltoVersion::parse(v).context("Neplatné `alto_version` v konfiguraci")?;
+    Ok(v.to_string())
+}
+
+fn unit_interval(name: &str, v: f64) -> Result<f64> {
+    if !(0.0..=1.0).contains(&v) {
+        bail!("Neplatné `{name}` v konfiguraci: {v} není 0–1");
+    }
+    Ok(v)
+}
+
+/// Hodnota z konfigurace s vyšší prioritou přepíše nižší
+fn overlay<T>(dst: &mut Option<T>, src: Option<T>) {
+    if src.is_some() {
+        *dst = src;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("lazyproarc-{}-{name}", std::process::id()))
+    }
+
+    fn args_from(config: &Config, cli: &[&str]) -> Args {
+        let matches = Args::command().get_matches_from(cli);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply_to_args(&mut args, &matches).unwrap();
+        args
+    }
+
+    #[test]
+    fn workspace_config_overrides_user_config() {
+        let user = temp_path("config-user.json");
+        let workspace = temp_path("config-workspace.json");
+        fs::write(&user, r#"{ "lang": "ces", "digits": 5, "jobs": 2 }"#).unwrap();
+        fs::write(&workspace, r#"{ "lang": "eng", "jobs": 3 }"#).unwrap();
+        let loaded = load_layers(Some(user.clone()), workspace.clone());
+        fs::remove_file(&user).unwrap();
+        fs::remove_file(&workspace).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.sources, vec![user, workspace]);
+
+        let args = args_from(&loaded.config, &["lazyproarcconvert"]);
+        assert_eq!(args.lang, "eng");
+        assert_eq!(args.jobs, Some(3));
+        assert_eq!(args.digits, 5);
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        let config = Config {
+            lang: Some("eng".to_string()),
+            dpi: Some(300),
+            ..Config::default()
+        };
+        let args = args_from(&config, &["lazyproarcconvert", "--lang", "deu"]);
+        assert_eq!(args.lang, "deu");
+        assert_eq!(args.dpi, Some(300));
+    }
+
+    #[test]
+    fn invalid_threshold_is_rejected() {
+        let config = Config {
+            review_threshold: Some(1.5),
+            ..Config::default()
+        };
+        let matches = Args::command().get_matches_from(["lazyproarcconvert"]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        assert!(config.apply_to_args(&mut args, &matches).is_err());
+    }
+}
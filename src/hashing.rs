@@ -0,0 +1,126 @@
+// src/hashing.rs
+use std::collections::BTreeMap;
+use std::{fs::File, io::Read, path::Path};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+/// Podporované algoritmy kontrolních součtů
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Soubor s kontrolními součty v `*_logs`. BLAKE3 zůstává v checksums.txt,
+    /// ostatní algoritmy mají vlastní soubor (checksums-md5.txt, …).
+    pub fn checksum_file_name(self) -> String {
+        match self {
+            HashAlgorithm::Blake3 => "checksums.txt".to_string(),
+            other => format!("checksums-{}.txt", other.name()),
+        }
+    }
+}
+
+/// Vybrané algoritmy + BLAKE3 (ten se počítá vždy), seřazené a bez duplicit
+pub fn with_blake3(selected: &[HashAlgorithm]) -> Vec<HashAlgorithm> {
+    let mut algorithms = selected.to_vec();
+    algorithms.push(HashAlgorithm::Blake3);
+    algorithms.sort();
+    algorithms.dedup();
+    algorithms
+}
+
+/// Rozpracovaný hash jednoho algoritmu
+enum Hasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+            HashAlgorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Md5(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha1(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha256(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha512(h) => format!("{:x}", h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Spočítá hashe zvolených algoritmů jedním průchodem souborem
+pub fn compute_digests(
+    path: &Path,
+    algorithms: &[HashAlgorithm],
+) -> Result<BTreeMap<HashAlgorithm, String>> {
+    let mut file = File::open(path)
+        .with_context(|| format!("Nelze otevřít `{}` pro výpočet hashe", path.display()))?;
+
+    let mut hashers: Vec<(HashAlgorithm, Hasher)> = algorithms
+        .iter()
+        .map(|&a| (a, Hasher::new(a)))
+        .collect();
+    let mut buf = [0u8; 65536];
+
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for (_, hasher) in hashers.iter_mut() {
+            hasher.update(&buf[..n]);
+        }
+    }
+
+    Ok(hashers
+        .into_iter()
+        .map(|(a, h)| (a, h.finalize_hex()))
+        .collect())
+}
@@ -0,0 +1,678 @@
+// src/headless.rs
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use serde_json::json;
+
+use crate::alto::{self, AltoVersion};
+use crate::html::write_html_report;
+use crate::outputs::find_logs_dirs;
+use crate::manifest::refresh_manifest_files;
+use crate::previews::generate_webp_previews;
+use crate::scheduler::capped_limits_warning;
+use crate::verify::{manifest_outputs, verify_batch, write_verify_report, FileStatus};
+use crate::worker::{JobControl, JobLog, Worker, WorkerEvent};
+use crate::{
+    batch_logs_dir, batch_name, build_job_request, create_roots, detect_tools,
+    init_jobs_from_dirs, job_display_name, load_batch_ocr, set_batch_ocr,
+    update_jobs_status_on_start, Args, BatchJob, JobStatus, ToolStatus,
+};
+
+/// Vše v pořádku
+pub const EXIT_OK: i32 = 0;
+/// Některé dávky selhaly, byly zrušeny nebo neprošly kontrolou
+pub const EXIT_PARTIAL: i32 = 3;
+/// Chybí / nefunguje Grok nebo Tesseract
+pub const EXIT_TOOL_ERROR: i32 = 4;
+
+/// Příkazy pro běh bez TUI (plánovač, SSH, testy).
+/// Návratové kódy: 0 = OK, 1 = chyba programu / konfigurace, 2 = chybné argumenty,
+/// 3 = některé dávky selhaly / neprošly kontrolou, 4 = chybí nástroj (Grok / Tesseract).
+#[derive(Subcommand, Debug, Clone)]
+pub enum CliCommand {
+    /// Zpracuje dávky bez TUI (čekající, nebo vybrané přes --batch)
+    Convert {
+        /// Zpracovat jen dávku s tímto názvem adresáře (lze opakovat)
+        #[arg(long = "batch")]
+        batches: Vec<String>,
+        /// Zpracovat znovu i hotové dávky, bez navazování na předchozí běh
+        #[arg(long)]
+        force: bool,
+        /// Nevytvářet AC (master) JP2
+        #[arg(long)]
+        no_master: bool,
+        /// Nevytvářet UC (user) JP2
+        #[arg(long)]
+        no_user: bool,
+        /// Nevytvářet TXT
+        #[arg(long)]
+        no_txt: bool,
+        /// Nevytvářet ALTO
+        #[arg(long)]
+        no_alto: bool,
+        /// Vytvářet hOCR (i když není zapnuto v konfiguraci)
+        #[arg(long)]
+        hocr: bool,
+        /// Vytvářet PDF s textovou vrstvou (i když není zapnuto v konfiguraci)
+        #[arg(long)]
+        pdf: bool,
+        /// Spojit PDF stránek do jednoho PDF dávky (zapne i --pdf)
+        #[arg(long)]
+        merge_pdf: bool,
+        /// Výstup jako JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Vypíše dávky a jejich stav
+    Status {
+        /// Výstup jako JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Znovu vytvoří WebP náhledy a HTML report z existujících manifestů
+    Report {
+        /// Jen dávka s tímto názvem adresáře (lze opakovat)
+        #[arg(long = "batch")]
+        batches: Vec<String>,
+        /// Výstup jako JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Znovu spočítá hashe podle manifest.json / checksums.txt v `*_logs`
+    /// adresářích output rootu (i po přenesení výstupů jinam)
+    Verify {
+        /// Jen dávka s tímto názvem (lze opakovat)
+        #[arg(long = "batch")]
+        batches: Vec<String>,
+        /// Nekontrolovat zdrojové TIFFy (např. po přenesení jen výstupů)
+        #[arg(long)]
+        skip_sources: bool,
+        /// Výstup jako JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Převede ALTO (xxxx.ocr.xml) hotových dávek podle manifestů v `*_logs`
+    /// na jinou verzi a aktualizuje manifest.json a kontrolní součty
+    AltoConvert {
+        /// Cílová verze ALTO (2.0, 2.1, 3.0, 3.1, 4.0–4.4)
+        #[arg(long = "to", value_parser = alto::parse_version_arg)]
+        to: String,
+        /// Jen dávka s tímto názvem (lze opakovat)
+        #[arg(long = "batch")]
+        batches: Vec<String>,
+        /// Výstup jako JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Nastavení předané z `main`. Nástroje a adresáře si připravuje jen
+/// `convert`, ostatní příkazy jen čtou existující výstupy.
+pub struct HeadlessEnv<'a> {
+    pub args: &'a Args,
+    pub input_root: &'a Path,
+    pub output_root: &'a Path,
+    /// AC / UC / TXT / ALTO / hOCR / PDF z konfigurace
+    pub formats: [bool; 6],
+    /// Spojené PDF dávky z konfigurace
+    pub merge_pdf: bool,
+}
+
+/// Spustí příkaz a vrátí návratový kód procesu
+pub fn run_command(command: &CliCommand, env: &HeadlessEnv) -> Result<i32> {
+    match command {
+        CliCommand::Convert {
+            batches,
+            force,
+            no_master,
+            no_user,
+            no_txt,
+            no_alto,
+            hocr,
+            pdf,
+            merge_pdf,
+            json,
+        } => {
+            let [do_master, do_user, do_txt, do_alto, do_hocr, do_pdf] = env.formats;
+            convert(
+                env,
+                batches,
+                *force,
+                [
+                    do_master && !no_master,
+                    do_user && !no_user,
+                    do_txt && !no_txt,
+                    do_alto && !no_alto,
+                    do_hocr || *hocr,
+                    do_pdf || *pdf || *merge_pdf,
+                ],
+                env.merge_pdf || *merge_pdf,
+                *json,
+            )
+        }
+        CliCommand::Status { json } => status(env, *json),
+        CliCommand::Report { batches, json } => report(env, batches, *json),
+        CliCommand::Verify {
+            batches,
+            skip_sources,
+            json,
+        } => verify(env, batches, *skip_sources, *json),
+        CliCommand::AltoConvert { to, batches, json } => alto_convert(env, to, batches, *json),
+    }
+}
+
+/// Vypíše událost buď jako JSON řádek, nebo jako text
+fn emit(json: bool, value: serde_json::Value, text: String) {
+    if json {
+        println!("{value}");
+    } else {
+        println!("{text}");
+    }
+}
+
+/// Načte dávky a aktualizuje jejich stav podle výstupů na disku
+fn load_jobs(env: &HeadlessEnv, formats: [bool; 6], merge_pdf: bool) -> Result<Vec<BatchJob>> {
+    let mut jobs = init_jobs_from_dirs(env.input_root, env.output_root, env.args)?;
+    let [do_master, do_user, do_txt, do_alto, do_hocr, do_pdf] = formats;
+    update_jobs_status_on_start(
+        &mut jobs,
+        env.output_root,
+        env.input_root,
+        do_master,
+        do_user,
+        do_txt,
+        do_alto,
+        do_hocr,
+        do_pdf,
+        merge_pdf,
+        env.args.digits,
+    );
+    Ok(jobs)
+}
+
+/// Indexy dávek vybraných přes --batch (prázdný filtr = všechny)
+fn select_jobs(jobs: &[BatchJob], names: &[String]) -> Result<Vec<usize>> {
+    for name in names {
+        if !jobs.iter().any(|j| job_display_name(j) == name) {
+            bail!("Dávka `{}` nenalezena", name);
+        }
+    }
+    Ok((0..jobs.len())
+        .filter(|&i| names.is_empty() || names.iter().any(|n| n == job_display_name(&jobs[i])))
+        .collect())
+}
+
+fn status_label(status: &JobStatus) -> &'static str {
+    match status {
+        JobStatus::Pending => "pending",
+        JobStatus::Queued => "queued",
+        JobStatus::Processing => "processing",
+        JobStatus::Paused => "paused",
+        JobStatus::Done => "done",
+        JobStatus::AlreadyDone => "already_done",
+        JobStatus::Failed(_) => "failed",
+        JobStatus::InvalidSettings(_) => "invalid_settings",
+        JobStatus::Cancelled => "cancelled",
+    }
+}
+
+fn convert(
+    env: &HeadlessEnv,
+    batches: &[String],
+    force: bool,
+    formats: [bool; 6],
+    merge_pdf: bool,
+    json: bool,
+) -> Result<i32> {
+    let [do_master, do_user, do_txt, do_alto, do_hocr, do_pdf] = formats;
+    create_roots(env.input_root, env.output_root)?;
+    let tools = detect_tools(env.args)?;
+    // Bez funkčních nástrojů nemá smysl začínat
+    if !env.args.dry_run {
+        let mut missing = Vec::new();
+        if (do_master || do_user)
+            && let ToolStatus::Error(e) = &tools.grok_status
+        {
+            missing.push(format!("Grok: {e}"));
+        }
+        if (do_txt || do_alto || do_hocr || do_pdf)
+            && let ToolStatus::Error(e) = &tools.tess_status
+        {
+            missing.push(format!("Tesseract: {e}"));
+        }
+        if !missing.is_empty() {
+            for m in &missing {
+                emit(
+                    json,
+                    json!({ "event": "tool_error", "message": m }),
+                    format!("Chyba nástroje – {m}"),
+                );
+            }
+            return Ok(EXIT_TOOL_ERROR);
+        }
+    }
+
+    let mut jobs = load_jobs(env, formats, merge_pdf)?;
+    let selected = select_jobs(&jobs, batches)?;
+
+    if let Some(warning) =
+        capped_limits_warning(env.args.jobs, env.args.grok_jobs, env.args.tess_jobs)
+    {
+        emit(json, json!({ "event": "warning", "message": warning }), warning.clone());
+    }
+
+    let mut worker = Worker::spawn();
+    let mut submitted = 0;
+    let mut skipped = 0;
+    let mut blocked = 0;
+    let do_ocr = do_txt || do_alto || do_hocr || do_pdf;
+
+    for &i in &selected {
+        let name = job_display_name(&jobs[i]).to_string();
+        // Neplatný ocr.json dávku zablokuje (načte se znovu těsně před spuštěním)
+        let loaded = load_batch_ocr(env.output_root, &jobs[i], env.args);
+        if let Err(e) = set_batch_ocr(&mut jobs[i], loaded) {
+            blocked += 1;
+            emit(
+                json,
+                json!({ "event": "blocked", "batch": name, "error": format!("{e:#}") }),
+                format!("Dávka {name} nespuštěna – neplatné nastavení OCR: {e:#}"),
+            );
+            continue;
+        }
+        if matches!(jobs[i].status, JobStatus::AlreadyDone) && !force {
+            skipped += 1;
+            emit(
+                json,
+                json!({ "event": "skipped", "batch": name, "reason": "already_done" }),
+                format!("Dávka {name} je již hotová – přeskočeno (--force pro přepracování)"),
+            );
+            continue;
+        }
+        // Dávka s nenainstalovaným jazykem se nespustí
+        let missing = tools.languages.missing(jobs[i].ocr.lang_or(&env.args.lang));
+        if do_ocr && !missing.is_empty() {
+            blocked += 1;
+            emit(
+                json,
+                json!({ "event": "blocked", "batch": name, "missing_languages": missing }),
+                format!(
+                    "Dávka {name} nespuštěna – chybí jazyky Tesseractu: {}",
+                    missing.join(", ")
+                ),
+            );
+            continue;
+        }
+        jobs[i].force_full = force;
+        let req = build_job_request(
+            i,
+            &jobs[i],
+            env.args,
+            env.input_root,
+            env.output_root,
+            &tools.grok_path,
+            &tools.tess_path,
+            &tools.tess_source,
+            tools.tessdata_dir.as_deref(),
+            do_master,
+            do_user,
+            do_txt,
+            do_alto,
+            do_hocr,
+            do_pdf,
+            merge_pdf,
+            &tools.profiles,
+            JobControl::new(),
+        );
+        if worker.submit(req).is_none() {
+            bail!("Worker neběží");
+        }
+        submitted += 1;
+    }
+
+    let mut finished = 0;
+    let mut done = 0;
+    let mut failed = blocked;
+    while finished < submitted {
+        let Some(event) = worker.recv() else {
+            bail!("Worker neočekávaně skončil");
+        };
+        match event {
+            WorkerEvent::Started { job, .. } => {
+                let name = job_display_name(&jobs[job]);
+                emit(
+                    json,
+                    json!({ "event": "started", "batch": name, "files": jobs[job].file_count }),
+                    format!("=== Start dávky {name} ({} stránek) ===", jobs[job].file_count),
+                );
+            }
+            WorkerEvent::Log { job, line, .. } => {
+                let name = job_display_name(&jobs[job]);
+                emit(
+                    json,
+                    json!({ "event": "log", "batch": name, "line": line }),
+                    line,
+                );
+            }
+            WorkerEvent::Progress { job, done: pages, .. } => {
+                let name = job_display_name(&jobs[job]);
+                let total = jobs[job].file_count;
+                emit(
+                    json,
+                    json!({ "event": "progress", "batch": name, "done": pages, "total": total }),
+                    format!("[{name}] {pages}/{total} stránek"),
+                );
+            }
+            WorkerEvent::Finished { job, status, .. } => {
+                finished += 1;
+                if matches!(status, JobStatus::Done) {
+                    done += 1;
+                } else {
+                    failed += 1;
+                }
+                let name = job_display_name(&jobs[job]);
+                let error = match &status {
+                    JobStatus::Failed(e) => Some(e.clone()),
+                    _ => None,
+                };
+                emit(
+                    json,
+                    json!({
+                        "event": "finished",
+                        "batch": name,
+                        "status": status_label(&status),
+                        "error": error,
+                    }),
+                    match &error {
+                        Some(e) => format!("=== Dávka {name}: CHYBA – {e} ==="),
+                        None => format!("=== Dávka {name}: {} ===", status_label(&status)),
+                    },
+                );
+                jobs[job].status = status;
+            }
+        }
+    }
+
+    let code = if failed > 0 { EXIT_PARTIAL } else { EXIT_OK };
+    emit(
+        json,
+        json!({
+            "event": "summary",
+            "done": done,
+            "failed": failed,
+            "skipped": skipped,
+            "blocked": blocked,
+            "exit_code": code,
+        }),
+        format!("Hotovo: {done}, selhalo: {failed}, přeskočeno: {skipped}"),
+    );
+    Ok(code)
+}
+
+fn status(env: &HeadlessEnv, json: bool) -> Result<i32> {
+    let jobs = load_jobs(env, env.formats, env.merge_pdf)?;
+    if jobs.is_empty() && !json {
+        println!("Žádné dávky v `{}`", env.input_root.display());
+    }
+    for job in &jobs {
+        let name = job_display_name(job);
+        // Prázdná dávka nemá žádný index, u přetečení se konec nevypíše
+        let index_end = u32::try_from(job.file_count)
+            .ok()
+            .and_then(|count| count.checked_sub(1))
+            .and_then(|last| job.index_start.checked_add(last));
+        let indexes = match index_end {
+            Some(end) => format!("{}-{end}", job.index_start),
+            None => "-".to_string(),
+        };
+        let failed: Vec<_> = job
+            .failed_pages
+            .iter()
+            .map(|(index, step, reason)| json!({ "index": index, "step": step, "error": reason }))
+            .collect();
+        let mut text = format!(
+            "{:<30} {:<16} {:>5} stránek  indexy {}  profil {}",
+            name,
+            status_label(&job.status),
+            job.file_count,
+            indexes,
+            job.profile
+        );
+        if !job.failed_pages.is_empty() {
+            text.push_str(&format!("  neúspěšné kroky: {}", job.failed_pages.len()));
+        }
+        if job.preflight.blocking + job.preflight.warnings > 0 {
+            text.push_str(&format!(
+                "  kontrola TIFF: {} blokujících, {} varování",
+                job.preflight.blocking, job.preflight.warnings
+            ));
+        }
+        let confidence = job.quality.as_ref().and_then(|q| q.ocr_confidence.as_ref());
+        if let Some(c) = confidence {
+            text.push_str(&format!(
+                "  konfidence OCR {:.1} %, ke kontrole: {}",
+                c.mean * 100.0,
+                c.review_pages.len()
+            ));
+        }
+        let dictionary = job.quality.as_ref().and_then(|q| q.dictionary.as_ref());
+        if let Some(d) = dictionary {
+            text.push_str(&format!("  shoda se slovníkem {:.1} %", d.hit_rate * 100.0));
+        }
+        emit(
+            json,
+            json!({
+                "batch": name,
+                "dir": job.dir.to_string_lossy(),
+                "status": status_label(&job.status),
+                "files": job.file_count,
+                "index_start": job.index_start,
+                "index_end": index_end,
+                "profile": job.profile,
+                "failed_steps": failed,
+                "preflight": {
+                    "warnings": job.preflight.warnings,
+                    "blocking": job.preflight.blocking,
+                },
+                "ocr_confidence": confidence,
+                "dictionary": dictionary,
+            }),
+            text,
+        );
+    }
+    Ok(EXIT_OK)
+}
+
+fn report(env: &HeadlessEnv, batches: &[String], json: bool) -> Result<i32> {
+    let jobs = load_jobs(env, env.formats, env.merge_pdf)?;
+    let selected = select_jobs(&jobs, batches)?;
+    let mut errors = 0;
+
+    for i in selected {
+        let name = job_display_name(&jobs[i]);
+        let logs_dir: PathBuf = batch_logs_dir(env.output_root, &jobs[i].name);
+        if !logs_dir.join("manifest.json").exists() {
+            emit(
+                json,
+                json!({ "event": "skipped", "batch": name, "reason": "no_manifest" }),
+                format!("Dávka {name}: manifest neexistuje – přeskočeno"),
+            );
+            continue;
+        }
+
+        // Průběh náhledů se v reportu nevypisuje, chyba reportu ano
+        let mut preview_log = JobLog::buffered();
+        let result = generate_webp_previews(&logs_dir, &mut preview_log)
+            .and_then(|()| write_html_report(&logs_dir));
+        match result {
+            Ok(()) => {
+                let html = logs_dir.join("index.html");
+                emit(
+                    json,
+                    json!({ "event": "report", "batch": name, "html": html.to_string_lossy() }),
+                    format!("Dávka {name}: report {}", html.display()),
+                );
+            }
+            Err(e) => {
+                errors += 1;
+                emit(
+                    json,
+                    json!({ "event": "error", "batch": name, "error": format!("{e:#}") }),
+                    format!("Dávka {name}: chyba reportu – {e:#}"),
+                );
+            }
+        }
+    }
+
+    Ok(if errors > 0 { EXIT_PARTIAL } else { EXIT_OK })
+}
+
+/// `*_logs` adresáře dávek vybraných přes --batch (prázdný filtr = všechny)
+fn selected_logs_dirs(env: &HeadlessEnv, batches: &[String]) -> Result<Vec<PathBuf>> {
+    let mut logs_dirs = find_logs_dirs(env.output_root)?;
+    if !batches.is_empty() {
+        logs_dirs.retain(|d| {
+            let name = batch_name(env.output_root, d);
+            batches.iter().any(|b| name == format!("{b}_logs"))
+        });
+        if logs_dirs.is_empty() {
+            bail!("Žádná z dávek {:?} nemá manifest v `{}`", batches, env.output_root.display());
+        }
+    }
+    Ok(logs_dirs)
+}
+
+fn verify(env: &HeadlessEnv, batches: &[String], skip_sources: bool, json: bool) -> Result<i32> {
+    let logs_dirs = selected_logs_dirs(env, batches)?;
+
+    let mut failed = 0;
+    for logs_dir in &logs_dirs {
+        let report = match verify_batch(logs_dir, skip_sources) {
+            Ok(r) => r,
+            Err(e) => {
+                failed += 1;
+                emit(
+                    json,
+                    json!({ "event": "error", "logs_dir": logs_dir.to_string_lossy(), "error": format!("{e:#}") }),
+                    format!("{}: chyba kontroly – {e:#}", logs_dir.display()),
+                );
+                continue;
+            }
+        };
+        let report_path = write_verify_report(&report, logs_dir)?;
+        let ok = report.summary.is_ok();
+        if !ok {
+            failed += 1;
+        }
+
+        let problems: Vec<_> = report
+            .files
+            .iter()
+            .filter(|f| f.status != FileStatus::Ok)
+            .collect();
+        let s = &report.summary;
+        let mut text = format!(
+            "Dávka {}: {} – v pořádku {}, chybí {}, nečitelné {}, změněno {}, jiná velikost {}, navíc {}, nesoulad {}",
+            report.batch_name,
+            if ok { "OK" } else { "CHYBY" },
+            s.ok,
+            s.missing,
+            s.unreadable,
+            s.changed,
+            s.size_mismatch,
+            s.extra,
+            s.inconsistent
+        );
+        for f in &problems {
+            text.push_str(&format!("\n  {:?}: {}", f.status, f.path));
+        }
+        emit(
+            json,
+            json!({
+                "event": "verified",
+                "batch": report.batch_name,
+                "ok": ok,
+                "summary": report.summary,
+                "problems": problems,
+                "report": report_path.to_string_lossy(),
+            }),
+            text,
+        );
+    }
+
+    if logs_dirs.is_empty() && !json {
+        println!("Žádné manifesty v `{}`", env.output_root.display());
+    }
+    Ok(if failed > 0 { EXIT_PARTIAL } else { EXIT_OK })
+}
+
+fn alto_convert(env: &HeadlessEnv, to: &str, batches: &[String], json: bool) -> Result<i32> {
+    let target = AltoVersion::parse(to)?;
+    let logs_dirs = selected_logs_dirs(env, batches)?;
+
+    let mut failed = 0;
+    for logs_dir in &logs_dirs {
+        let files = match manifest_outputs(logs_dir, "alto") {
+            Ok(files) => files,
+            Err(e) => {
+                failed += 1;
+                emit(
+                    json,
+                    json!({ "event": "error", "logs_dir": logs_dir.to_string_lossy(), "error": format!("{e:#}") }),
+                    format!("{}: {e:#}", logs_dir.display()),
+                );
+                continue;
+            }
+        };
+
+        let mut converted = Vec::new();
+        let mut errors = Vec::new();
+        for (recorded, actual) in files {
+            match alto::convert_file(&actual, target) {
+                Ok(()) => converted.push((recorded, actual)),
+                Err(e) => errors.push(format!("{e:#}")),
+            }
+        }
+        // Manifest a součty i po částečném převodu, aby odpovídaly souborům
+        let refreshed = refresh_manifest_files(logs_dir, &converted, |manifest| {
+            if errors.is_empty() {
+                manifest["alto_version"] = json!(target.to_string());
+            }
+        })
+        .and_then(|()| write_html_report(logs_dir));
+        if let Err(e) = refreshed {
+            errors.push(format!("{e:#}"));
+        }
+        if !errors.is_empty() {
+            failed += 1;
+        }
+
+        let name = batch_name(env.output_root, logs_dir);
+        let mut text = format!(
+            "Dávka {}: ALTO {target} – převedeno {}, chyb {}",
+            name.trim_end_matches("_logs"),
+            converted.len(),
+            errors.len()
+        );
+        for e in &errors {
+            text.push_str(&format!("\n  {e}"));
+        }
+        emit(
+            json,
+            json!({
+                "event": "alto_converted",
+                "logs_dir": logs_dir.to_string_lossy(),
+                "version": target.to_string(),
+                "converted": converted.len(),
+                "errors": errors,
+            }),
+            text,
+        );
+    }
+
+    if logs_dirs.is_empty() && !json {
+        println!("Žádné manifesty v `{}`", env.output_root.display());
+    }
+    Ok(if failed > 0 { EXIT_PARTIAL } else { EXIT_OK })
+}
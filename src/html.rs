@@ -2,32 +2,65 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
 #[derive(Debug, Deserialize)]
 struct ManifestFile {
     path: String,
-    size: u64,
-    blake3: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct ManifestPage {
     index: String,
     
-    // Podpora pro oba názvy
-    #[serde(alias = "original_tiff", rename = "tiff")]
-    tiff: ManifestFile,
-    
-    #[serde(rename = "ac_jp2")]
-    ac_jp2: Option<ManifestFile>,
-    
-    #[serde(rename = "uc_jp2")]
-    uc_jp2: Option<ManifestFile>,
-    
     txt: Option<ManifestFile>,
     alto: Option<ManifestFile>,
+
+    #[serde(default)]
+    ac_jp2_check: Option<ManifestJp2Check>,
+
+    #[serde(default)]
+    uc_jp2_check: Option<ManifestJp2Check>,
+
+    #[serde(default)]
+    ocr_confidence: Option<ManifestConfidence>,
+
+    #[serde(default)]
+    dictionary: Option<ManifestDictionary>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestJp2Check {
+    valid: bool,
+    #[serde(default)]
+    violations: Vec<String>,
+}
+
+/// Konfidence slov stránky nebo dávky (u dávky i práh a stránky ke kontrole)
+#[derive(Debug, Deserialize)]
+struct ManifestConfidence {
+    words: usize,
+    mean: f64,
+    median: f64,
+    low_share: f64,
+    #[serde(default)]
+    needs_review: bool,
+    #[serde(default)]
+    threshold: Option<f64>,
+    #[serde(default)]
+    review_pages: Vec<String>,
+}
+
+/// Shoda slov TXT se slovníkem (stránka nebo dávka)
+#[derive(Debug, Deserialize)]
+struct ManifestDictionary {
+    words: usize,
+    hits: usize,
+    hit_rate: f64,
+    #[serde(default)]
+    wordlists: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,15 +68,6 @@ struct Manifest {
     #[serde(rename = "batch_name")]
     batch_name: String,
     
-    #[serde(rename = "start_index")]
-    start_index: Option<u32>,
-    
-    #[serde(rename = "file_count")]
-    file_count: Option<u32>,
-    
-    #[serde(rename = "input_dir")]
-    input_dir: String,
-    
     #[serde(rename = "output_dir")]
     output_dir: String,
     
@@ -53,23 +77,22 @@ struct Manifest {
     #[serde(rename = "created_at")]
     created_at: String,
     
-    #[serde(rename = "generated")]
-    generated: Option<String>,
-    
     lang: String,
     
     #[serde(rename = "alto_version")]
     alto_version: String,
     
     pages: Vec<ManifestPage>,
+
+    #[serde(default)]
+    ocr_confidence: Option<ManifestConfidence>,
+
+    #[serde(default)]
+    dictionary: Option<ManifestDictionary>,
 }
 
 pub fn write_html_report(logs_dir: &Path) -> Result<()> {
-    // Debug výpis pro diagnostiku
-    println!("DEBUG: Generuji HTML report z adresáře: {:?}", logs_dir);
-    
     let manifest_path = logs_dir.join("manifest.json");
-    println!("DEBUG: Hledám manifest: {:?}", manifest_path);
     
     // Zkontrolovat existenci manifestu
     if !manifest_path.exists() {
@@ -79,39 +102,23 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     let manifest_text = fs::read_to_string(&manifest_path)
         .with_context(|| format!("Nelze načíst manifest.json z {:?}", manifest_path))?;
     
-    println!("DEBUG: Manifest načten, velikost: {} znaků", manifest_text.len());
-    
-    // Debug: zobrazit začátek manifestu
-    let preview_len = manifest_text.len().min(200);
-    println!("DEBUG: Náhled manifestu (prvních {} znaků): {}", preview_len, &manifest_text[..preview_len]);
-    
-    // Parsovat manifest - UPRAVENÁ ČÁST
-    let manifest: Manifest = match serde_json::from_str::<Manifest>(&manifest_text) {
-        Ok(m) => {
-            println!("DEBUG: Manifest úspěšně parsován");
-            println!("DEBUG: Batch: {}, stránek: {}", m.batch_name, m.pages.len());
-            m
-        }
-        Err(e) => {
-            eprintln!("DEBUG: CHYBA PARSOVÁNÍ MANIFESTU: {}", e);
-            eprintln!("DEBUG: Detaily: {:?}", e);
-            return Err(e.into());
-        }
-    };
+    let manifest: Manifest = serde_json::from_str(&manifest_text)
+        .with_context(|| format!("Nelze parsovat manifest.json z {:?}", manifest_path))?;
     
     let batch_name = manifest.batch_name.clone();
     let lang = manifest.lang.clone();
     let alto_version = manifest.alto_version.clone();
     
-    // Pro ladění: zobrazit informace o stránkách
-    for (i, page) in manifest.pages.iter().enumerate() {
-        println!("DEBUG: Stránka {}: index={}, txt={:?}, alto={:?}", 
-            i, page.index, 
-            page.txt.as_ref().map(|t| t.path.as_str()),
-            page.alto.as_ref().map(|a| a.path.as_str()));
-    }
-    
-    let rel_prefix = format!("../{}", batch_name);
+    // Výstupní adresář dávky leží vedle `*_logs` (u dávky z input_root je to jeho rodič)
+    let output_dir = Path::new(&manifest.output_dir);
+    let rel_prefix = if Path::new(&manifest.logs_dir).parent() == Some(output_dir) {
+        "..".to_string()
+    } else {
+        match output_dir.file_name().and_then(|s| s.to_str()) {
+            Some(name) => format!("../{}", name),
+            None => format!("../{}", batch_name),
+        }
+    };
 
     let pages_json: Vec<_> = manifest
         .pages
@@ -142,6 +149,15 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
 
             let image_url = format!("page_{}.webp", p.index);
 
+            // Odchylky JP2 od profilu kódování ("AC: …", "UC: …")
+            let jp2_checked = p.ac_jp2_check.is_some() || p.uc_jp2_check.is_some();
+            let jp2_issues: Vec<String> = [("AC", &p.ac_jp2_check), ("UC", &p.uc_jp2_check)]
+                .into_iter()
+                .filter_map(|(label, check)| check.as_ref().map(|c| (label, c)))
+                .filter(|(_, c)| !c.valid)
+                .flat_map(|(label, c)| c.violations.iter().map(move |v| format!("{label}: {v}")))
+                .collect();
+
             json!({
                 "index": p.index,
                 "txtUrl": txt_url,
@@ -149,6 +165,20 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
                 "imageUrl": image_url,
                 "txtName": txt_name,
                 "altoName": alto_name,
+                "jp2Checked": jp2_checked,
+                "jp2Issues": jp2_issues,
+                "confidence": p.ocr_confidence.as_ref().map(|c| json!({
+                    "words": c.words,
+                    "mean": c.mean,
+                    "median": c.median,
+                    "lowShare": c.low_share,
+                    "needsReview": c.needs_review,
+                })),
+                "dictionary": p.dictionary.as_ref().map(|d| json!({
+                    "words": d.words,
+                    "hits": d.hits,
+                    "hitRate": d.hit_rate,
+                })),
             })
         })
         .collect();
@@ -158,6 +188,20 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
         "createdAt": manifest.created_at,
         "lang": lang,
         "altoVersion": alto_version,
+        "confidence": manifest.ocr_confidence.as_ref().map(|c| json!({
+            "words": c.words,
+            "mean": c.mean,
+            "median": c.median,
+            "lowShare": c.low_share,
+            "threshold": c.threshold,
+            "reviewPages": c.review_pages,
+        })),
+        "dictionary": manifest.dictionary.as_ref().map(|d| json!({
+            "words": d.words,
+            "hits": d.hits,
+            "hitRate": d.hit_rate,
+            "languages": d.wordlists.keys().collect::<Vec<_>>(),
+        })),
         "pages": pages_json,
     });
 
@@ -293,6 +337,14 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
       color: var(--text-muted);
     }
 
+    #pageList button span.badge.warn {
+      color: var(--danger);
+    }
+
+    #pageList button span.badge.review {
+      color: #ffb74d;
+    }
+
     #pageList button:hover {
       background: var(--accent-soft);
       border-color: rgba(79,195,247,0.4);
@@ -985,6 +1037,8 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     <span id="metaBatch"></span> ·
     <span id="metaLang"></span> ·
     <span id="metaAlto"></span>
+    <span id="metaConfidence"></span>
+    <span id="metaDictionary"></span>
   </div>
 </header>
 <main>
@@ -1072,6 +1126,18 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
         <div class="label">Zoom</div>
         <div id="infoZoom">100%</div>
       </div>
+      <div class="row">
+        <div class="label">JP2</div>
+        <div id="infoJp2">-</div>
+      </div>
+      <div class="row">
+        <div class="label">Konfidence</div>
+        <div id="infoConfidence">-</div>
+      </div>
+      <div class="row">
+        <div class="label">Slovník</div>
+        <div id="infoDictionary">-</div>
+      </div>
     </div>
     
     <!-- TXT editor -->
@@ -1222,6 +1288,9 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
   const zoomDisplayEl = document.getElementById('zoomDisplay');
   const infoIndexEl = document.getElementById('infoIndex');
   const infoElementsEl = document.getElementById('infoElements');
+  const infoJp2El = document.getElementById('infoJp2');
+  const infoConfidenceEl = document.getElementById('infoConfidence');
+  const infoDictionaryEl = document.getElementById('infoDictionary');
   const infoDimensionsEl = document.getElementById('infoDimensions');
   const infoZoomEl = document.getElementById('infoZoom');
   const statusEl = document.getElementById('statusBar');
@@ -1403,6 +1472,10 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     return { x, y, w, h };
   }
 
+  function formatPercent(value) {
+    return (value * 100).toFixed(1) + ' %';
+  }
+
   // === PAGE MANAGEMENT ===
   function createPageList() {
     if (!pageListEl) return;
@@ -1417,10 +1490,26 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     
     pages.forEach((page, idx) => {
       const btn = document.createElement('button');
+      const jp2Issues = page.jp2Issues || [];
+      const review = page.confidence && page.confidence.needsReview;
       btn.innerHTML = `
         <span class="index">${page.index || idx + 1}</span>
-        <span class="badge">ALTO</span>
+        ${jp2Issues.length > 0
+          ? '<span class="badge warn">JP2 ⚠</span>'
+          : review
+            ? '<span class="badge review">OCR ⚠</span>'
+            : '<span class="badge">ALTO</span>'}
       `;
+      const hints = [...jp2Issues];
+      if (review) {
+        hints.push(`Nízká konfidence OCR: ${formatPercent(page.confidence.mean)}`);
+      }
+      if (page.dictionary) {
+        hints.push(`Shoda se slovníkem: ${formatPercent(page.dictionary.hitRate)}`);
+      }
+      if (hints.length > 0) {
+        btn.title = hints.join('\n');
+      }
       btn.dataset.idx = idx;
       btn.addEventListener('click', () => {
         if (txtModified || altoModified) {
@@ -1461,6 +1550,38 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     // Update info
     if (infoIndexEl) infoIndexEl.textContent = page.index || '-';
     if (infoElementsEl) infoElementsEl.textContent = '-';
+    if (infoJp2El) {
+      const jp2Issues = page.jp2Issues || [];
+      if (!page.jp2Checked) {
+        infoJp2El.textContent = '-';
+        infoJp2El.style.color = '';
+      } else if (jp2Issues.length === 0) {
+        infoJp2El.textContent = 'OK (odpovídá profilu)';
+        infoJp2El.style.color = 'var(--success)';
+      } else {
+        infoJp2El.textContent = jp2Issues.join('; ');
+        infoJp2El.style.color = 'var(--danger)';
+      }
+    }
+    if (infoConfidenceEl) {
+      const c = page.confidence;
+      if (!c) {
+        infoConfidenceEl.textContent = '-';
+        infoConfidenceEl.style.color = '';
+      } else {
+        infoConfidenceEl.textContent =
+          `průměr ${formatPercent(c.mean)}, medián ${formatPercent(c.median)}, ` +
+          `${c.words} slov, nízká ${formatPercent(c.lowShare)}` +
+          (c.needsReview ? ' – ke kontrole' : '');
+        infoConfidenceEl.style.color = c.needsReview ? '#ffb74d' : '';
+      }
+    }
+    if (infoDictionaryEl) {
+      const d = page.dictionary;
+      infoDictionaryEl.textContent = d
+        ? `${formatPercent(d.hitRate)} (${d.hits} z ${d.words} slov)`
+        : '-';
+    }
     
     // Load data
     await Promise.all([
@@ -2876,6 +2997,62 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     showModal(saveModal);
   }
 
+  // Uloží soubor přes lazyalto (PUT do výstupního adresáře dávky).
+  // Otevřeno bez serveru (file://) → stažení v prohlížeči.
+  async function storeFile(url, name, content, type) {
+    if (location.protocol === 'file:') {
+      downloadFile(name, content, type);
+      return 'download';
+    }
+    const response = await fetch(url, {
+      method: 'PUT',
+      headers: { 'Content-Type': type },
+      body: content
+    });
+    if (!response.ok) {
+      throw new Error(`HTTP ${response.status}: ${await response.text()}`);
+    }
+    return 'server';
+  }
+
+  function downloadFile(name, content, type) {
+    const blob = new Blob([content], { type });
+    const url = URL.createObjectURL(blob);
+    const a = document.createElement('a');
+    a.href = url;
+    a.download = name;
+    document.body.appendChild(a);
+    a.click();
+    document.body.removeChild(a);
+    URL.revokeObjectURL(url);
+  }
+
+  // ALTO k uložení: v režimu XML text editoru, jinak původní XML s upraveným
+  // textem a polohou slov (String podle ID); smazaná slova se odeberou.
+  // Nově nakreslené prvky nemají v XML rodiče a neukládají se.
+  function buildAltoXml() {
+    if (altoDisplayMode === 'xml' && altoXmlText) return altoXmlText.value;
+    if (!currentAltoData) return currentAltoXml;
+    const doc = new DOMParser().parseFromString(currentAltoXml, 'application/xml');
+    const words = new Map(currentAltoData.words.map(w => [w.id, w]));
+    for (const el of Array.from(doc.getElementsByTagName('String'))) {
+      const id = el.getAttribute('ID');
+      if (!id) continue;
+      const word = words.get(id);
+      if (!word) {
+        el.parentNode.removeChild(el);
+        continue;
+      }
+      el.setAttribute('CONTENT', word.text);
+      el.setAttribute('HPOS', word.x);
+      el.setAttribute('VPOS', word.y);
+      el.setAttribute('WIDTH', word.w);
+      el.setAttribute('HEIGHT', word.h);
+    }
+    const xml = new XMLSerializer().serializeToString(doc);
+    return xml.startsWith('<?xml') ? xml : '<?xml version="1.0" encoding="UTF-8"?>\n' + xml;
+  }
+
   async function saveTxtFile() {
     const page = MANIFEST.pages[currentPageIndex];
     if (!page || !page.txtName) return false;
@@ -2883,16 +3060,7 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     try {
       const newText = ocrTextEl.value;
       
-      // Save to file (client-side download)
-      const blob = new Blob([newText], { type: 'text/plain;charset=utf-8' });
-      const url = URL.createObjectURL(blob);
-      const a = document.createElement('a');
-      a.href = url;
-      a.download = page.txtName;
-      document.body.appendChild(a);
-      a.click();
-      document.body.removeChild(a);
-      URL.revokeObjectURL(url);
+      const target = await storeFile(page.txtUrl, page.txtName, newText, 'text/plain;charset=utf-8');
       
       // Update state
       fullOcrText = newText;
@@ -2900,11 +3068,13 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
       txtModified = false;
       updateSaveButtons();
       
-      showNotification('TXT soubor připraven ke stažení', 2000);
+      showNotification(target === 'server'
+        ? `${page.txtName} uložen (původní verze zálohována)`
+        : 'TXT soubor připraven ke stažení', 2000);
       return true;
     } catch (err) {
       console.error('Chyba ukládání TXT:', err);
-      showNotification('Chyba při ukládání TXT', 3000);
+      showNotification(`Chyba při ukládání TXT: ${err.message}`, 4000);
       return false;
     }
   }
@@ -2914,25 +3084,20 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     if (!page || !page.altoName) return false;
     
     try {
-      // TODO: Generate XML from currentAltoData
-      const blob = new Blob([currentAltoXml], { type: 'application/xml;charset=utf-8' });
-      const url = URL.createObjectURL(blob);
-      const a = document.createElement('a');
-      a.href = url;
-      a.download = page.altoName;
-      document.body.appendChild(a);
-      a.click();
-      document.body.removeChild(a);
-      URL.revokeObjectURL(url);
+      const xml = buildAltoXml();
+      const target = await storeFile(page.altoUrl, page.altoName, xml, 'application/xml;charset=utf-8');
       
+      currentAltoXml = xml;
       altoModified = false;
       updateSaveButtons();
       
-      showNotification('ALTO soubor připraven ke stažení', 2000);
+      showNotification(target === 'server'
+        ? `${page.altoName} uložen (původní verze zálohována)`
+        : 'ALTO soubor připraven ke stažení', 2000);
       return true;
     } catch (err) {
       console.error('Chyba ukládání ALTO:', err);
-      showNotification('Chyba při ukládání ALTO', 3000);
+      showNotification(`Chyba při ukládání ALTO: ${err.message}`, 4000);
       return false;
     }
   }
@@ -2963,10 +3128,31 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     if (metaBatch) metaBatch.textContent = 'Dávka: ' + MANIFEST.batchName;
     if (metaLang) metaLang.textContent = 'Jazyk OCR: ' + MANIFEST.lang;
     if (metaAlto) metaAlto.textContent = 'ALTO: ' + MANIFEST.altoVersion;
+    const metaConfidence = document.getElementById('metaConfidence');
+    const confidence = MANIFEST.confidence;
+    if (metaConfidence && confidence) {
+      const review = confidence.reviewPages || [];
+      metaConfidence.textContent = ' · Konfidence OCR: ' + formatPercent(confidence.mean) +
+        (review.length > 0 ? ` (${review.length} stránek ke kontrole)` : '');
+      if (review.length > 0) {
+        metaConfidence.title = 'Ke kontrole: ' + review.join(', ');
+      }
+    }
     
-    // Load first page
+    const metaDictionary = document.getElementById('metaDictionary');
+    const dictionary = MANIFEST.dictionary;
+    if (metaDictionary && dictionary) {
+      metaDictionary.textContent = ` · Slovník (${dictionary.languages.join('+')}): ` +
+        formatPercent(dictionary.hitRate);
+    }
+    
+    // Load first page (nebo stránku z odkazu #page=0001, např. z lazyalto)
     if (MANIFEST.pages && MANIFEST.pages.length > 0) {
-      selectPage(0);
+      const match = location.hash.match(/^#page=(.+)$/);
+      const start = match
+        ? MANIFEST.pages.findIndex(p => p.index === decodeURIComponent(match[1]))
+        : -1;
+      selectPage(start >= 0 ? start : 0);
     } else {
       setStatus('Žádné stránky k zobrazení', true);
     }
@@ -2982,13 +3168,8 @@ pub fn write_html_report(logs_dir: &Path) -> Result<()> {
     );
 
     let output_path = logs_dir.join("index.html");
-    println!("DEBUG: Ukládám HTML do: {:?}", output_path);
-    
     fs::write(&output_path, html)
         .with_context(|| format!("Nelze zapsat HTML soubor: {:?}", output_path))?;
     
-    println!("DEBUG: HTML úspěšně vygenerován: {:?}", output_path);
-    println!("DEBUG: Velikost HTML: cca {} znaků", manifest_js.len() + 50000); // Přibližná velikost
-    
     Ok(())
 }
\ No newline at end of file
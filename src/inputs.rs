@@ -0,0 +1,224 @@
+// src/inputs.rs
+// Vstupní formáty stránek. TIFF, JPEG a PNG čtou Grok i Tesseract přímo;
+// JP2 se nejdřív dekóduje (grk_decompress) do dočasného TIFFu.
+// Vícestránkový TIFF se rozdělí na stránky, každá dostane vlastní index.
+// Nový formát = nová varianta `InputFormat` + způsob přípravy v `prepare_input`.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::preflight::{extract_tiff_page, tiff_page_count};
+use crate::worker::{JobControl, JobLog};
+use crate::{command_to_string, run_command};
+
+/// Adresář pro dočasné TIFFy v `*_logs` (po dávce se smaže, pokud je prázdný)
+pub const WORK_DIR: &str = "input_tmp";
+
+/// Formát zdrojového souboru stránky
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    Tiff,
+    Jpeg,
+    Png,
+    Jp2,
+}
+
+impl InputFormat {
+    pub const ALL: [InputFormat; 4] = [
+        InputFormat::Tiff,
+        InputFormat::Jpeg,
+        InputFormat::Png,
+        InputFormat::Jp2,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InputFormat::Tiff => "TIFF",
+            InputFormat::Jpeg => "JPEG",
+            InputFormat::Png => "PNG",
+            InputFormat::Jp2 => "JP2",
+        }
+    }
+
+    /// Přípony souborů (malými písmeny)
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            InputFormat::Tiff => &["tif", "tiff"],
+            InputFormat::Jpeg => &["jpg", "jpeg"],
+            InputFormat::Png => &["png"],
+            InputFormat::Jp2 => &["jp2", "j2k", "jpf"],
+        }
+    }
+
+    /// Formát podle přípony; None = soubor není vstupem dávky
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|f| f.extensions().contains(&ext.as_str()))
+    }
+
+    /// Grok i Tesseract soubor čtou bez převodu
+    pub fn is_native(self) -> bool {
+        !matches!(self, InputFormat::Jp2)
+    }
+}
+
+/// Jedna stránka dávky: zdrojový soubor a u vícestránkového TIFFu číslo stránky (od 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageSource {
+    pub path: PathBuf,
+    pub page: Option<usize>,
+}
+
+impl PageSource {
+    /// Identifikace zdroje stránky (do state.json): cesta, u stránky TIFFu `cesta#N`
+    pub fn key(&self) -> String {
+        match self.page {
+            Some(page) => format!("{}#{page}", self.path.display()),
+            None => self.path.to_string_lossy().to_string(),
+        }
+    }
+
+    /// Název souboru, u stránky TIFFu s číslem stránky
+    pub fn label(&self) -> String {
+        let name = self
+            .path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match self.page {
+            Some(page) => format!("{name} (str. {page})"),
+            None => name,
+        }
+    }
+}
+
+/// Rozdělí soubory dávky na stránky: vícestránkový TIFF dá jednu stránku
+/// za každé IFD. Nečitelný TIFF zůstane jednou stránkou (zachytí ho kontrola TIFFů).
+pub fn expand_pages(files: &[PathBuf]) -> Vec<PageSource> {
+    let mut pages = Vec::with_capacity(files.len());
+    for path in files {
+        let count = match InputFormat::from_path(path) {
+            Some(InputFormat::Tiff) => tiff_page_count(path).unwrap_or(1),
+            _ => 1,
+        };
+        if count > 1 {
+            pages.extend((1..=count).map(|page| PageSource {
+                path: path.clone(),
+                page: Some(page),
+            }));
+        } else {
+            pages.push(PageSource {
+                path: path.clone(),
+                page: None,
+            });
+        }
+    }
+    pages
+}
+
+/// Vstup připravený pro Grok/Tesseract. Dočasný TIFF se smaže při zahození.
+pub struct PreparedInput {
+    pub path: PathBuf,
+    temporary: bool,
+}
+
+impl Drop for PreparedInput {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Připraví zdroj stránky: nativní formát vrátí beze změny, ostatní formáty
+/// a stránky vícestránkového TIFFu převede do `<work_dir>/<index>.tif`.
+pub fn prepare_input(
+    page: &PageSource,
+    grok_path: &Path,
+    work_dir: &Path,
+    index_str: &str,
+    dry_run: bool,
+    control: &JobControl,
+    logs: &mut JobLog,
+) -> Result<PreparedInput> {
+    let source = page.path.as_path();
+    let format = InputFormat::from_path(source)
+        .ok_or_else(|| anyhow!("Nepodporovaný vstupní formát `{}`", source.display()))?;
+    if format.is_native() && page.page.is_none() {
+        return Ok(PreparedInput {
+            path: source.to_path_buf(),
+            temporary: false,
+        });
+    }
+
+    fs::create_dir_all(work_dir)
+        .with_context(|| format!("Nelze vytvořit `{}`", work_dir.display()))?;
+    let tiff = work_dir.join(format!("{index_str}.tif"));
+    let prepared = PreparedInput {
+        path: tiff,
+        temporary: !dry_run,
+    };
+
+    match (format, page.page) {
+        (InputFormat::Tiff, Some(n)) => {
+            logs.push(format!("Stránka {n} z `{}` → {}", source.display(), prepared.path.display()));
+            if dry_run {
+                logs.push("(dry-run)".to_string());
+            } else {
+                extract_tiff_page(source, n, &prepared.path)
+                    .with_context(|| format!("Nelze vyjmout stránku {n} z `{}`", source.display()))?;
+            }
+        }
+        (InputFormat::Jp2, None) => {
+            decode_jp2(source, &prepared.path, grok_path, dry_run, control, logs)?
+        }
+        (other, _) => return Err(anyhow!("Převod z {} není k dispozici", other.name())),
+    }
+    Ok(prepared)
+}
+
+/// Dekóduje JP2 do TIFFu pomocí grk_decompress (vedle grk_compress)
+fn decode_jp2(
+    source: &Path,
+    output_tiff: &Path,
+    grok_path: &Path,
+    dry_run: bool,
+    control: &JobControl,
+    logs: &mut JobLog,
+) -> Result<()> {
+    let mut cmd = Command::new(grk_decompress_path(grok_path));
+    cmd.arg("-i").arg(source).arg("-o").arg(output_tiff);
+
+    logs.push(command_to_string("Grok dekódování JP2", &cmd));
+    if dry_run {
+        logs.push("(dry-run)".to_string());
+        return Ok(());
+    }
+
+    let output = run_command(&mut cmd, "grk_decompress", control)?;
+    if output.status.success() && output_tiff.exists() {
+        logs.push("OK".to_string());
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        logs.push(format!("FAILED: {stderr}"));
+        let _ = fs::remove_file(output_tiff);
+        Err(anyhow!("grk_decompress selhalo pro `{}`", source.display()))
+    }
+}
+
+/// grk_decompress ve stejném adresáři jako grk_compress (nebo z PATH)
+fn grk_decompress_path(grok_path: &Path) -> PathBuf {
+    let name = grok_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("grk_compress")
+        .replace("grk_compress", "grk_decompress");
+    grok_path.with_file_name(name)
+}
@@ -0,0 +1,606 @@
+// src/jp2check.rs
+// Kontrola vytvořených JP2 proti profilu kódování.
+// Čte jen hlavičky: JP2 boxy (`ftyp`, `jp2h`/`colr`, `jp2c`)
+// a hlavní hlavičku codestreamu (SIZ, COD, QCD, TLM) až po první SOT.
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::profiles::GrokParams;
+
+// Markery codestreamu
+const SOC: u16 = 0xFF4F;
+const SIZ: u16 = 0xFF51;
+const COD: u16 = 0xFF52;
+const TLM: u16 = 0xFF55;
+const QCD: u16 = 0xFF5C;
+const SOT: u16 = 0xFF90;
+
+/// Co se z hlaviček JP2 podařilo vyčíst
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Jp2Info {
+    pub width: u32,
+    pub height: u32,
+    pub components: u16,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub progression: String,
+    pub layers: u16,
+    pub decomposition_levels: u8,
+    pub code_block: (u32, u32),
+    /// Velikosti precinktů od nejnižšího rozlišení (prázdné = výchozí 2^15)
+    pub precincts: Vec<(u32, u32)>,
+    /// true = 5-3 bezeztrátová, false = 9-7 ztrátová vlnka
+    pub reversible: bool,
+    pub sop: bool,
+    pub eph: bool,
+    /// Kvantizace z QCD: "none", "derived", "expounded"
+    pub quantization: String,
+    pub tlm: bool,
+    /// Barevný prostor z `colr`: "sRGB", "greyscale", "sYCC", "ICC", …
+    pub colour: Option<String>,
+}
+
+/// Výsledek kontroly jednoho JP2 souboru (zapisuje se do manifestu)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Jp2Check {
+    pub valid: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<Jp2Info>,
+}
+
+/// Přečte hlavičky JP2 a porovná je s parametry profilu.
+/// Nečitelný soubor je také porušení (valid = false).
+pub fn check_jp2(path: &Path, expected: &GrokParams) -> Jp2Check {
+    match read_jp2_info(path) {
+        Ok(info) => {
+            let violations = validate(&info, expected);
+            Jp2Check {
+                valid: violations.is_empty(),
+                violations,
+                info: Some(info),
+            }
+        }
+        Err(e) => Jp2Check {
+            valid: false,
+            violations: vec![format!("Nelze přečíst JP2: {e:#}")],
+            info: None,
+        },
+    }
+}
+
+/// Projde JP2 boxy a hlavní hlavičku codestreamu
+pub fn read_jp2_info(path: &Path) -> Result<Jp2Info> {
+    let file = File::open(path).with_context(|| format!("Nelze otevřít `{}`", path.display()))?;
+    let file_len = file.metadata()?.len();
+    let mut r = BufReader::new(file);
+
+    let mut info = Jp2Info::default();
+    let mut seen_signature = false;
+    let mut seen_ftyp = false;
+    let mut seen_jp2h = false;
+    let mut seen_codestream = false;
+
+    let mut pos = 0u64;
+    while pos < file_len {
+        r.seek(SeekFrom::Start(pos))?;
+        let (box_type, header_len, box_len) = read_box_header(&mut r, file_len - pos)?;
+        let content_len = box_len - header_len;
+
+        match &box_type {
+            b"jP  " => seen_signature = true,
+            b"ftyp" => {
+                let brand = read_array::<4>(&mut r)?;
+                if &brand != b"jp2 " {
+                    bail!("Neočekávaný brand `{}` v ftyp", String::from_utf8_lossy(&brand));
+                }
+                seen_ftyp = true;
+            }
+            b"jp2h" => {
+                read_jp2h(&mut r, content_len, &mut info)?;
+                seen_jp2h = true;
+            }
+            b"jp2c" => {
+                read_main_header(&mut r, &mut info)?;
+                seen_codestream = true;
+                break;
+            }
+            _ => {}
+        }
+        pos += box_len;
+    }
+
+    if !seen_signature {
+        bail!("Chybí JP2 signature box");
+    }
+    if !seen_ftyp {
+        bail!("Chybí ftyp box");
+    }
+    if !seen_jp2h {
+        bail!("Chybí jp2h box");
+    }
+    if !seen_codestream {
+        bail!("Chybí codestream (jp2c)");
+    }
+    Ok(info)
+}
+
+/// Vrací (typ, délka hlavičky, celková délka boxu)
+fn read_box_header<R: Read>(r: &mut R, remaining: u64) -> Result<([u8; 4], u64, u64)> {
+    let len = read_u32(r)? as u64;
+    let box_type = read_array::<4>(r)?;
+    let (header_len, box_len) = match len {
+        0 => (8, remaining),
+        1 => (16, read_u64(r)?),
+        n => (8, n),
+    };
+    if box_len < header_len || box_len > remaining {
+        bail!("Poškozený box `{}`", String::from_utf8_lossy(&box_type));
+    }
+    Ok((box_type, header_len, box_len))
+}
+
+/// Projde podboxy `jp2h` (ihdr, colr)
+fn read_jp2h<R: Read + Seek>(r: &mut R, len: u64, info: &mut Jp2Info) -> Result<()> {
+    let start = r.stream_position()?;
+    let mut pos = 0u64;
+    while pos < len {
+        r.seek(SeekFrom::Start(start + pos))?;
+        let (box_type, header_len, box_len) = read_box_header(r, len - pos)?;
+        if &box_type == b"colr" && info.colour.is_none() {
+            let content = read_vec(r, (box_len - header_len) as usize)?;
+            info.colour = Some(parse_colr(&content)?);
+        }
+        pos += box_len;
+    }
+    Ok(())
+}
+
+fn parse_colr(content: &[u8]) -> Result<String> {
+    let Some(&method) = content.first() else {
+        bail!("Prázdný colr box");
+    };
+    match method {
+        1 => {
+            let cs = content
+                .get(3..7)
+                .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| anyhow!("Zkrácený colr box"))?;
+            Ok(match cs {
+                16 => "sRGB".to_string(),
+                17 => "greyscale".to_string(),
+                18 => "sYCC".to_string(),
+                other => format!("enumCS {other}"),
+            })
+        }
+        2 | 3 => {
+            if content.len() <= 3 {
+                bail!("colr box bez ICC profilu");
+            }
+            Ok("ICC".to_string())
+        }
+        other => Ok(format!("metoda {other}")),
+    }
+}
+
+/// Čte markery hlavní hlavičky až po první SOT
+fn read_main_header<R: Read>(r: &mut R, info: &mut Jp2Info) -> Result<()> {
+    if read_u16(r)? != SOC {
+        bail!("Codestream nezačíná markerem SOC");
+    }
+    let mut seen_siz = false;
+    let mut seen_cod = false;
+    let mut seen_qcd = false;
+
+    loop {
+        let marker = read_u16(r)?;
+        if marker == SOT {
+            break;
+        }
+        if marker >> 8 != 0xFF {
+            bail!("Neplatný marker 0x{marker:04X} v hlavní hlavičce");
+        }
+        let seg_len = read_u16(r)? as usize;
+        if seg_len < 2 {
+            bail!("Neplatná délka segmentu markeru 0x{marker:04X}");
+        }
+        let seg = read_vec(r, seg_len - 2)?;
+        match marker {
+            SIZ => {
+                parse_siz(&seg, info)?;
+                seen_siz = true;
+            }
+            COD => {
+                parse_cod(&seg, info)?;
+                seen_cod = true;
+            }
+            QCD => {
+                let sqcd = *seg.first().ok_or_else(|| anyhow!("Prázdný QCD"))?;
+                info.quantization = match sqcd & 0x1F {
+                    0 => "none",
+                    1 => "derived",
+                    2 => "expounded",
+                    _ => "unknown",
+                }
+                .to_string();
+                seen_qcd = true;
+            }
+            TLM => info.tlm = true,
+            _ => {}
+        }
+    }
+
+    if !seen_siz {
+        bail!("Chybí marker SIZ");
+    }
+    if !seen_cod {
+        bail!("Chybí marker COD");
+    }
+    if !seen_qcd {
+        bail!("Chybí marker QCD");
+    }
+    Ok(())
+}
+
+fn parse_siz(seg: &[u8], info: &mut Jp2Info) -> Result<()> {
+    if seg.len() < 36 {
+        bail!("Zkrácený marker SIZ");
+    }
+    let u32_at = |i: usize| u32::from_be_bytes([seg[i], seg[i + 1], seg[i + 2], seg[i + 3]]);
+    // Rsiz(2) Xsiz Ysiz XOsiz YOsiz XTsiz YTsiz XTOsiz YTOsiz Csiz(2)
+    let (xsiz, ysiz, xosiz, yosiz) = (u32_at(2), u32_at(6), u32_at(10), u32_at(14));
+    info.width = xsiz.saturating_sub(xosiz);
+    info.height = ysiz.saturating_sub(yosiz);
+    info.tile_width = u32_at(18);
+    info.tile_height = u32_at(22);
+    info.components = u16::from_be_bytes([seg[34], seg[35]]);
+    Ok(())
+}
+
+fn parse_cod(seg: &[u8], info: &mut Jp2Info) -> Result<()> {
+    if seg.len() < 10 {
+        bail!("Zkrácený marker COD");
+    }
+    let scod = seg[0];
+    info.sop = scod & 0x02 != 0;
+    info.eph = scod & 0x04 != 0;
+    info.progression = match seg[1] {
+        0 => "LRCP",
+        1 => "RLCP",
+        2 => "RPCL",
+        3 => "PCRL",
+        4 => "CPRL",
+        _ => "?",
+    }
+    .to_string();
+    info.layers = u16::from_be_bytes([seg[2], seg[3]]);
+    // seg[4] = MCT
+    info.decomposition_levels = seg[5];
+    // Exponenty code-blocku jsou podle normy nejvýš 8 (1024 px)
+    let (xcb, ycb) = (seg[6], seg[7]);
+    if xcb > 8 || ycb > 8 {
+        bail!("Neplatná velikost code-blocku v COD (xcb {xcb}, ycb {ycb})");
+    }
+    info.code_block = (1 << (xcb + 2), 1 << (ycb + 2));
+    // seg[8] = styl code-blocků
+    info.reversible = seg[9] == 1;
+    if scod & 0x01 != 0 {
+        let count = info.decomposition_levels as usize + 1;
+        let pp = seg
+            .get(10..10 + count)
+            .ok_or_else(|| anyhow!("Zkrácené precinkty v COD"))?;
+        info.precincts = pp
+            .iter()
+            .map(|b| (1u32 << (b & 0x0F), 1u32 << (b >> 4)))
+            .collect();
+    }
+    Ok(())
+}
+
+/// Porovná vyčtené hodnoty s profilem, vrací seznam porušení
+fn validate(info: &Jp2Info, p: &GrokParams) -> Vec<String> {
+    let mut v = Vec::new();
+
+    if info.reversible == p.irreversible {
+        v.push(format!(
+            "vlnka {} (očekáváno {})",
+            wavelet_label(info.reversible),
+            wavelet_label(!p.irreversible)
+        ));
+    }
+    let expected_quant_ok = if p.irreversible {
+        info.quantization != "none"
+    } else {
+        info.quantization == "none"
+    };
+    if !expected_quant_ok {
+        v.push(format!("kvantizace QCD `{}` neodpovídá vlnce", info.quantization));
+    }
+
+    if let Some((tw, th)) = parse_pair(&p.tile_size) {
+        // Dlaždice větší než obrázek jsou rovnocenné celému obrázku
+        let same_w = info.tile_width == tw || (tw >= info.width && info.tile_width >= info.width);
+        let same_h = info.tile_height == th || (th >= info.height && info.tile_height >= info.height);
+        if !(same_w && same_h) {
+            v.push(format!(
+                "dlaždice {}x{} (očekáváno {}x{})",
+                info.tile_width, info.tile_height, tw, th
+            ));
+        }
+    }
+
+    if !info.progression.eq_ignore_ascii_case(&p.progression) {
+        v.push(format!(
+            "progrese {} (očekáváno {})",
+            info.progression, p.progression
+        ));
+    }
+
+    let expected_levels = p.resolutions.saturating_sub(1);
+    if info.decomposition_levels as u32 != expected_levels {
+        v.push(format!(
+            "úrovně dekompozice {} (očekáváno {})",
+            info.decomposition_levels, expected_levels
+        ));
+    }
+
+    if let Some((cw, ch)) = parse_pair(&p.code_block)
+        && info.code_block != (cw, ch)
+    {
+        v.push(format!(
+            "code-block {}x{} (očekáváno {}x{})",
+            info.code_block.0, info.code_block.1, cw, ch
+        ));
+    }
+
+    if let Some(ref precincts) = p.precincts {
+        let expected = parse_precincts(precincts);
+        if info.precincts.is_empty() {
+            v.push("chybí precinkty v COD".to_string());
+        } else {
+            // V profilu jsou od nejvyššího rozlišení, v COD od nejnižšího.
+            // Kontrolujeme jen rozlišení, která profil zadává explicitně.
+            for (k, exp) in expected.iter().enumerate() {
+                let Some(actual) = info.precincts.len().checked_sub(k + 1).map(|i| info.precincts[i]) else {
+                    break;
+                };
+                if actual != *exp {
+                    v.push(format!(
+                        "precinkt rozlišení r{} {}x{} (očekáváno {}x{})",
+                        info.precincts.len() - k - 1,
+                        actual.0,
+                        actual.1,
+                        exp.0,
+                        exp.1
+                    ));
+                }
+            }
+        }
+    }
+
+    let expected_layers = p
+        .rates
+        .as_ref()
+        .map(|r| r.split(',').filter(|s| !s.trim().is_empty()).count())
+        .unwrap_or(1);
+    if info.layers as usize != expected_layers {
+        v.push(format!(
+            "kvalitativní vrstvy {} (očekáváno {})",
+            info.layers, expected_layers
+        ));
+    }
+
+    if info.sop != p.sop {
+        v.push(format!("SOP markery {}", presence_label(info.sop)));
+    }
+    if info.eph != p.eph {
+        v.push(format!("EPH markery {}", presence_label(info.eph)));
+    }
+    if p.tlm && !info.tlm {
+        v.push("chybí TLM marker".to_string());
+    }
+
+    if info.colour.is_none() {
+        v.push("chybí colr box (barevný prostor / ICC)".to_string());
+    }
+
+    v
+}
+
+fn wavelet_label(reversible: bool) -> &'static str {
+    if reversible {
+        "5-3 bezeztrátová"
+    } else {
+        "9-7 ztrátová"
+    }
+}
+
+fn presence_label(present: bool) -> &'static str {
+    if present {
+        "navíc"
+    } else {
+        "chybí"
+    }
+}
+
+/// "4096,4096" -> (4096, 4096)
+fn parse_pair(s: &str) -> Option<(u32, u32)> {
+    let (a, b) = s.split_once(',')?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+/// "[256,256],[128,128]" -> [(256, 256), (128, 128)]
+fn parse_precincts(s: &str) -> Vec<(u32, u32)> {
+    s.split(']')
+        .filter_map(|part| parse_pair(part.trim_start_matches([',', '[', ' '])))
+        .collect()
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf).context("Neočekávaný konec souboru")?;
+    Ok(buf)
+}
+
+fn read_vec(r: &mut impl Read, len: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    r.read_exact(&mut buf).context("Neočekávaný konec souboru")?;
+    Ok(buf)
+}
+
+fn read_u16(r: &mut impl Read) -> Result<u16> {
+    Ok(u16::from_be_bytes(read_array::<2>(r)?))
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    Ok(u32::from_be_bytes(read_array::<4>(r)?))
+}
+
+fn read_u64(r: &mut impl Read) -> Result<u64> {
+    Ok(u64::from_be_bytes(read_array::<8>(r)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// COD: Scod, progrese, vrstvy, MCT, úrovně, xcb, ycb, styl, vlnka
+    fn cod(scod: u8, levels: u8, xcb: u8, ycb: u8) -> Vec<u8> {
+        vec![scod, 2, 0, 12, 1, levels, xcb, ycb, 0, 0]
+    }
+
+    fn siz(width: u32, height: u32) -> Vec<u8> {
+        let mut seg = vec![0, 0];
+        for v in [width, height, 0, 0, width, height, 0, 0] {
+            seg.extend(v.to_be_bytes());
+        }
+        seg.extend(3u16.to_be_bytes());
+        seg
+    }
+
+    fn marker(code: u16, seg: &[u8]) -> Vec<u8> {
+        let mut out = code.to_be_bytes().to_vec();
+        out.extend(((seg.len() + 2) as u16).to_be_bytes());
+        out.extend(seg);
+        out
+    }
+
+    fn codestream(segments: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = SOC.to_be_bytes().to_vec();
+        for s in segments {
+            out.extend(s);
+        }
+        out.extend(SOT.to_be_bytes());
+        out
+    }
+
+    #[test]
+    fn parse_cod_reads_fields() {
+        let mut info = Jp2Info::default();
+        parse_cod(&cod(0x06, 5, 4, 4), &mut info).unwrap();
+        assert_eq!(info.progression, "RPCL");
+        assert_eq!(info.layers, 12);
+        assert_eq!(info.decomposition_levels, 5);
+        assert_eq!(info.code_block, (64, 64));
+        assert!(info.sop && info.eph);
+        assert!(!info.reversible);
+        assert!(info.precincts.is_empty());
+    }
+
+    #[test]
+    fn parse_cod_reads_precincts() {
+        let mut seg = cod(0x01, 2, 4, 4);
+        seg.extend([0x77, 0x88, 0xFF]);
+        let mut info = Jp2Info::default();
+        parse_cod(&seg, &mut info).unwrap();
+        assert_eq!(info.precincts, vec![(128, 128), (256, 256), (32768, 32768)]);
+    }
+
+    #[test]
+    fn parse_cod_rejects_malformed() {
+        let mut info = Jp2Info::default();
+        assert!(parse_cod(&[0; 9], &mut info).is_err());
+        // Precinkty ohlášené, ale chybí
+        assert!(parse_cod(&cod(0x01, 5, 4, 4), &mut info).is_err());
+        // Exponent code-blocku mimo normu (dřív přetečení posunu)
+        assert!(parse_cod(&cod(0, 5, 9, 4), &mut info).is_err());
+        assert!(parse_cod(&cod(0, 5, 4, 0xFF), &mut info).is_err());
+    }
+
+    #[test]
+    fn parse_siz_reads_size_and_rejects_truncated() {
+        let mut info = Jp2Info::default();
+        parse_siz(&siz(2000, 3000), &mut info).unwrap();
+        assert_eq!((info.width, info.height), (2000, 3000));
+        assert_eq!(info.components, 3);
+        assert!(parse_siz(&siz(1, 1)[..35], &mut info).is_err());
+    }
+
+    #[test]
+    fn parse_colr_methods() {
+        assert_eq!(parse_colr(&[1, 0, 0, 0, 0, 0, 16]).unwrap(), "sRGB");
+        assert_eq!(parse_colr(&[1, 0, 0, 0, 0, 0, 17]).unwrap(), "greyscale");
+        assert_eq!(parse_colr(&[2, 0, 0, 1, 2, 3]).unwrap(), "ICC");
+        assert!(parse_colr(&[]).is_err());
+        assert!(parse_colr(&[1, 0, 0, 0]).is_err());
+        assert!(parse_colr(&[2, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn read_box_header_rejects_bad_lengths() {
+        let mut data = 20u32.to_be_bytes().to_vec();
+        data.extend(b"jp2h");
+        assert_eq!(read_box_header(&mut Cursor::new(&data), 100).unwrap(), (*b"jp2h", 8, 20));
+        // Box delší než zbytek souboru
+        assert!(read_box_header(&mut Cursor::new(&data), 10).is_err());
+        // Délka menší než hlavička
+        let mut data = 4u32.to_be_bytes().to_vec();
+        data.extend(b"jp2h");
+        assert!(read_box_header(&mut Cursor::new(&data), 100).is_err());
+        // Zkrácená hlavička
+        assert!(read_box_header(&mut Cursor::new(&[0u8, 0, 0]), 100).is_err());
+    }
+
+    #[test]
+    fn read_main_header_reads_markers() {
+        let data = codestream(&[
+            marker(SIZ, &siz(100, 200)),
+            marker(COD, &cod(0, 5, 4, 4)),
+            marker(QCD, &[0x22]),
+            marker(TLM, &[0, 0]),
+        ]);
+        let mut info = Jp2Info::default();
+        read_main_header(&mut Cursor::new(data), &mut info).unwrap();
+        assert_eq!((info.width, info.height), (100, 200));
+        assert_eq!(info.quantization, "expounded");
+        assert!(info.tlm);
+    }
+
+    #[test]
+    fn read_main_header_rejects_malformed() {
+        let mut info = Jp2Info::default();
+        // Bez SOC
+        assert!(read_main_header(&mut Cursor::new(vec![0xFF, 0x51]), &mut info).is_err());
+        // Chybí COD
+        let data = codestream(&[marker(SIZ, &siz(1, 1)), marker(QCD, &[0])]);
+        assert!(read_main_header(&mut Cursor::new(data), &mut info).is_err());
+        // Délka segmentu menší než 2
+        let mut data = SOC.to_be_bytes().to_vec();
+        data.extend([0xFF, 0x52, 0, 1]);
+        assert!(read_main_header(&mut Cursor::new(data), &mut info).is_err());
+        // Segment delší než data
+        let mut data = SOC.to_be_bytes().to_vec();
+        data.extend([0xFF, 0x52, 0xFF, 0xFF, 0]);
+        assert!(read_main_header(&mut Cursor::new(data), &mut info).is_err());
+        // Neplatný marker
+        let mut data = SOC.to_be_bytes().to_vec();
+        data.extend([0x12, 0x34]);
+        assert!(read_main_header(&mut Cursor::new(data), &mut info).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+// src/languages.rs
+// Jazyky Tesseractu: nainstalované `*.traineddata` v tessdata adresáři
+// (nebo `tesseract --list-langs`) a české názvy známých jazyků pro menu.
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Známé jazyky a skripty Tesseractu (kód, název)
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("afr", "afrikánština"),
+    ("amh", "amharština"),
+    ("ara", "arabština"),
+    ("asm", "ásámština"),
+    ("aze", "ázerbájdžánština"),
+    ("aze_cyrl", "ázerbájdžánština (cyrilice)"),
+    ("bel", "běloruština"),
+    ("ben", "bengálština"),
+    ("bod", "tibetština"),
+    ("bos", "bosenština"),
+    ("bre", "bretonština"),
+    ("bul", "bulharština"),
+    ("cat", "katalánština"),
+    ("ceb", "cebuánština"),
+    ("ces", "čeština"),
+    ("chi_sim", "čínština (zjednodušená)"),
+    ("chi_sim_vert", "čínština (zjednodušená, vertikální)"),
+    ("chi_tra", "čínština (tradiční)"),
+    ("chi_tra_vert", "čínština (tradiční, vertikální)"),
+    ("chr", "čerokézština"),
+    ("cos", "korsičtina"),
+    ("cym", "velština"),
+    ("dan", "dánština"),
+    ("deu", "němčina"),
+    ("deu_latf", "němčina (fraktur)"),
+    ("div", "maledivština"),
+    ("dzo", "dzongkä"),
+    ("ell", "řečtina"),
+    ("eng", "angličtina"),
+    ("enm", "střední angličtina"),
+    ("epo", "esperanto"),
+    ("equ", "matematika"),
+    ("est", "estonština"),
+    ("eus", "baskičtina"),
+    ("fao", "faerština"),
+    ("fas", "perština"),
+    ("fil", "filipínština"),
+    ("fin", "finština"),
+    ("fra", "francouzština"),
+    ("frm", "střední francouzština"),
+    ("fry", "fríština"),
+    ("gla", "skotská gaelština"),
+    ("gle", "irština"),
+    ("glg", "galicijština"),
+    ("grc", "stará řečtina"),
+    ("guj", "gudžarátština"),
+    ("hat", "haitština"),
+    ("heb", "hebrejština"),
+    ("hin", "hindština"),
+    ("hrv", "chorvatština"),
+    ("hun", "maďarština"),
+    ("hye", "arménština"),
+    ("iku", "inuktitutština"),
+    ("ind", "indonéština"),
+    ("isl", "islandština"),
+    ("ita", "italština"),
+    ("ita_old", "stará italština"),
+    ("jav", "jávština"),
+    ("jpn", "japonština"),
+    ("jpn_vert", "japonština (vertikální)"),
+    ("kan", "kannadština"),
+    ("kat", "gruzínština"),
+    ("kat_old", "stará gruzínština"),
+    ("kaz", "kazaština"),
+    ("khm", "khmerština"),
+    ("kir", "kyrgyzština"),
+    ("kmr", "kurdština (kurmanji)"),
+    ("kor", "korejština"),
+    ("kor_vert", "korejština (vertikální)"),
+    ("lao", "laoština"),
+    ("lat", "latina"),
+    ("lav", "lotyština"),
+    ("lit", "litevština"),
+    ("ltz", "lucemburština"),
+    ("mal", "malajálamština"),
+    ("mar", "maráthština"),
+    ("mkd", "makedonština"),
+    ("mlt", "maltština"),
+    ("mon", "mongolština"),
+    ("mri", "maorština"),
+    ("msa", "malajština"),
+    ("mya", "barmština"),
+    ("nep", "nepálština"),
+    ("nld", "nizozemština"),
+    ("nor", "norština"),
+    ("oci", "okcitánština"),
+    ("ori", "urijština"),
+    ("osd", "orientace a detekce skriptu"),
+    ("pan", "pandžábština"),
+    ("pol", "polština"),
+    ("por", "portugalština"),
+    ("pus", "paštština"),
+    ("que", "kečuánština"),
+    ("ron", "rumunština"),
+    ("rus", "ruština"),
+    ("san", "sanskrt"),
+    ("sin", "sinhálština"),
+    ("slk", "slovenština"),
+    ("slv", "slovinština"),
+    ("snd", "sindhština"),
+    ("spa", "španělština"),
+    ("spa_old", "stará španělština"),
+    ("sqi", "albánština"),
+    ("srp", "srbština (cyrilice)"),
+    ("srp_latn", "srbština (latinka)"),
+    ("sun", "sundánština"),
+    ("swa", "svahilština"),
+    ("swe", "švédština"),
+    ("syr", "syrština"),
+    ("tam", "tamilština"),
+    ("tat", "tatarština"),
+    ("tel", "telugština"),
+    ("tgk", "tádžičtina"),
+    ("tha", "thajština"),
+    ("tir", "tigrinijština"),
+    ("ton", "tongánština"),
+    ("tur", "turečtina"),
+    ("uig", "ujgurština"),
+    ("ukr", "ukrajinština"),
+    ("urd", "urdština"),
+    ("uzb", "uzbečtina (latinka)"),
+    ("uzb_cyrl", "uzbečtina (cyrilice)"),
+    ("vie", "vietnamština"),
+    ("yid", "jidiš"),
+    ("yor", "jorubština"),
+    // Skripty
+    ("script/Arabic", "arabské písmo"),
+    ("script/Armenian", "arménské písmo"),
+    ("script/Bengali", "bengálské písmo"),
+    ("script/Canadian_Aboriginal", "domorodé kanadské písmo"),
+    ("script/Cherokee", "čerokézské písmo"),
+    ("script/Cyrillic", "cyrilice"),
+    ("script/Devanagari", "dévanágarí"),
+    ("script/Ethiopic", "etiopské písmo"),
+    ("script/Fraktur", "fraktura"),
+    ("script/Georgian", "gruzínské písmo"),
+    ("script/Greek", "řecké písmo"),
+    ("script/Gujarati", "gudžarátské písmo"),
+    ("script/Gurmukhi", "gurmukhí"),
+    ("script/HanS", "čínské písmo (zjednodušené)"),
+    ("script/HanS_vert", "čínské písmo (zjednodušené, vertikální)"),
+    ("script/HanT", "čínské písmo (tradiční)"),
+    ("script/HanT_vert", "čínské písmo (tradiční, vertikální)"),
+    ("script/Hangul", "hangul (korejské písmo)"),
+    ("script/Hangul_vert", "hangul (korejské písmo, vertikální)"),
+    ("script/Hebrew", "hebrejské písmo"),
+    ("script/Japanese", "japonské písmo"),
+    ("script/Japanese_vert", "japonské písmo (vertikální)"),
+    ("script/Kannada", "kannadské písmo"),
+    ("script/Khmer", "khmerské písmo"),
+    ("script/Lao", "laoské písmo"),
+    ("script/Latin", "latinka"),
+    ("script/Malayalam", "malajálamské písmo"),
+    ("script/Myanmar", "myanmarské písmo"),
+    ("script/Oriya", "orijské písmo"),
+    ("script/Sinhala", "sinhálské písmo"),
+    ("script/Syriac", "syrské písmo"),
+    ("script/Tamil", "tamilské písmo"),
+    ("script/Telugu", "telugské písmo"),
+    ("script/Thaana", "thaana (maledivské písmo)"),
+    ("script/Thai", "thajské písmo"),
+    ("script/Tibetan", "tibetské písmo"),
+    ("script/Vietnamese", "vietnamské písmo"),
+];
+
+/// Odkud pochází seznam nainstalovaných jazyků
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LanguageSource {
+    /// `*.traineddata` v tessdata adresáři
+    Tessdata(PathBuf),
+    /// Výstup `tesseract --list-langs`
+    ListLangs,
+    /// Nepodařilo se zjistit – jazyky se neblokují
+    Unknown,
+}
+
+/// Nainstalované jazyky Tesseractu
+#[derive(Debug, Clone)]
+pub struct InstalledLanguages {
+    pub codes: BTreeSet<String>,
+    pub source: LanguageSource,
+}
+
+/// Položka menu jazyků
+#[derive(Debug, Clone)]
+pub struct LanguageEntry {
+    pub code: String,
+    pub name: String,
+    pub installed: bool,
+}
+
+impl InstalledLanguages {
+    /// Zjistí jazyky: nejprve z tessdata adresáře (stejná logika jako při OCR),
+    /// jinak z `tesseract --list-langs` (ne v dry-run).
+    pub fn discover(tess_path: &Path, tessdata_dir: Option<&Path>, dry_run: bool) -> Self {
+        if let Some(dir) = resolve_tessdata(tessdata_dir) {
+            let codes = scan_tessdata(&dir);
+            if !codes.is_empty() {
+                return Self {
+                    codes,
+                    source: LanguageSource::Tessdata(dir),
+                };
+            }
+        }
+        if !dry_run
+            && let Some(codes) = list_langs(tess_path)
+        {
+            return Self {
+                codes,
+                source: LanguageSource::ListLangs,
+            };
+        }
+        Self {
+            codes: BTreeSet::new(),
+            source: LanguageSource::Unknown,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.source != LanguageSource::Unknown
+    }
+
+    /// Jazyky z kombinace (`eng+ces`), které nejsou nainstalované.
+    /// Pokud seznam nelze zjistit, nechybí nic.
+    pub fn missing(&self, lang: &str) -> Vec<String> {
+        if !self.is_known() {
+            return Vec::new();
+        }
+        lang.split('+')
+            .map(str::trim)
+            .filter(|code| !code.is_empty() && !self.codes.contains(*code))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.source {
+            LanguageSource::Tessdata(dir) => {
+                format!("{} jazyků v {}", self.codes.len(), dir.display())
+            }
+            LanguageSource::ListLangs => {
+                format!("{} jazyků (tesseract --list-langs)", self.codes.len())
+            }
+            LanguageSource::Unknown => "nelze zjistit – jazyky se nekontrolují".to_string(),
+        }
+    }
+
+    /// Položky menu: nainstalované jazyky (i vlastní traineddata), pak chybějící známé
+    pub fn menu_entries(&self) -> Vec<LanguageEntry> {
+        let mut entries: Vec<LanguageEntry> = self
+            .codes
+            .iter()
+            .filter(|code| code.as_str() != "osd")
+            .map(|code| LanguageEntry {
+                code: code.clone(),
+                name: language_name(code)
+                    .unwrap_or("vlastní traineddata")
+                    .to_string(),
+                installed: true,
+            })
+            .collect();
+        entries.extend(
+            LANGUAGE_NAMES
+                .iter()
+                .filter(|(code, _)| !self.codes.contains(*code))
+                .map(|(code, name)| LanguageEntry {
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    // Bez seznamu nainstalovaných nelze nic označit jako chybějící
+                    installed: !self.is_known(),
+                }),
+        );
+        entries
+    }
+}
+
+fn language_name(code: &str) -> Option<&'static str> {
+    LANGUAGE_NAMES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+/// Adresář s `*.traineddata`: `<dir>/tessdata`, nebo `<dir>` samotný,
+/// bez zadaného adresáře `$TESSDATA_PREFIX`
+fn resolve_tessdata(tessdata_dir: Option<&Path>) -> Option<PathBuf> {
+    let dir = match tessdata_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => PathBuf::from(std::env::var_os("TESSDATA_PREFIX")?),
+    };
+    let sub = dir.join("tessdata");
+    if sub.is_dir() {
+        Some(sub)
+    } else if dir.is_dir() {
+        Some(dir)
+    } else {
+        None
+    }
+}
+
+/// Kódy jazyků podle `*.traineddata` (skripty v `script/` jako `script/Latin`)
+fn scan_tessdata(dir: &Path) -> BTreeSet<String> {
+    let mut codes = BTreeSet::new();
+    for (sub, prefix) in [(dir.to_path_buf(), ""), (dir.join("script"), "script/")] {
+        let Ok(entries) = fs::read_dir(&sub) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("traineddata")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                codes.insert(format!("{prefix}{stem}"));
+            }
+        }
+    }
+    codes
+}
+
+/// `tesseract --list-langs`: první řádek je hlavička, pak jeden kód na řádek
+fn list_langs(tess_path: &Path) -> Option<BTreeSet<String>> {
+    let output = Command::new(tess_path).arg("--list-langs").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Starší verze píší seznam na stderr
+    let text = if output.stdout.is_empty() {
+        String::from_utf8_lossy(&output.stderr).to_string()
+    } else {
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let codes: BTreeSet<String> = text
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!codes.is_empty()).then_some(codes)
+}
@@ -0,0 +1,4 @@
+// src/lib.rs
+//! Moduly sdílené binárkami `lazyproarcconvert` a `lazyalto`
+pub mod hashing;
+pub mod outputs;
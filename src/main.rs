@@ -1,5 +1,5 @@
-use anyhow::{anyhow, Context, Result};
-use clap::Parser;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{CommandFactory, FromArgMatches, Parser};
 use chrono::Local;
 use crossterm::{
     event::{
@@ -17,26 +17,71 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Paragraph, ListState},
     Frame, Terminal,
 };
+use std::collections::BTreeMap;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-mod blake3;
+mod alto;
+mod config;
 mod manifest;
+mod numbering;
+mod ocr;
+mod ordering;
+mod pdf;
+mod preflight;
 mod previews;
+mod profiles;
+mod quality;
+mod headless;
 mod html;
-
+mod inputs;
+mod jp2check;
+mod languages;
+mod scheduler;
+mod state;
+mod verify;
+mod worker;
+
+use alto::{AltoUnit, AltoVersion, PostProcess};
+use config::{load_config, Config};
+use lazyproarcconvert::{hashing, outputs};
+use hashing::{compute_digests, HashAlgorithm};
+use headless::{CliCommand, HeadlessEnv};
 use manifest::build_manifest_for_batch;
+use numbering::{assign_starts, BatchNumbering, NumberingMode};
+use ocr::{OcrField, OcrSettings};
+use ordering::{natural_order, page_order, PageOrder};
+use pdf::{merge_pdfs, merged_pdf_name};
+use preflight::{preflight_batch, read_tiff_info, write_preflight_report, BatchPreflight, PreflightRules, RuleAction};
 use previews::generate_webp_previews;
+use profiles::{find_profile, load_profiles, EncodingProfile, GrokParams};
+use quality::{BatchQuality, Dictionary, PageSort};
 use crate::html::write_html_report;
+use languages::{InstalledLanguages, LanguageEntry};
+use inputs::{expand_pages, prepare_input, InputFormat, PageSource};
+use scheduler::{
+    capped_limits_warning, not_started, resolve_limits, run_pages, Limits, NotStarted, PageOutcome, Tool,
+};
+use state::{BatchState, StateTracker, Step};
+use worker::{JobControl, JobLog, JobRequest, Worker, WorkerEvent};
 
 /// LazyProArcConvert
 /// Batch wrapper kolem Grok JP2 komprese + Tesseract OCR/ALTO,
 /// s Ratatui TUI rozhraním.
 #[derive(Parser, Debug, Clone)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = "Návratové kódy bez TUI: 0 = OK, 1 = chyba programu / konfigurace, \
+                  2 = chybné argumenty, 3 = některé dávky selhaly / neprošly kontrolou, \
+                  4 = chybí Grok / Tesseract"
+)]
 struct Args {
     /// Kořenový vstupní adresář (v něm budou dávky jako podadresáře)
     #[arg(short, long)]
@@ -46,10 +91,25 @@ struct Args {
     #[arg(short, long)]
     output: Option<PathBuf>,
 
+    /// Hledat dávky rekurzivně: dávkou je každý adresář se vstupními soubory,
+    /// výstupy a logy kopírují relativní cestu (titul/rok/číslo)
+    #[arg(long)]
+    recursive: bool,
+
+    /// Max. hloubka podadresářů při --recursive (výchozí bez omezení)
+    #[arg(long)]
+    max_depth: Option<usize>,
+
     /// Počáteční index (####) pro první stránku v první dávce
     #[arg(long, default_value_t = 1)]
     start_index: u32,
 
+    /// Číslování stránek: global = souvisle přes všechny dávky,
+    /// per-batch = každá dávka od --start-index. Ručně zadaný začátek
+    /// dávky (klávesa N v TUI) platí vždy.
+    #[arg(long, value_enum, default_value = "global")]
+    numbering: NumberingMode,
+
     /// Šířka indexu (počet číslic)
     #[arg(long, default_value_t = 4)]
     digits: usize,
@@ -58,10 +118,38 @@ struct Args {
     #[arg(long, default_value = "ces")]
     lang: String,
 
-    /// Verze ALTO (např. 4.4, 4.0, 3.0)
-    #[arg(long, default_value = "4.4")]
+    /// Verze ALTO výstupu (2.0, 2.1, 3.0, 3.1, 4.0–4.4); Tesseract ALTO se na ni převede
+    #[arg(long, default_value = "4.4", value_parser = alto::parse_version_arg)]
     alto_version: String,
 
+    /// Jednotka souřadnic v upraveném ALTO (pixel, mm10, inch1200; převod podle DPI)
+    #[arg(long, value_enum)]
+    alto_unit: Option<AltoUnit>,
+
+    /// Tesseract: režim segmentace stránky (0–13, např. 4 = sloupce, 6 = jeden blok)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=13))]
+    psm: Option<u8>,
+
+    /// Tesseract: OCR engine (0 = legacy, 1 = LSTM, 2 = obojí, 3 = výchozí)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=3))]
+    oem: Option<u8>,
+
+    /// Tesseract: DPI vstupu, pokud ho obrázek neuvádí nebo je chybné
+    #[arg(long)]
+    dpi: Option<u32>,
+
+    /// Tesseract: soubor se slovy navíc (--user-words)
+    #[arg(long)]
+    user_words: Option<PathBuf>,
+
+    /// Tesseract: soubor se vzory (--user-patterns)
+    #[arg(long)]
+    user_patterns: Option<PathBuf>,
+
+    /// Tesseract: další proměnná `název=hodnota` (-c, lze opakovat)
+    #[arg(long = "tess-var", value_parser = ocr::parse_variable)]
+    tess_vars: Vec<(String, String)>,
+
     /// Cesta / název binárky Grok (grk_compress).
     /// "auto" = ./grok/bin/grk_compress(.exe) nebo ./grok/grk_compress(.exe), jinak PATH.
     #[arg(long, default_value = "auto")]
@@ -83,14 +171,73 @@ struct Args {
     /// Vynutit použití lokálního Tesseractu (ignorovat PATH)
     #[arg(long)]
     force_local_tess: bool,
+
+    /// Max. počet souběžně běžících procesů (stránky i kroky AC/UC/OCR jedné stránky).
+    /// Výchozí = součet --grok-jobs a --tess-jobs, bez nich 1. Menší hodnota
+    /// limity nástrojů omezí (program na to upozorní).
+    #[arg(short = 'j', long)]
+    jobs: Option<usize>,
+
+    /// Max. počet souběžných Grok procesů (výchozí = --jobs, jinak 1)
+    #[arg(long)]
+    grok_jobs: Option<usize>,
+
+    /// Max. počet souběžných Tesseract procesů (výchozí = --jobs, jinak 1)
+    #[arg(long)]
+    tess_jobs: Option<usize>,
+
+    /// JSON soubor s profily kódování Grok (výchozí ./profiles.json, pokud existuje)
+    #[arg(long)]
+    profiles: Option<PathBuf>,
+
+    /// Profil kódování pro všechny dávky (lze změnit po dávkách v TUI)
+    #[arg(long, default_value = profiles::BUILTIN_PROFILE)]
+    profile: String,
+
+    /// Algoritmy kontrolních součtů pro manifest a checksums soubory, oddělené
+    /// čárkou (md5, sha1, sha256, sha512, blake3). BLAKE3 se počítá vždy.
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "blake3")]
+    checksums: Vec<HashAlgorithm>,
+
+    /// Práh konfidence OCR (0–1): stránky s nižší průměrnou konfidencí slov
+    /// se v manifestu, HTML reportu a lazyalto označí k ruční kontrole.
+    #[arg(long, default_value_t = quality::DEFAULT_REVIEW_THRESHOLD, value_parser = quality::parse_threshold)]
+    review_threshold: f64,
+
+    /// Práh shody TXT se slovníkem (0–1): stránky pod ním TUI zvýrazní
+    /// a zobrazí ve filtru „jen shoda se slovníkem pod prahem“ (klávesa K)
+    #[arg(long, default_value_t = quality::DEFAULT_DICTIONARY_THRESHOLD, value_parser = quality::parse_threshold)]
+    dictionary_threshold: f64,
+
+    /// Slovník pro odhad kvality OCR z TXT: `jazyk=soubor` (kód jazyka
+    /// Tesseractu, jedno slovo na řádek; lze opakovat)
+    #[arg(long = "wordlist", value_parser = quality::parse_wordlist)]
+    wordlists: Vec<(String, PathBuf)>,
+
+    /// Pravidla kontroly vstupních TIFFů (jen z konfigurace)
+    #[arg(skip)]
+    preflight: PreflightRules,
+
+    /// Konfigurační soubor (výchozí ./lazyproarcconvert.json; uživatelská
+    /// konfigurace se načítá vždy). Hodnoty z příkazové řádky mají přednost.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Příkaz pro běh bez TUI; bez příkazu se spustí TUI
+    #[command(subcommand)]
+    command: Option<CliCommand>,
 }
 
 #[derive(Debug, Clone)]
 enum JobStatus {
     Pending,
+    Queued,     // Čeká ve frontě workeru
     Processing,
+    Paused,     // Pozastaveno – nové kroky se nespouští
     Done,
     Failed(String),
+    InvalidSettings(String), // Neplatný ocr.json – dávku nelze spustit
+    Cancelled,  // Zrušeno uživatelem, neúplné výstupy smazány
     AlreadyDone, // Nový stav - dávka byla již dříve zpracována
 }
 
@@ -98,12 +245,39 @@ enum JobStatus {
 struct BatchJob {
     /// Adresář dávky (input/něco)
     dir: PathBuf,
+    /// Název dávky = cesta od input_root ("titul/2020/01")
+    name: String,
     /// První index stránky pro tuto dávky
     index_start: u32,
-    /// Počet TIFF souborů v dávce
+    /// První index byl zadán ručně (numbering.json)
+    explicit_start: bool,
+    /// Počet vstupních souborů (stránek) v dávce
     file_count: usize,
     /// Stav dávky
     status: JobStatus,
+    /// Počet hotových stránek během zpracování
+    pages_done: usize,
+    /// Řízení běhu (pauza / zrušení), existuje od zařazení do fronty
+    control: Option<Arc<JobControl>>,
+    /// Číslo posledního zařazení do fronty; zprávy workeru z jiného
+    /// (např. zrušeného) běhu se ignorují
+    run: u64,
+    /// Příští běh zpracuje vše znovu (F/R), jinak se navazuje na stav dávky
+    force_full: bool,
+    /// Neúspěšné kroky z posledního běhu: (index, krok, důvod)
+    failed_pages: Vec<(String, &'static str, String)>,
+    /// Název profilu kódování Grok pro tuto dávku
+    profile: String,
+    /// Kontrola vstupních TIFFů při načtení dávky
+    preflight: BatchPreflight,
+    /// Pořadí stránek (order.txt nebo přirozené řazení)
+    order: PageOrder,
+    /// Nastavení Tesseractu pro tuto dávku
+    ocr: OcrSettings,
+    /// Dávka má vlastní nastavení OCR (ocr_settings.json)
+    ocr_custom: bool,
+    /// Kvalita OCR z manifestu posledního běhu
+    quality: Option<BatchQuality>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -111,9 +285,13 @@ enum UiMode {
     Normal,
     EditInput,
     EditOutput,
+    EditStartIndex,
     LanguageMenu,
     AltoVersionMenu,
+    ProfileMenu,
     CustomLangInput,
+    OcrSettingsMenu,
+    EditOcrField,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -129,6 +307,18 @@ enum ToolStatus {
     Error(String),
 }
 
+/// Nalezené nástroje, jazyky a profily kódování (pro TUI a `convert`)
+struct Tools {
+    grok_path: PathBuf,
+    grok_status: ToolStatus,
+    tess_path: PathBuf,
+    tess_source: String,
+    tess_status: ToolStatus,
+    tessdata_dir: Option<PathBuf>,
+    languages: InstalledLanguages,
+    profiles: Vec<EncodingProfile>,
+}
+
 struct App {
     args: Args,
     input_root: PathBuf,
@@ -139,27 +329,42 @@ struct App {
     do_user: bool,
     do_txt: bool,
     do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    /// Spojit PDF stránek do jednoho PDF dávky
+    merge_pdf: bool,
     log_lines: Vec<String>,
     mode: UiMode,
     edit_buffer: String,
     grok_path: PathBuf,
     grok_status: ToolStatus,
-    grok_version: String,
     tess_path: PathBuf,
     tess_source: String, // Uložíme odkud pochází
     tess_status: ToolStatus,
     focus: FocusedPane,
     detail_scroll: usize,
+    /// Řazení stránek podle kvality OCR v detailu dávky
+    page_sort: PageSort,
     log_scroll: usize,
     language_list_state: ListState,
     alto_version_list_state: ListState,
-    available_languages: Vec<(String, String)>, // (kód, název)
+    /// Nainstalované jazyky Tesseractu (tessdata / --list-langs)
+    installed_languages: InstalledLanguages,
+    available_languages: Vec<LanguageEntry>,
     available_alto_versions: Vec<String>,
     custom_lang_input: String,
     tessdata_dir: Option<PathBuf>,
+    profiles: Vec<EncodingProfile>,
+    profile_list_state: ListState,
+    ocr_list_state: ListState,
+    /// Kam klávesa S ukládá nastavení
+    config_path: PathBuf,
+    worker: Worker,
+    quit_armed: bool,
 }
 
 impl App {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         args: Args,
         input_root: PathBuf,
@@ -167,178 +372,18 @@ impl App {
         jobs: Vec<BatchJob>,
         grok_path: PathBuf,
         grok_status: ToolStatus,
-        grok_version: String,
         tess_path: PathBuf,
         tess_source: String,
         tess_status: ToolStatus,
         tessdata_dir: Option<PathBuf>,
+        installed_languages: InstalledLanguages,
+        profiles: Vec<EncodingProfile>,
+        config: &Config,
+        config_sources: &[PathBuf],
+        config_path: PathBuf,
     ) -> Self {
-        // Seznam dostupných jazyků pro Tesseract s názvy
-        let available_languages = vec![
-            ("afr".to_string(), "afrikánština".to_string()),
-            ("amh".to_string(), "amharština".to_string()),
-            ("ara".to_string(), "arabština".to_string()),
-            ("asm".to_string(), "ásámština".to_string()),
-            ("aze".to_string(), "ázerbájdžánština".to_string()),
-            ("aze_cyrl".to_string(), "ázerbájdžánština (cyrilice)".to_string()),
-            ("bel".to_string(), "běloruština".to_string()),
-            ("ben".to_string(), "bengálština".to_string()),
-            ("bod".to_string(), "tibetština".to_string()),
-            ("bos".to_string(), "bosenština".to_string()),
-            ("bre".to_string(), "bretonština".to_string()),
-            ("bul".to_string(), "bulharština".to_string()),
-            ("cat".to_string(), "katalánština".to_string()),
-            ("ceb".to_string(), "cebuánština".to_string()),
-            ("ces".to_string(), "čeština".to_string()),
-            ("chi_sim".to_string(), "čínština (zjednodušená)".to_string()),
-            ("chi_sim_vert".to_string(), "čínština (zjednodušená, vertikální)".to_string()),
-            ("chi_tra".to_string(), "čínština (tradiční)".to_string()),
-            ("chi_tra_vert".to_string(), "čínština (tradiční, vertikální)".to_string()),
-            ("chr".to_string(), "čerokézština".to_string()),
-            ("cos".to_string(), "korsičtina".to_string()),
-            ("cym".to_string(), "velština".to_string()),
-            ("dan".to_string(), "dánština".to_string()),
-            ("deu".to_string(), "němčina".to_string()),
-            ("deu_latf".to_string(), "němčina (fraktur)".to_string()),
-            ("div".to_string(), "maledivština".to_string()),
-            ("dzo".to_string(), "dzongkä".to_string()),
-            ("ell".to_string(), "řečtina".to_string()),
-            ("eng".to_string(), "angličtina".to_string()),
-            ("enm".to_string(), "střední angličtina".to_string()),
-            ("epo".to_string(), "esperanto".to_string()),
-            ("equ".to_string(), "matematika".to_string()),
-            ("est".to_string(), "estonština".to_string()),
-            ("eus".to_string(), "baskičtina".to_string()),
-            ("fao".to_string(), "faerština".to_string()),
-            ("fas".to_string(), "perština".to_string()),
-            ("fil".to_string(), "filipínština".to_string()),
-            ("fin".to_string(), "finština".to_string()),
-            ("fra".to_string(), "francouzština".to_string()),
-            ("frm".to_string(), "střední francouzština".to_string()),
-            ("fry".to_string(), "fríština".to_string()),
-            ("gla".to_string(), "skotská gaelština".to_string()),
-            ("gle".to_string(), "irština".to_string()),
-            ("glg".to_string(), "galicijština".to_string()),
-            ("grc".to_string(), "stará řečtina".to_string()),
-            ("guj".to_string(), "gudžarátština".to_string()),
-            ("hat".to_string(), "haitština".to_string()),
-            ("heb".to_string(), "hebrejština".to_string()),
-            ("hin".to_string(), "hindština".to_string()),
-            ("hrv".to_string(), "chorvatština".to_string()),
-            ("hun".to_string(), "maďarština".to_string()),
-            ("hye".to_string(), "arménština".to_string()),
-            ("iku".to_string(), "inuktitutština".to_string()),
-            ("ind".to_string(), "indonéština".to_string()),
-            ("isl".to_string(), "islandština".to_string()),
-            ("ita".to_string(), "italština".to_string()),
-            ("ita_old".to_string(), "stará italština".to_string()),
-            ("jav".to_string(), "jávština".to_string()),
-            ("jpn".to_string(), "japonština".to_string()),
-            ("jpn_vert".to_string(), "japonština (vertikální)".to_string()),
-            ("kan".to_string(), "kannadština".to_string()),
-            ("kat".to_string(), "gruzínština".to_string()),
-            ("kat_old".to_string(), "stará gruzínština".to_string()),
-            ("kaz".to_string(), "kazaština".to_string()),
-            ("khm".to_string(), "khmerština".to_string()),
-            ("kir".to_string(), "kyrgyzština".to_string()),
-            ("kmr".to_string(), "kurdština (kurmanji)".to_string()),
-            ("kor".to_string(), "korejština".to_string()),
-            ("kor_vert".to_string(), "korejština (vertikální)".to_string()),
-            ("lao".to_string(), "laoština".to_string()),
-            ("lat".to_string(), "latina".to_string()),
-            ("lav".to_string(), "lotyština".to_string()),
-            ("lit".to_string(), "litevština".to_string()),
-            ("ltz".to_string(), "lucemburština".to_string()),
-            ("mal".to_string(), "malajálamština".to_string()),
-            ("mar".to_string(), "maráthština".to_string()),
-            ("mkd".to_string(), "makedonština".to_string()),
-            ("mlt".to_string(), "maltština".to_string()),
-            ("mon".to_string(), "mongolština".to_string()),
-            ("mri".to_string(), "maorština".to_string()),
-            ("msa".to_string(), "malajština".to_string()),
-            ("mya".to_string(), "barmština".to_string()),
-            ("nep".to_string(), "nepálština".to_string()),
-            ("nld".to_string(), "nizozemština".to_string()),
-            ("nor".to_string(), "norština".to_string()),
-            ("oci".to_string(), "okcitánština".to_string()),
-            ("ori".to_string(), "urijština".to_string()),
-            ("osd".to_string(), "orientace a detekce skriptu".to_string()),
-            ("pan".to_string(), "pandžábština".to_string()),
-            ("pol".to_string(), "polština".to_string()),
-            ("por".to_string(), "portugalština".to_string()),
-            ("pus".to_string(), "paštština".to_string()),
-            ("que".to_string(), "kečuánština".to_string()),
-            ("ron".to_string(), "rumunština".to_string()),
-            ("rus".to_string(), "ruština".to_string()),
-            ("san".to_string(), "sanskrt".to_string()),
-            ("sin".to_string(), "sinhálština".to_string()),
-            ("slk".to_string(), "slovenština".to_string()),
-            ("slv".to_string(), "slovinština".to_string()),
-            ("snd".to_string(), "sindhština".to_string()),
-            ("spa".to_string(), "španělština".to_string()),
-            ("spa_old".to_string(), "stará španělština".to_string()),
-            ("sqi".to_string(), "albánština".to_string()),
-            ("srp".to_string(), "srbština (cyrilice)".to_string()),
-            ("srp_latn".to_string(), "srbština (latinka)".to_string()),
-            ("sun".to_string(), "sundánština".to_string()),
-            ("swa".to_string(), "svahilština".to_string()),
-            ("swe".to_string(), "švédština".to_string()),
-            ("syr".to_string(), "syrština".to_string()),
-            ("tam".to_string(), "tamilština".to_string()),
-            ("tat".to_string(), "tatarština".to_string()),
-            ("tel".to_string(), "telugština".to_string()),
-            ("tgk".to_string(), "tádžičtina".to_string()),
-            ("tha".to_string(), "thajština".to_string()),
-            ("tir".to_string(), "tigrinijština".to_string()),
-            ("ton".to_string(), "tongánština".to_string()),
-            ("tur".to_string(), "turečtina".to_string()),
-            ("uig".to_string(), "ujgurština".to_string()),
-            ("ukr".to_string(), "ukrajinština".to_string()),
-            ("urd".to_string(), "urdština".to_string()),
-            ("uzb".to_string(), "uzbečtina (latinka)".to_string()),
-            ("uzb_cyrl".to_string(), "uzbečtina (cyrilice)".to_string()),
-            ("vie".to_string(), "vietnamština".to_string()),
-            ("yid".to_string(), "jidiš".to_string()),
-            ("yor".to_string(), "jorubština".to_string()),
-            // Skripty (můžete je přidat jako speciální jazyky)
-            ("script/Arabic".to_string(), "arabské písmo".to_string()),
-            ("script/Armenian".to_string(), "arménské písmo".to_string()),
-            ("script/Bengali".to_string(), "bengálské písmo".to_string()),
-            ("script/Canadian_Aboriginal".to_string(), "domorodé kanadské písmo".to_string()),
-            ("script/Cherokee".to_string(), "čerokézské písmo".to_string()),
-            ("script/Cyrillic".to_string(), "cyrilice".to_string()),
-            ("script/Devanagari".to_string(), "dévanágarí".to_string()),
-            ("script/Ethiopic".to_string(), "etiopské písmo".to_string()),
-            ("script/Fraktur".to_string(), "fraktura".to_string()),
-            ("script/Georgian".to_string(), "gruzínské písmo".to_string()),
-            ("script/Greek".to_string(), "řecké písmo".to_string()),
-            ("script/Gujarati".to_string(), "gudžarátské písmo".to_string()),
-            ("script/Gurmukhi".to_string(), "gurmukhí".to_string()),
-            ("script/HanS".to_string(), "čínské písmo (zjednodušené)".to_string()),
-            ("script/HanS_vert".to_string(), "čínské písmo (zjednodušené, vertikální)".to_string()),
-            ("script/HanT".to_string(), "čínské písmo (tradiční)".to_string()),
-            ("script/HanT_vert".to_string(), "čínské písmo (tradiční, vertikální)".to_string()),
-            ("script/Hangul".to_string(), "hangul (korejské písmo)".to_string()),
-            ("script/Hangul_vert".to_string(), "hangul (korejské písmo, vertikální)".to_string()),
-            ("script/Hebrew".to_string(), "hebrejské písmo".to_string()),
-            ("script/Japanese".to_string(), "japonské písmo".to_string()),
-            ("script/Japanese_vert".to_string(), "japonské písmo (vertikální)".to_string()),
-            ("script/Kannada".to_string(), "kannadské písmo".to_string()),
-            ("script/Khmer".to_string(), "khmerské písmo".to_string()),
-            ("script/Lao".to_string(), "laoské písmo".to_string()),
-            ("script/Latin".to_string(), "latinka".to_string()),
-            ("script/Malayalam".to_string(), "malajálamské písmo".to_string()),
-            ("script/Myanmar".to_string(), "myanmarské písmo".to_string()),
-            ("script/Oriya".to_string(), "orijské písmo".to_string()),
-            ("script/Sinhala".to_string(), "sinhálské písmo".to_string()),
-            ("script/Syriac".to_string(), "syrské písmo".to_string()),
-            ("script/Tamil".to_string(), "tamilské písmo".to_string()),
-            ("script/Telugu".to_string(), "telugské písmo".to_string()),
-            ("script/Thaana".to_string(), "thaana (maledivské písmo)".to_string()),
-            ("script/Thai".to_string(), "thajské písmo".to_string()),
-            ("script/Tibetan".to_string(), "tibetské písmo".to_string()),
-            ("script/Vietnamese".to_string(), "vietnamské písmo".to_string()),
-        ];
+        // Nainstalované jazyky první, pak známé chybějící
+        let available_languages = installed_languages.menu_entries();
 
         // Seznam dostupných ALTO verzí
         let available_alto_versions = vec![
@@ -347,20 +392,16 @@ impl App {
             "4.2".to_string(),
             "4.1".to_string(),
             "4.0".to_string(),
+            "3.1".to_string(),
             "3.0".to_string(),
             "2.1".to_string(),
             "2.0".to_string(),
-            "1.4".to_string(),
-            "1.3".to_string(),
-            "1.2".to_string(),
-            "1.1".to_string(),
-            "1.0".to_string(),
         ];
 
         let mut language_list_state = ListState::default();
         let lang_index = available_languages
             .iter()
-            .position(|(code, _)| code == &args.lang)
+            .position(|entry| entry.code == args.lang)
             .unwrap_or(0);
         language_list_state.select(Some(lang_index));
 
@@ -377,28 +418,38 @@ impl App {
             output_root,
             jobs,
             selected: 0,
-            do_master: true,
-            do_user: true,
-            do_txt: true,
-            do_alto: true,
+            do_master: config.do_master.unwrap_or(true),
+            do_user: config.do_user.unwrap_or(true),
+            do_txt: config.do_txt.unwrap_or(true),
+            do_alto: config.do_alto.unwrap_or(true),
+            do_hocr: config.do_hocr.unwrap_or(false),
+            do_pdf: config.do_pdf.unwrap_or(false),
+            merge_pdf: config.merge_pdf.unwrap_or(false),
             log_lines: vec![],
             mode: UiMode::Normal,
             edit_buffer: String::new(),
             grok_path,
             grok_status,
-            grok_version,
             tess_path,
             tess_source,
             tess_status,
             focus: FocusedPane::Jobs,
             detail_scroll: 0,
+            page_sort: PageSort::default(),
             log_scroll: 0,
             language_list_state,
             alto_version_list_state,
+            installed_languages,
             available_languages,
             available_alto_versions,
             custom_lang_input: String::new(),
             tessdata_dir,
+            profiles,
+            profile_list_state: ListState::default(),
+            ocr_list_state: ListState::default(),
+            config_path,
+            worker: Worker::spawn(),
+            quit_armed: false,
         };
 
         // Kontrola, které dávky jsou již zpracovány
@@ -410,6 +461,9 @@ impl App {
             app.do_user,
             app.do_txt,
             app.do_alto,
+            app.do_hocr,
+            app.do_pdf,
+            app.merge_pdf,
             app.args.digits,
         );
 
@@ -420,17 +474,26 @@ impl App {
         app.push_log("".to_string());
         app.push_log("OVLÁDÁNÍ:".to_string());
         app.push_log("  ↑/↓: výběr dávky".to_string());
-        app.push_log("  Enter: zpracovat vybranou dávku".to_string());
+        app.push_log("  Enter: zpracovat vybranou dávku (po chybě navazuje)".to_string());
         app.push_log("  a: zpracovat všechny Pending dávky".to_string());
         app.push_log("  m/u/t/l: přepnout AC/UC/TXT/ALTO".to_string());
+        app.push_log("  h/d: přepnout hOCR/PDF, D: spojené PDF dávky".to_string());
         app.push_log("  L: výběr jazyka".to_string());
         app.push_log("  A: výběr ALTO verze".to_string());
+        app.push_log("  G: profil kódování Grok pro vybranou dávku".to_string());
+        app.push_log("  T: nastavení Tesseractu (PSM, OEM, DPI, -c) pro vybranou dávku".to_string());
+        app.push_log("  N: ruční první index vybrané dávky".to_string());
+        app.push_log("  M: číslování souvisle / každá dávka od začátku".to_string());
+        app.push_log("  K: řazení / filtr stránek podle kvality OCR (slovník, konfidence)".to_string());
         app.push_log("  Tab/Shift+Tab: přepnout fokus (Dávky/Detail/Log)".to_string());
         app.push_log("  PgUp/PgDn: stránkování ve fokussovaném panelu".to_string());
         app.push_log("  i/o: změnit input/output root".to_string());
         app.push_log("  F: vynutit přepracování vybrané dávky".to_string());
         app.push_log("  R: vynutit přepracování všech hotových dávek".to_string());
-        app.push_log("  q: konec".to_string());
+        app.push_log("  p: pozastavit / pokračovat vybranou dávku".to_string());
+        app.push_log("  c: zrušit vybranou dávku (ukončí Grok/Tesseract)".to_string());
+        app.push_log("  S: uložit nastavení do konfigurace".to_string());
+        app.push_log("  q: konec (během zpracování 2× q)".to_string());
         app.push_log("".to_string());
         
         // Log nalezených nástrojů
@@ -465,9 +528,67 @@ impl App {
         }
         
         app.push_log("=== NASTAVENÍ ===".to_string());
+        if config_sources.is_empty() {
+            app.push_log("Konfigurace: žádná (výchozí hodnoty)".to_string());
+        }
+        for source in config_sources {
+            app.push_log(format!("Konfigurace: {}", source.display()));
+        }
+        app.push_log(format!(
+            "Hledání dávek: {}",
+            match (app.args.recursive, app.args.max_depth) {
+                (false, _) => "jen 1. úroveň podadresářů".to_string(),
+                (true, None) => "rekurzivně".to_string(),
+                (true, Some(depth)) => format!("rekurzivně do hloubky {depth}"),
+            }
+        ));
+        app.push_log(format!(
+            "Číslování: {} od {}",
+            app.args.numbering.label(),
+            app.args.start_index
+        ));
         app.push_log(format!("Jazyk: {}", app.args.lang));
+        app.push_log(format!(
+            "Jazyky Tesseractu: {}",
+            app.installed_languages.describe()
+        ));
+        app.warn_missing_languages();
         app.push_log(format!("ALTO verze: {}", app.args.alto_version));
+        app.push_log(format!(
+            "Nastavení OCR: {}",
+            OcrSettings::from_args(&app.args).describe()
+        ));
+        app.push_log(format!(
+            "Profil kódování: {} (dostupné: {})",
+            app.args.profile,
+            app.profiles
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        app.push_log(format!(
+            "Kontrolní součty: {}",
+            hashing::with_blake3(&app.args.checksums)
+                .iter()
+                .map(|a| a.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
         app.push_log(format!("Force local Tesseract: {}", app.args.force_local_tess));
+        let (jobs, grok_jobs, tess_jobs) =
+            resolve_limits(app.args.jobs, app.args.grok_jobs, app.args.tess_jobs);
+        if let Some(warning) =
+            capped_limits_warning(app.args.jobs, app.args.grok_jobs, app.args.tess_jobs)
+        {
+            app.push_log(warning);
+        }
+        app.push_log(format!(
+            "Souběh: {} procesů (Grok {}, Tesseract {})",
+            jobs,
+            grok_jobs,
+            tess_jobs
+        ));
         app.push_log("Ready.".to_string());
 
         app
@@ -621,6 +742,20 @@ impl App {
         }
     }
 
+    fn scroll_profile_menu_up(&mut self) {
+        let len = self.profiles.len();
+        let selected = self.profile_list_state.selected().unwrap_or(0);
+        self.profile_list_state
+            .select(Some(if selected > 0 { selected - 1 } else { len - 1 }));
+    }
+
+    fn scroll_profile_menu_down(&mut self) {
+        let len = self.profiles.len();
+        let selected = self.profile_list_state.selected().unwrap_or(0);
+        self.profile_list_state
+            .select(Some(if selected + 1 < len { selected + 1 } else { 0 }));
+    }
+
     fn auto_scroll_log(&mut self) {
         let visible_lines = 20; // přibližný počet viditelných řádků
         let total_lines = self.log_lines.len();
@@ -683,6 +818,31 @@ impl App {
         self.update_jobs_status();
     }
 
+    fn toggle_hocr(&mut self) {
+        self.do_hocr = !self.do_hocr;
+        self.push_log(format!("hOCR {}", if self.do_hocr { "ON" } else { "OFF" }));
+        // Překontrolovat stav dávek po změně formátů
+        self.update_jobs_status();
+    }
+
+    fn toggle_pdf(&mut self) {
+        self.do_pdf = !self.do_pdf;
+        self.push_log(format!("PDF {}", if self.do_pdf { "ON" } else { "OFF" }));
+        // Překontrolovat stav dávek po změně formátů
+        self.update_jobs_status();
+    }
+
+    fn toggle_merge_pdf(&mut self) {
+        self.merge_pdf = !self.merge_pdf;
+        self.push_log(format!(
+            "Spojené PDF dávky {}{}",
+            if self.merge_pdf { "ON" } else { "OFF" },
+            if self.merge_pdf && !self.do_pdf { " (vyžaduje PDF – d)" } else { "" }
+        ));
+        // Překontrolovat stav dávek po změně formátů
+        self.update_jobs_status();
+    }
+
     fn update_jobs_status(&mut self) {
         update_jobs_status_on_start(
             &mut self.jobs,
@@ -692,11 +852,18 @@ impl App {
             self.do_user,
             self.do_txt,
             self.do_alto,
+            self.do_hocr,
+            self.do_pdf,
+            self.merge_pdf,
             self.args.digits,
         );
     }
 
     fn start_edit_input(&mut self) {
+        if self.is_busy() {
+            self.push_log("Input root nelze měnit, dokud běží zpracování dávek.".to_string());
+            return;
+        }
         self.mode = UiMode::EditInput;
         self.edit_buffer = self.input_root.to_string_lossy().to_string();
         self.push_log(
@@ -710,6 +877,89 @@ impl App {
         self.push_log("Editace output root – Enter=potvrdit, Esc=zrušit".to_string());
     }
 
+    /// Ruční první index vybrané dávky (N)
+    fn start_edit_start_index(&mut self) {
+        let Some(job) = self.jobs.get(self.selected) else {
+            self.push_log("Žádná dávka pro změnu číslování.".to_string());
+            return;
+        };
+        if matches!(
+            job.status,
+            JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+        ) {
+            self.push_log("Číslování nelze měnit, dokud se dávka zpracovává.".to_string());
+            return;
+        }
+        self.edit_buffer = if job.explicit_start {
+            job.index_start.to_string()
+        } else {
+            String::new()
+        };
+        self.mode = UiMode::EditStartIndex;
+        self.push_log(
+            "První index dávky – číslo = ruční začátek, prázdné = podle režimu číslování; Enter=potvrdit, Esc=zrušit"
+                .to_string(),
+        );
+    }
+
+    fn apply_start_index(&mut self, value: &str) {
+        let Some(job) = self.jobs.get(self.selected) else {
+            return;
+        };
+        let name = job.name.clone();
+        let logs_dir = batch_logs_dir(&self.output_root, &name);
+        let result = if value.is_empty() {
+            BatchNumbering::remove(&logs_dir).map(|()| {
+                format!("Dávka {name}: ruční začátek zrušen, číslování podle režimu")
+            })
+        } else {
+            match value.parse::<u32>() {
+                Ok(start) => {
+                    let numbering = BatchNumbering {
+                        index_start: start,
+                        explicit: true,
+                        mode: self.args.numbering,
+                    };
+                    // Začátek, se kterým by indexy této či další dávky přetekly, se neuloží
+                    let mut batches = numbering_batches(&self.jobs, &self.output_root, &self.args);
+                    batches[self.selected].0 = Some(numbering.clone());
+                    assign_starts(self.args.numbering, self.args.start_index, &batches)
+                        .and_then(|_| numbering.save(&logs_dir))
+                        .map(|()| format!("Dávka {name}: první index nastaven ručně na {start}"))
+                }
+                Err(_) => Err(anyhow!("`{value}` není platné číslo")),
+            }
+        };
+        match result {
+            Ok(msg) => self.push_log(msg),
+            Err(e) => self.push_log(format!("Číslování nezměněno: {e:#}")),
+        }
+        if let Err(e) = renumber_jobs(&mut self.jobs, &self.output_root, &self.args) {
+            self.push_log(format!("Číslování nezměněno: {e:#}"));
+        }
+        self.update_jobs_status();
+    }
+
+    /// Přepne režim číslování (M)
+    fn toggle_numbering(&mut self) {
+        if self.is_busy() {
+            self.push_log("Režim číslování nelze měnit, dokud běží zpracování dávek.".to_string());
+            return;
+        }
+        let previous = self.args.numbering;
+        self.args.numbering = match previous {
+            NumberingMode::Global => NumberingMode::PerBatch,
+            NumberingMode::PerBatch => NumberingMode::Global,
+        };
+        if let Err(e) = renumber_jobs(&mut self.jobs, &self.output_root, &self.args) {
+            self.args.numbering = previous;
+            self.push_log(format!("Režim číslování nezměněn: {e:#}"));
+            return;
+        }
+        self.update_jobs_status();
+        self.push_log(format!("Číslování: {}", self.args.numbering.label()));
+    }
+
     fn show_language_menu(&mut self) {
         self.mode = UiMode::LanguageMenu;
         self.push_log(
@@ -723,6 +973,163 @@ impl App {
         self.push_log("Výběr ALTO verze – ↑/↓: pohyb, Enter: vybrat, Esc: zrušit".to_string());
     }
 
+    fn show_profile_menu(&mut self) {
+        let Some(job) = self.jobs.get(self.selected) else {
+            self.push_log("Žádná dávka pro výběr profilu.".to_string());
+            return;
+        };
+        let current = self
+            .profiles
+            .iter()
+            .position(|p| p.name == job.profile)
+            .unwrap_or(0);
+        self.profile_list_state.select(Some(current));
+        self.mode = UiMode::ProfileMenu;
+        self.push_log(
+            "Výběr profilu kódování – ↑/↓: pohyb, Enter: vybraná dávka, F2: všechny dávky, Esc: zrušit"
+                .to_string(),
+        );
+    }
+
+    /// Panel nastavení Tesseractu pro vybranou dávku (T)
+    fn show_ocr_menu(&mut self) {
+        let Some(job) = self.jobs.get(self.selected) else {
+            self.push_log("Žádná dávka pro nastavení OCR.".to_string());
+            return;
+        };
+        if matches!(
+            job.status,
+            JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+        ) {
+            self.push_log("Nastavení OCR nelze měnit, dokud se dávka zpracovává.".to_string());
+            return;
+        }
+        self.ocr_list_state.select(Some(0));
+        self.mode = UiMode::OcrSettingsMenu;
+        self.push_log(
+            "Nastavení OCR – ↑/↓: pohyb, Enter: upravit, F2: použít pro všechny dávky, r: výchozí, Esc: zavřít"
+                .to_string(),
+        );
+    }
+
+    fn selected_ocr_field(&self) -> OcrField {
+        let i = self.ocr_list_state.selected().unwrap_or(0);
+        OcrField::ALL[i.min(OcrField::ALL.len() - 1)]
+    }
+
+    fn handle_ocr_menu_key(&mut self, key: KeyCode) {
+        let len = OcrField::ALL.len();
+        match key {
+            KeyCode::Esc => {
+                self.mode = UiMode::Normal;
+            }
+            KeyCode::Up => {
+                let i = self.ocr_list_state.selected().unwrap_or(0);
+                self.ocr_list_state.select(Some(if i == 0 { len - 1 } else { i - 1 }));
+            }
+            KeyCode::Down => {
+                let i = self.ocr_list_state.selected().unwrap_or(0);
+                self.ocr_list_state.select(Some((i + 1) % len));
+            }
+            KeyCode::Enter => {
+                let field = self.selected_ocr_field();
+                let Some(job) = self.jobs.get(self.selected) else {
+                    return;
+                };
+                self.edit_buffer = job.ocr.value(field);
+                self.mode = UiMode::EditOcrField;
+                self.push_log(format!(
+                    "{} – {}; prázdné = výchozí, Enter=potvrdit, Esc=zrušit",
+                    field.label(),
+                    field.hint()
+                ));
+            }
+            KeyCode::Char('r') => {
+                let Some(job) = self.jobs.get_mut(self.selected) else {
+                    return;
+                };
+                let name = job.name.clone();
+                let result = OcrSettings::remove(&batch_logs_dir(&self.output_root, &name))
+                    .and_then(|()| OcrSettings::for_batch(&self.args, &job.dir))
+                    .map(|settings| (settings, false));
+                match set_batch_ocr(job, result) {
+                    Ok(()) => {
+                        let description = job.ocr.describe();
+                        self.push_log(format!(
+                            "Dávka {name}: výchozí nastavení OCR ({description})"
+                        ));
+                    }
+                    Err(e) => self.push_log(format!("Dávka {name}: neplatné nastavení OCR: {e:#}")),
+                }
+            }
+            KeyCode::F(2) => {
+                let Some(settings) = self.jobs.get(self.selected).map(|j| j.ocr.clone()) else {
+                    return;
+                };
+                let mut changed = 0;
+                let mut errors = Vec::new();
+                for job in self.jobs.iter_mut() {
+                    // Běžící dávka má parametry už převzaté
+                    if matches!(
+                        job.status,
+                        JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+                    ) {
+                        continue;
+                    }
+                    match settings.save(&batch_logs_dir(&self.output_root, &job.name)) {
+                        Ok(()) => {
+                            let _ = set_batch_ocr(job, Ok((settings.clone(), true)));
+                            changed += 1;
+                        }
+                        Err(e) => errors.push(format!("{}: {e:#}", job.name)),
+                    }
+                }
+                self.push_log(format!(
+                    "Nastavení OCR ({}) použito pro {changed} dávek.",
+                    settings.describe()
+                ));
+                for e in errors {
+                    self.push_log(format!("Chyba při ukládání nastavení OCR – {e}"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Uloží upravenou položku nastavení OCR vybrané dávky do `*_logs/ocr_settings.json`
+    fn apply_ocr_field(&mut self, value: &str) {
+        let field = self.selected_ocr_field();
+        let Some(job) = self.jobs.get_mut(self.selected) else {
+            return;
+        };
+        let mut settings = job.ocr.clone();
+        let result = settings
+            .set(field, value)
+            .and_then(|()| settings.save(&batch_logs_dir(&self.output_root, &job.name)));
+        let msg = match result {
+            Ok(()) => {
+                let _ = set_batch_ocr(job, Ok((settings, true)));
+                let missing = job.ocr.missing_files();
+                let mut msg = format!("Dávka {}: OCR {}", job.name, job.ocr.describe());
+                for path in missing {
+                    msg.push_str(&format!(" (varování: `{}` neexistuje)", path.display()));
+                }
+                let languages = self
+                    .installed_languages
+                    .missing(job.ocr.lang_or(&self.args.lang));
+                if !languages.is_empty() {
+                    msg.push_str(&format!(
+                        " (varování: jazyky {} nejsou nainstalované)",
+                        languages.join(", ")
+                    ));
+                }
+                msg
+            }
+            Err(e) => format!("Nastavení OCR nezměněno: {e:#}"),
+        };
+        self.push_log(msg);
+    }
+
     fn start_custom_lang_input(&mut self) {
         self.mode = UiMode::CustomLangInput;
         self.custom_lang_input = self.args.lang.clone();
@@ -745,14 +1152,13 @@ impl App {
                 } else {
                     self.input_root = new_path.clone();
                     self.push_log(format!("Input root nastaven na `{}`", new_path.display()));
-                    let jobs =
-                        init_jobs_from_dirs(&self.input_root, self.args.start_index)
-                            .unwrap_or_else(|e| {
-                                self.push_log(format!(
-                                    "Chyba při načítání dávek: {e}. Joby zůstávají prázdné."
-                                ));
-                                Vec::new()
-                            });
+                    let jobs = init_jobs_from_dirs(&self.input_root, &self.output_root, &self.args)
+                        .unwrap_or_else(|e| {
+                            self.push_log(format!(
+                                "Chyba při načítání dávek: {e}. Joby zůstávají prázdné."
+                            ));
+                            Vec::new()
+                        });
                     self.jobs = jobs;
                     self.selected = 0;
                     self.detail_scroll = 0;
@@ -771,10 +1177,36 @@ impl App {
                 } else {
                     self.output_root = new_path.clone();
                     self.push_log(format!("Output root nastaven na `{}`", new_path.display()));
-                    // Kontrola stavu dávek s novou output cestou
+                    // Uložené číslování, nastavení OCR a stav dávek podle nové output cesty
+                    let mut errors = Vec::new();
+                    if let Err(e) = renumber_jobs(&mut self.jobs, &self.output_root, &self.args) {
+                        errors.push(format!("číslování: {e:#}"));
+                    }
+                    for job in &mut self.jobs {
+                        if matches!(
+                            job.status,
+                            JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+                        ) {
+                            continue;
+                        }
+                        let loaded = load_batch_ocr(&self.output_root, job, &self.args);
+                        if let Err(e) = set_batch_ocr(job, loaded) {
+                            errors.push(format!("{}: {e:#}", job.name));
+                        }
+                    }
+                    for e in errors {
+                        self.push_log(format!("Nastavení OCR nenačteno – {e}"));
+                    }
                     self.update_jobs_status();
                 }
             }
+            UiMode::EditStartIndex => self.apply_start_index(&trimmed),
+            UiMode::EditOcrField => {
+                self.apply_ocr_field(&trimmed);
+                self.mode = UiMode::OcrSettingsMenu;
+                self.edit_buffer.clear();
+                return;
+            }
             UiMode::Normal => {}
             _ => {}
         }
@@ -783,7 +1215,11 @@ impl App {
     }
 
     fn cancel_edit(&mut self) {
-        self.mode = UiMode::Normal;
+        self.mode = if self.mode == UiMode::EditOcrField {
+            UiMode::OcrSettingsMenu
+        } else {
+            UiMode::Normal
+        };
         self.edit_buffer.clear();
         self.custom_lang_input.clear();
         self.push_log("Editace zrušena.".to_string());
@@ -818,6 +1254,7 @@ impl App {
                         "Jazyk nastaven na vlastní kombinaci: {}",
                         self.args.lang
                     ));
+                    self.warn_missing_languages();
                     self.mode = UiMode::Normal;
                     self.custom_lang_input.clear();
                 }
@@ -832,6 +1269,17 @@ impl App {
         }
     }
 
+    /// Upozorní na jazyky, které v tessdata chybí (dávky s OCR se nespustí)
+    fn warn_missing_languages(&mut self) {
+        let missing = self.installed_languages.missing(&self.args.lang);
+        if !missing.is_empty() {
+            self.push_log(format!(
+                "Varování: jazyky {} nejsou nainstalované – OCR dávky se nespustí",
+                missing.join(", ")
+            ));
+        }
+    }
+
     fn handle_language_menu_key(&mut self, key: KeyCode) {
         match key {
             KeyCode::Esc => {
@@ -858,12 +1306,13 @@ impl App {
                 self.start_custom_lang_input();
             }
             KeyCode::Enter => {
-                if let Some(selected) = self.language_list_state.selected() {
-                    if selected < self.available_languages.len() {
-                        self.args.lang = self.available_languages[selected].0.clone();
-                        self.push_log(format!("Jazyk nastaven na: {}", self.args.lang));
-                        self.mode = UiMode::Normal;
-                    }
+                if let Some(selected) = self.language_list_state.selected()
+                    && selected < self.available_languages.len()
+                {
+                    self.args.lang = self.available_languages[selected].code.clone();
+                    self.push_log(format!("Jazyk nastaven na: {}", self.args.lang));
+                    self.warn_missing_languages();
+                    self.mode = UiMode::Normal;
                 }
             }
             _ => {}
@@ -893,16 +1342,119 @@ impl App {
                 }
             }
             KeyCode::Enter => {
-                if let Some(selected) = self.alto_version_list_state.selected() {
-                    if selected < self.available_alto_versions.len() {
-                        self.args.alto_version = self.available_alto_versions[selected].clone();
-                        self.push_log(format!(
-                            "ALTO verze nastavena na: {}",
-                            self.args.alto_version
-                        ));
-                        self.mode = UiMode::Normal;
+                if let Some(selected) = self.alto_version_list_state.selected()
+                    && selected < self.available_alto_versions.len()
+                {
+                    self.args.alto_version = self.available_alto_versions[selected].clone();
+                    self.push_log(format!(
+                        "ALTO verze nastavena na: {}",
+                        self.args.alto_version
+                    ));
+                    self.mode = UiMode::Normal;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Uloží aktuální nastavení (kořeny, jazyk, ALTO, formáty, nástroje…)
+    /// do konfiguračního souboru
+    fn save_config(&mut self) {
+        let config = Config {
+            input: Some(self.input_root.clone()),
+            output: Some(self.output_root.clone()),
+            start_index: Some(self.args.start_index),
+            numbering: Some(self.args.numbering),
+            digits: Some(self.args.digits),
+            lang: Some(self.args.lang.clone()),
+            alto_version: Some(self.args.alto_version.clone()),
+            alto_unit: self.args.alto_unit,
+            psm: self.args.psm,
+            oem: self.args.oem,
+            dpi: self.args.dpi,
+            user_words: self.args.user_words.clone(),
+            user_patterns: self.args.user_patterns.clone(),
+            tess_vars: (!self.args.tess_vars.is_empty())
+                .then(|| self.args.tess_vars.iter().cloned().collect()),
+            do_master: Some(self.do_master),
+            do_user: Some(self.do_user),
+            do_txt: Some(self.do_txt),
+            do_alto: Some(self.do_alto),
+            do_hocr: Some(self.do_hocr),
+            do_pdf: Some(self.do_pdf),
+            merge_pdf: Some(self.merge_pdf),
+            grok_bin: Some(self.args.grok_bin.clone()),
+            tess_bin: Some(self.args.tess_bin.clone()),
+            tessdata_dir: self.args.tessdata_dir.clone(),
+            force_local_tess: Some(self.args.force_local_tess),
+            jobs: self.args.jobs,
+            grok_jobs: self.args.grok_jobs,
+            tess_jobs: self.args.tess_jobs,
+            profiles: self.args.profiles.clone(),
+            profile: Some(self.args.profile.clone()),
+            recursive: Some(self.args.recursive),
+            max_depth: self.args.max_depth,
+            checksums: Some(self.args.checksums.clone()),
+            review_threshold: Some(self.args.review_threshold),
+            dictionary_threshold: Some(self.args.dictionary_threshold),
+            wordlists: (!self.args.wordlists.is_empty())
+                .then(|| self.args.wordlists.iter().cloned().collect()),
+            preflight: Some(self.args.preflight.clone()),
+        };
+        match config.save(&self.config_path) {
+            Ok(()) => self.push_log(format!(
+                "Nastavení uloženo do `{}`",
+                self.config_path.display()
+            )),
+            Err(e) => self.push_log(format!("Chyba při ukládání nastavení: {e:#}")),
+        }
+    }
+
+    fn handle_profile_menu_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.mode = UiMode::Normal;
+                self.push_log("Výběr profilu zrušen.".to_string());
+            }
+            KeyCode::Up => {
+                self.scroll_profile_menu_up();
+            }
+            KeyCode::Down => {
+                self.scroll_profile_menu_down();
+            }
+            KeyCode::Enter | KeyCode::F(2) => {
+                let Some(name) = self
+                    .profile_list_state
+                    .selected()
+                    .and_then(|i| self.profiles.get(i))
+                    .map(|p| p.name.clone())
+                else {
+                    return;
+                };
+                let all = key == KeyCode::F(2);
+                let mut changed = 0;
+                for (i, job) in self.jobs.iter_mut().enumerate() {
+                    if !all && i != self.selected {
+                        continue;
+                    }
+                    // Běžící dávka má parametry už převzaté
+                    if matches!(
+                        job.status,
+                        JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+                    ) {
+                        continue;
                     }
+                    job.profile = name.clone();
+                    changed += 1;
                 }
+                if all {
+                    self.push_log(format!("Profil {} nastaven pro {} dávek.", name, changed));
+                } else if changed == 0 {
+                    self.push_log("Dávka se zpracovává – profil nelze změnit.".to_string());
+                } else {
+                    self.push_log(format!("Profil dávky nastaven na: {}", name));
+                }
+                self.mode = UiMode::Normal;
             }
             _ => {}
         }
@@ -934,6 +1486,16 @@ impl App {
             JobStatus::AlreadyDone => {
                 self.push_log("Dávka je již zpracována. Použijte 'F' pro vynucení přepracování.".to_string());
             }
+            JobStatus::Queued | JobStatus::Processing | JobStatus::Paused => {
+                self.push_log("Dávka už je ve frontě nebo se zpracovává.".to_string());
+            }
+            JobStatus::Failed(_) | JobStatus::Cancelled => {
+                self.push_log(format!(
+                    "Navazuji na předchozí běh dávky {} – hotové kroky se přeskočí.",
+                    job_display_name(&self.jobs[idx])
+                ));
+                self.run_job(idx);
+            }
             _ => {
                 self.run_job(idx);
             }
@@ -946,18 +1508,23 @@ impl App {
             return;
         }
         
-        self.push_log("Spouštím zpracování všech Pending dávek...".to_string());
-        
+        let mut count = 0;
         for i in 0..self.jobs.len() {
             let status = &self.jobs[i].status;
-            let is_processable = matches!(status, JobStatus::Pending | JobStatus::Failed(_));
+            let is_processable = matches!(
+                status,
+                JobStatus::Pending
+                    | JobStatus::Failed(_)
+                    | JobStatus::InvalidSettings(_)
+                    | JobStatus::Cancelled
+            );
             
-            if is_processable {
-                self.run_job(i);
+            if is_processable && self.run_job(i) {
+                count += 1;
             }
         }
         
-        self.push_log("Hromadné zpracování dokončeno.".to_string());
+        self.push_log(format!("Do fronty zařazeno {} dávek.", count));
     }
 
     fn force_rerun_selected(&mut self) {
@@ -972,9 +1539,10 @@ impl App {
         match current_status {
             JobStatus::AlreadyDone | JobStatus::Done => {
                 self.jobs[idx].status = JobStatus::Pending;
+                self.jobs[idx].force_full = true;
                 self.push_log(format!("Dávka {} nastavena na Pending pro přepracování.", idx));
             }
-            JobStatus::Processing => {
+            JobStatus::Queued | JobStatus::Processing | JobStatus::Paused => {
                 self.push_log("Dávka právě zpracovává - nelze přepracovat.".to_string());
             }
             _ => {
@@ -988,189 +1556,744 @@ impl App {
         for job in self.jobs.iter_mut() {
             if matches!(job.status, JobStatus::AlreadyDone | JobStatus::Done) {
                 job.status = JobStatus::Pending;
+                job.force_full = true;
                 count += 1;
             }
         }
         self.push_log(format!("{} hotových/AlreadyDone dávek nastaveno na Pending.", count));
     }
 
-    fn run_job(&mut self, job_index: usize) {
-        if job_index >= self.jobs.len() {
+    /// Běží nebo čeká ve frontě nějaká dávka?
+    fn is_busy(&self) -> bool {
+        self.jobs.iter().any(|j| {
+            matches!(
+                j.status,
+                JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+            )
+        })
+    }
+
+    /// Pozastaví / obnoví vybranou dávku. Rozběhnuté procesy doběhnou,
+    /// další kroky se spustí až po obnovení.
+    fn toggle_pause_selected(&mut self) {
+        let Some(job) = self.jobs.get_mut(self.selected) else {
             return;
+        };
+        let name = job_display_name(job).to_string();
+        match (&job.status, &job.control) {
+            (JobStatus::Processing, Some(control)) => {
+                control.pause();
+                job.status = JobStatus::Paused;
+                self.push_log(format!(
+                    "Dávka {} pozastavena (rozběhnuté kroky doběhnou).",
+                    name
+                ));
+            }
+            (JobStatus::Paused, Some(control)) => {
+                control.resume();
+                job.status = JobStatus::Processing;
+                self.push_log(format!("Dávka {} pokračuje.", name));
+            }
+            _ => {
+                self.push_log("Pozastavit lze jen právě zpracovávanou dávku.".to_string());
+            }
         }
+    }
 
-        let dir;
-        let index_start;
-        let file_count;
-        {
-            let job = &mut self.jobs[job_index];
-            job.status = JobStatus::Processing;
-            dir = job.dir.clone();
-            index_start = job.index_start;
-            file_count = job.file_count;
-        }
-
-        let args = self.args.clone();
-        let do_master = self.do_master;
-        let do_user = self.do_user;
-        let do_txt = self.do_txt;
-        let do_alto = self.do_alto;
-        let output_root = self.output_root.clone();
-        let input_root = self.input_root.clone();
-        let grok_path = self.grok_path.clone();
-        let tess_path = self.tess_path.clone();
-        let alto_version = self.args.alto_version.clone();
-        let tessdata_dir = self.tessdata_dir.clone();
-
-        let batch_out_dir = batch_output_dir(&output_root, &input_root, &dir);
-        let _ = fs::create_dir_all(&batch_out_dir);
-
-        let mut local_logs: Vec<String> = Vec::new();
-        local_logs.push(format!(
-            "=== Dávka {} ({}), start index {}, ALTO v{} ===",
-            dir.file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("<root>"),
-            dir.display(),
-            index_start,
-            alto_version
-        ));
-        
-        // Přidáme informace o použitém Tesseractu
-        local_logs.push(format!("Tesseract: {} ({})", 
-            self.tess_path.display(), 
-            self.tess_source));
-        local_logs.push(format!("Tessdata dir: {:?}", tessdata_dir));
-
-        // 1. Nejprve zpracujeme dávku (JP2, OCR, ALTO)
-        let res = process_batch(
-            &args,
-            &grok_path,
-            &tess_path,
-            &dir,
-            index_start,
-            &batch_out_dir,
-            do_master,
-            do_user,
-            do_txt,
-            do_alto,
-            &alto_version,
-            tessdata_dir.as_deref(),
-            &mut local_logs,
-        );
-
-        let batch_successful = res.is_ok();
-        
-        if batch_successful {
-            if let Some(job) = self.jobs.get_mut(job_index) {
-                job.status = JobStatus::Done;
+    /// Zruší vybranou dávku: ve frontě se nespustí, běžící Grok/Tesseract
+    /// procesy se ukončí a jejich neúplné výstupy smažou.
+    fn cancel_selected(&mut self) {
+        let Some(job) = self.jobs.get_mut(self.selected) else {
+            return;
+        };
+        let name = job_display_name(job).to_string();
+        match (&job.status, &job.control) {
+            (JobStatus::Queued, Some(control)) => {
+                control.cancel();
+                job.status = JobStatus::Cancelled;
+                self.push_log(format!("Dávka {} odebrána z fronty.", name));
             }
-            local_logs.push("Dávka OK".to_string());
-            
-            // Malé zpoždění pro zápis souborů na disk
-            local_logs.push("Čekám na dokončení zápisu souborů...".to_string());
-            std::thread::sleep(std::time::Duration::from_millis(1000));
-            
-            // 2. Počkáme na vytvoření JP2 souborů (pokud jsou povoleny)
-            if do_master || do_user {
-                wait_for_jp2_files(&batch_out_dir, index_start, file_count, 
-                    args.digits, do_master, do_user, &mut local_logs);
+            (JobStatus::Processing | JobStatus::Paused, Some(control)) => {
+                control.cancel();
+                self.push_log(format!("Ruším dávku {}...", name));
             }
-        } else {
-            if let Some(job) = self.jobs.get_mut(job_index) {
-                job.status = JobStatus::Failed(res.err().unwrap().to_string());
+            _ => {
+                self.push_log("Dávka neběží ani nečeká ve frontě.".to_string());
             }
-            local_logs.push("Dávka FAILED".to_string());
         }
+    }
 
-        // 3. Teprve po úspěšném zpracování dávky pokračujeme s manifestem a WebP
-        if batch_successful {
-            let batch_name = dir
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("root")
-                .to_string();
-            let safe_batch = if batch_name.is_empty() {
-                "root".to_string()
-            } else {
-                batch_name.clone()
-            };
-            let logs_dir = self.output_root.join(format!("{safe_batch}_logs"));
-
-            // 4. Vytvoříme manifest (teprve po vytvoření všech souborů)
-            match build_manifest_for_batch(
-                &safe_batch,
-                &dir,
-                &batch_out_dir,
-                &logs_dir,
-                index_start,
-                args.digits,
-                do_master,
-                do_user,
-                do_txt,
-                do_alto,
-                &args.lang,
-                &alto_version,
-            ) {
-                Ok(manifest) => {
-                    // 5. Zapišeme manifest a log.txt - s přidáním logu
-                    if let Err(e) = write_manifest_and_log_with_process_logs(&manifest, &logs_dir, &local_logs) {
-                        local_logs.push(format!("Chyba při zápisu manifestu/log.txt: {e}"));
-                    } else {
-                        local_logs.push("Manifest a log.txt vytvořeny".to_string());
-                    }
+    /// Při ukončení programu zruší všechny dávky a chvíli počká,
+    /// než worker ukončí externí procesy a uklidí neúplné výstupy.
+    fn shutdown(&mut self) {
+        if !self.is_busy() {
+            return;
+        }
+        for job in self.jobs.iter() {
+            if let Some(ref control) = job.control {
+                control.cancel();
+            }
+        }
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while self
+            .jobs
+            .iter()
+            .any(|j| matches!(j.status, JobStatus::Processing | JobStatus::Paused))
+            && Instant::now() < deadline
+        {
+            self.poll_worker();
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
 
-                    // 6. Zkontrolujeme, zda máme co konvertovat na WebP
-                    let has_jp2_files = manifest.pages.iter().any(|p| p.ac_jp2.is_some() || p.uc_jp2.is_some());
-                    
-                    if has_jp2_files {
-                        // 7. WebP náhledy (teprve po vytvoření JP2 a manifestu)
-                        local_logs.push("Generuji WebP náhledy...".to_string());
-                        match generate_webp_previews(&logs_dir) {
-                            Ok(()) => {
-                                local_logs.push(format!(
-                                    "WEBP náhledy vygenerovány v {} (z TIFF)",
-                                    logs_dir.display()
-                                ));
-                            }
-                            Err(e) => {
-                                local_logs.push(format!(
-                                    "Chyba při generování WEBP náhledů: {e}"
-                                ));
-                            }
+    /// Vrací true, pokud se má aplikace ukončit.
+    /// Během zpracování je potřeba q stisknout dvakrát.
+    fn request_quit(&mut self) -> bool {
+        if !self.is_busy() || self.quit_armed {
+            return true;
+        }
+        self.quit_armed = true;
+        self.push_log(
+            "Zpracování stále běží! Opětovné stisknutí q ukončí program a přeruší dávku."
+                .to_string(),
+        );
+        false
+    }
+
+    /// Zařadí dávku do fronty workeru. Samotné zpracování běží na pozadí,
+    /// průběh se do TUI dostává přes `poll_worker`.
+    /// Vrací false, pokud dávku nelze spustit.
+    fn run_job(&mut self, job_index: usize) -> bool {
+        if job_index >= self.jobs.len() {
+            return false;
+        }
+
+        // ocr.json se mohl od načtení dávky změnit – neplatný dávku zablokuje
+        let loaded = load_batch_ocr(&self.output_root, &self.jobs[job_index], &self.args);
+        if let Err(e) = set_batch_ocr(&mut self.jobs[job_index], loaded) {
+            self.push_log(format!(
+                "Dávku {} nelze spustit: {e:#}",
+                job_display_name(&self.jobs[job_index])
+            ));
+            return false;
+        }
+
+        // Bez nainstalovaných jazyků by OCR selhalo u každé stránky
+        let do_ocr = self.do_txt || self.do_alto || self.do_hocr || self.do_pdf;
+        let lang = self.jobs[job_index].ocr.lang_or(&self.args.lang);
+        let missing = self.installed_languages.missing(lang);
+        if do_ocr && !missing.is_empty() {
+            self.push_log(format!(
+                "Dávku {} nelze spustit: chybí jazyky Tesseractu {} ({})",
+                job_display_name(&self.jobs[job_index]),
+                missing.join(", "),
+                self.installed_languages.describe()
+            ));
+            return false;
+        }
+
+        let control = JobControl::new();
+        let req = build_job_request(
+            job_index,
+            &self.jobs[job_index],
+            &self.args,
+            &self.input_root,
+            &self.output_root,
+            &self.grok_path,
+            &self.tess_path,
+            &self.tess_source,
+            self.tessdata_dir.as_deref(),
+            self.do_master,
+            self.do_user,
+            self.do_txt,
+            self.do_alto,
+            self.do_hocr,
+            self.do_pdf,
+            self.merge_pdf,
+            &self.profiles,
+            control.clone(),
+        );
+
+        let name = req.batch_name.clone();
+        if let Some(run) = self.worker.submit(req) {
+            let job = &mut self.jobs[job_index];
+            job.run = run;
+            job.status = JobStatus::Queued;
+            job.pages_done = 0;
+            job.force_full = false;
+            job.control = Some(control);
+            self.push_log(format!("Dávka {} zařazena do fronty.", name));
+            true
+        } else {
+            self.jobs[job_index].status =
+                JobStatus::Failed("Worker neběží".to_string());
+            self.push_log(format!("Dávku {} nelze spustit: worker neběží.", name));
+            false
+        }
+    }
+
+    /// Převezme zprávy z workeru (log, průběh, konec dávky).
+    /// Vrací true, pokud některá dávka skončila (kvůli úplnému překreslení).
+    fn poll_worker(&mut self) -> bool {
+        let mut finished = false;
+        for event in self.worker.drain() {
+            let (WorkerEvent::Started { job, run }
+            | WorkerEvent::Log { job, run, .. }
+            | WorkerEvent::Progress { job, run, .. }
+            | WorkerEvent::Finished { job, run, .. }) = event;
+            // Zpráva z dřívějšího běhu dávky (zrušené a znovu zařazené)
+            if self.jobs.get(job).is_none_or(|j| j.run != run) {
+                continue;
+            }
+            match event {
+                WorkerEvent::Started { job, .. } => {
+                    if let Some(j) = self.jobs.get_mut(job)
+                        && !matches!(j.status, JobStatus::Cancelled)
+                    {
+                        j.status = JobStatus::Processing;
+                        j.pages_done = 0;
+                    }
+                }
+                WorkerEvent::Log { line, .. } => {
+                    self.push_log(line);
+                }
+                WorkerEvent::Progress { job, done, .. } => {
+                    if let Some(j) = self.jobs.get_mut(job) {
+                        j.pages_done = done;
+                    }
+                }
+                WorkerEvent::Finished { job, status, .. } => {
+                    if let Some(j) = self.jobs.get_mut(job) {
+                        j.status = status;
+                        j.failed_pages = load_failed_pages(&self.output_root, &j.name);
+                        j.quality = BatchQuality::load(&batch_logs_dir(&self.output_root, &j.name));
+                        if let Some(preflight) =
+                            BatchPreflight::load(&batch_logs_dir(&self.output_root, &j.name))
+                        {
+                            j.preflight = preflight;
                         }
-                    } else {
-                        local_logs.push("Žádné JP2 soubory pro WebP konverzi".to_string());
                     }
+                    finished = true;
+                }
+            }
+        }
+        if finished && !self.is_busy() {
+            self.quit_armed = false;
+        }
+        finished
+    }
+}
 
-                    // 8. HTML report (až po WebP)
-                    match write_html_report(&logs_dir) {
-                        Ok(()) => {
-                            local_logs.push(format!(
-                                "HTML report vytvořen: {}",
-                                logs_dir.join("index.html").display()
-                            ));
-                        }
-                        Err(e) => {
-                            local_logs.push(format!(
-                                "Chyba při tvorbě HTML reportu: {e}"
-                            ));
-                        }
+/// Sestaví požadavek pro worker z dávky a aktuálního nastavení
+/// (společné pro TUI i režim bez TUI)
+#[allow(clippy::too_many_arguments)]
+fn build_job_request(
+    job_index: usize,
+    job: &BatchJob,
+    args: &Args,
+    input_root: &Path,
+    output_root: &Path,
+    grok_path: &Path,
+    tess_path: &Path,
+    tess_source: &str,
+    tessdata_dir: Option<&Path>,
+    do_master: bool,
+    do_user: bool,
+    do_txt: bool,
+    do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    merge_pdf: bool,
+    profiles: &[EncodingProfile],
+    control: Arc<JobControl>,
+) -> JobRequest {
+    JobRequest {
+        job_index,
+        run: 0, // přidělí Worker::submit
+        args: args.clone(),
+        dir: job.dir.clone(),
+        batch_name: job.name.clone(),
+        index_start: job.index_start,
+        file_count: job.file_count,
+        output_root: output_root.to_path_buf(),
+        input_root: input_root.to_path_buf(),
+        grok_path: grok_path.to_path_buf(),
+        tess_path: tess_path.to_path_buf(),
+        tess_source: tess_source.to_string(),
+        tessdata_dir: tessdata_dir.map(Path::to_path_buf),
+        do_master,
+        do_user,
+        do_txt,
+        do_alto,
+        do_hocr,
+        do_pdf,
+        merge_pdf: do_pdf && merge_pdf,
+        lang: job.ocr.lang_or(&args.lang).to_string(),
+        alto_version: job.ocr.alto_version_or(&args.alto_version).to_string(),
+        resume: !job.force_full,
+        profile: find_profile(profiles, &job.profile)
+            .cloned()
+            .unwrap_or_else(profiles::builtin_profile),
+        ocr: job.ocr.clone(),
+        control,
+    }
+}
+
+/// Název dávky pro zobrazení a výběr přes --batch
+fn job_display_name(job: &BatchJob) -> &str {
+    &job.name
+}
+
+/// Název dávky: cesta od input_root oddělená `/` ("titul/2020/01");
+/// samotný input_root se jmenuje podle svého adresáře
+fn batch_name(input_root: &Path, batch_dir: &Path) -> String {
+    match batch_dir.strip_prefix(input_root) {
+        Ok(rel) if !rel.as_os_str().is_empty() => rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        _ => batch_dir
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "root".to_string()),
+    }
+}
+
+/// Vypíše do logu výsledky kontroly vstupních TIFFů (jen problémy)
+fn log_preflight(preflight: &BatchPreflight, logs: &mut JobLog) {
+    for (path, issue) in preflight.issues() {
+        let level = match issue.action {
+            RuleAction::Block => "BLOKUJE",
+            _ => "varování",
+        };
+        logs.push(format!(
+            "Kontrola TIFF {}: {} – {}",
+            level,
+            path.file_name().and_then(|s| s.to_str()).unwrap_or("?"),
+            issue.message
+        ));
+    }
+    logs.push(format!(
+        "Kontrola TIFF: {} souborů, {} varování, {} blokujících",
+        preflight.files.len(),
+        preflight.warnings,
+        preflight.blocking
+    ));
+}
+
+/// Vypíše do logu výsledky kontroly JP2 z manifestu (jen odchylky)
+fn log_jp2_checks(manifest: &manifest::BatchManifest, logs: &mut JobLog) {
+    let mut checked = 0;
+    let mut invalid = 0;
+    for page in &manifest.pages {
+        for (label, check) in [("AC", &page.ac_jp2_check), ("UC", &page.uc_jp2_check)] {
+            let Some(check) = check else {
+                continue;
+            };
+            checked += 1;
+            if !check.valid {
+                invalid += 1;
+                logs.push(format!(
+                    "Kontrola JP2 {} {}: {}",
+                    page.index,
+                    label,
+                    check.violations.join("; ")
+                ));
+            }
+        }
+    }
+    if checked > 0 {
+        logs.push(format!(
+            "Kontrola JP2 ({}): {} souborů, {} s odchylkami",
+            manifest.encoding_profile.name, checked, invalid
+        ));
+    }
+}
+
+/// Vypíše do logu souhrn konfidence OCR a stránky k ruční kontrole
+fn log_ocr_confidence(manifest: &manifest::BatchManifest, logs: &mut JobLog) {
+    let Some(c) = &manifest.ocr_confidence else {
+        return;
+    };
+    logs.push(format!(
+        "Konfidence OCR: {} slov, průměr {:.1} %, medián {:.1} %, pod prahem {:.1} %",
+        c.words,
+        c.mean * 100.0,
+        c.median * 100.0,
+        c.low_share * 100.0
+    ));
+    if !c.review_pages.is_empty() {
+        logs.push(format!(
+            "K ruční kontrole (průměr pod {:.0} %): {}",
+            c.threshold * 100.0,
+            c.review_pages.join(", ")
+        ));
+    }
+}
+
+/// Vypíše do logu shodu TXT se slovníkem za dávku a 10 nejhorších stránek
+fn log_dictionary(manifest: &manifest::BatchManifest, logs: &mut JobLog) {
+    let Some(d) = &manifest.dictionary else {
+        return;
+    };
+    logs.push(format!(
+        "Shoda se slovníkem ({}): {} z {} slov, {:.1} %",
+        d.wordlists.keys().cloned().collect::<Vec<_>>().join("+"),
+        d.hits,
+        d.words,
+        d.hit_rate * 100.0
+    ));
+    if !d.missing_languages.is_empty() {
+        logs.push(format!(
+            "Bez slovníku (--wordlist): {}",
+            d.missing_languages.join(", ")
+        ));
+    }
+    let mut pages: Vec<_> = manifest
+        .pages
+        .iter()
+        .filter_map(|p| p.dictionary.as_ref().filter(|h| h.words > 0).map(|h| (p, h)))
+        .collect();
+    pages.sort_by(|a, b| a.1.hit_rate.total_cmp(&b.1.hit_rate));
+    if pages.len() > 1 {
+        logs.push(format!(
+            "Nejnižší shoda se slovníkem: {}",
+            pages
+                .iter()
+                .take(10)
+                .map(|(p, h)| format!("{} ({:.1} %)", p.index, h.hit_rate * 100.0))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+}
+
+/// Adresář s logy, manifestem a stavem dávky: output_root / <název>_logs
+/// (u vnořených dávek vedle jejich výstupního adresáře, např. titul/2020/01_logs)
+fn batch_logs_dir(output_root: &Path, batch_name: &str) -> PathBuf {
+    output_root.join(format!("{batch_name}_logs"))
+}
+
+/// Neúspěšné kroky z uloženého stavu dávky (prázdné, pokud stav není)
+fn load_failed_pages(output_root: &Path, batch_name: &str) -> Vec<(String, &'static str, String)> {
+    BatchState::load(&batch_logs_dir(output_root, batch_name))
+        .map(|state| state.failures())
+        .unwrap_or_default()
+}
+
+/// Kompletní zpracování jedné dávky – běží ve vlákně workeru.
+/// 1. JP2/OCR/ALTO, 2. manifest + log.txt, 3. WebP náhledy, 4. HTML report
+fn execute_job(req: &JobRequest, logs: &mut JobLog) -> JobStatus {
+    let args = &req.args;
+    let dir = &req.dir;
+    let index_start = req.index_start;
+    let do_master = req.do_master;
+    let do_user = req.do_user;
+    let do_txt = req.do_txt;
+    let do_alto = req.do_alto;
+    let do_hocr = req.do_hocr;
+    let do_pdf = req.do_pdf;
+    let lang = &req.lang;
+    let alto_version = &req.alto_version;
+
+    let batch_out_dir = batch_output_dir(&req.output_root, &req.input_root, dir);
+    let _ = fs::create_dir_all(&batch_out_dir);
+    let logs_dir = batch_logs_dir(&req.output_root, &req.batch_name);
+    let _ = fs::create_dir_all(&logs_dir);
+    let merged_pdf = batch_out_dir.join(merged_pdf_name(&req.batch_name));
+
+    // Použitý začátek se zapamatuje, aby přidání jiné dávky tuto nepřečíslovalo
+    let numbering = BatchNumbering {
+        index_start,
+        explicit: BatchNumbering::load(&logs_dir).is_some_and(|n| n.explicit),
+        mode: args.numbering,
+    };
+    if let Err(e) = numbering.save(&logs_dir) {
+        logs.push(format!("Chyba při zápisu {}: {e:#}", numbering::NUMBERING_FILE));
+    }
+
+    let order = match collect_page_order(dir) {
+        Ok(order) => order,
+        Err(e) => {
+            logs.push(format!("Nelze určit pořadí stránek: {e:#}"));
+            return JobStatus::Failed(format!("{e:#}"));
+        }
+    };
+    logs.push(format!("Pořadí stránek: {}", order.describe()));
+    for name in &order.unlisted {
+        logs.push(format!("  {name} není v {} – zařazen na konec", ordering::ORDER_FILE));
+    }
+    let multi_page = order.pages.iter().filter(|p| p.page == Some(1)).count();
+    if multi_page > 0 {
+        logs.push(format!(
+            "Vícestránkové TIFFy: {multi_page} souborů → {} stránek celkem",
+            order.pages.len()
+        ));
+    }
+
+    // Kontrola vstupních TIFFů – blokující problém dávku nespustí
+    let preflight = preflight_batch(&order.files, &args.preflight);
+    if let Err(e) = write_preflight_report(&preflight, &logs_dir) {
+        logs.push(format!("Chyba při zápisu preflight.json: {e}"));
+    }
+    log_preflight(&preflight, logs);
+    if preflight.is_blocked() {
+        logs.push("Dávka ZASTAVENA kontrolou vstupních TIFFů".to_string());
+        return JobStatus::Failed(format!(
+            "Kontrola TIFFů: {} blokujících problémů",
+            preflight.blocking
+        ));
+    }
+
+    // Stav po stránkách z minulého běhu – hotové kroky se přeskočí
+    let state = if req.resume {
+        BatchState::load(&logs_dir)
+    } else {
+        None
+    };
+    let state = match state {
+        Some(st) if (do_master || do_user) && st.profile != req.profile.name => {
+            logs.push(format!(
+                "Profil kódování se změnil ({} → {}) – předchozí stav se nepoužije",
+                st.profile, req.profile.name
+            ));
+            None
+        }
+        other => other,
+    };
+    let resuming = state.is_some();
+    let mut state = state.unwrap_or_default();
+    // Jiné nastavení Tesseractu (včetně globálního jazyka / verze ALTO) = OCR znovu, JP2 zůstanou
+    let do_ocr = do_txt || do_alto || do_hocr || do_pdf;
+    let ocr_used = req.ocr.resolved(lang, alto_version);
+    if do_ocr && resuming && state.ocr.as_ref() != Some(&ocr_used) {
+        let forgotten = state.forget_step(Step::Ocr);
+        if forgotten > 0 {
+            logs.push(format!(
+                "Nastavení OCR se změnilo – OCR {forgotten} stránek se provede znovu"
+            ));
+        }
+    }
+    if do_ocr {
+        state.ocr = Some(ocr_used);
+    }
+    // Stránky, které po změně pořadí dostaly jiný index, se zpracují znovu
+    let sources: BTreeMap<String, String> = order
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| {
+            (
+                format!("{:0width$}", index_start + i as u32, width = args.digits),
+                page.key(),
+            )
+        })
+        .collect();
+    let moved = state.forget_changed_sources(&sources);
+    if moved > 0 {
+        logs.push(format!(
+            "Pořadí stránek se změnilo – {moved} stránek se zpracuje znovu"
+        ));
+    }
+    state.profile = req.profile.name.clone();
+    let tracker = StateTracker::new(state, &logs_dir);
+
+    logs.push(format!(
+        "=== Dávka {} ({}), start index {}, ALTO v{} ===",
+        req.batch_name,
+        dir.display(),
+        index_start,
+        alto_version
+    ));
+    
+    // Přidáme informace o použitém Tesseractu
+    logs.push(format!("Tesseract: {} ({})", 
+        req.tess_path.display(), 
+        req.tess_source));
+    logs.push(format!("Tessdata dir: {:?}", req.tessdata_dir));
+    logs.push(format!("Nastavení OCR: {}", req.ocr.describe()));
+    for path in req.ocr.missing_files() {
+        logs.push(format!("Varování: soubor `{}` z nastavení OCR neexistuje", path.display()));
+    }
+    if do_master || do_user {
+        logs.push(format!("Profil kódování: {}", req.profile.name));
+    }
+    if resuming {
+        logs.push("Navazuji na předchozí běh (state.json)".to_string());
+    }
+
+    // 1. Nejprve zpracujeme dávku (JP2, OCR, ALTO)
+    let res = process_batch(
+        args,
+        &req.grok_path,
+        &req.tess_path,
+        dir,
+        index_start,
+        &batch_out_dir,
+        do_master,
+        do_user,
+        do_txt,
+        do_alto,
+        do_hocr,
+        do_pdf,
+        lang,
+        alto_version,
+        req.tessdata_dir.as_deref(),
+        &req.ocr,
+        &req.profile,
+        &logs_dir.join(inputs::WORK_DIR),
+        &req.control,
+        &tracker,
+        logs,
+    );
+    let _ = fs::remove_dir(logs_dir.join(inputs::WORK_DIR));
+
+    if req.control.is_cancelled() {
+        logs.push("Dávka ZRUŠENA uživatelem".to_string());
+        return JobStatus::Cancelled;
+    }
+
+    let status = match res {
+        Ok(()) => {
+            logs.push("Dávka OK".to_string());
+            
+            // Malé zpoždění pro zápis souborů na disk
+            logs.push("Čekám na dokončení zápisu souborů...".to_string());
+            std::thread::sleep(std::time::Duration::from_millis(1000));
+            
+            // 2. Počkáme na vytvoření JP2 souborů (pokud jsou povoleny)
+            if do_master || do_user {
+                wait_for_jp2_files(&batch_out_dir, index_start, req.file_count, 
+                    args.digits, do_master, do_user, logs);
+            }
+            // 3. PDF stránek spojíme do jednoho PDF dávky
+            if req.merge_pdf
+                && let Err(e) = merge_batch_pdf(
+                    &batch_out_dir,
+                    &merged_pdf,
+                    index_start,
+                    req.file_count,
+                    args.digits,
+                    args.dry_run,
+                    logs,
+                )
+            {
+                logs.push(format!("Chyba při spojování PDF: {e:#}"));
+                JobStatus::Failed(format!("Spojení PDF: {e:#}"))
+            } else {
+                JobStatus::Done
+            }
+        }
+        Err(e) => {
+            logs.push("Dávka FAILED".to_string());
+            return JobStatus::Failed(e.to_string());
+        }
+    };
+
+    // Slovníky jazyků OCR pro odhad kvality z TXT
+    let dictionary = if do_txt {
+        match Dictionary::load(&args.wordlists, lang) {
+            Ok(None) if !args.wordlists.is_empty() => {
+                logs.push(format!("Odhad kvality slovníkem vynechán: pro jazyk {lang} není slovník"));
+                None
+            }
+            Ok(dictionary) => dictionary,
+            Err(e) => {
+                logs.push(format!("Odhad kvality slovníkem vynechán: {e:#}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // 3.+4. Teprve po úspěšném zpracování dávky vytvoříme manifest
+    // (až po vytvoření všech souborů), pak WebP a HTML
+    match build_manifest_for_batch(
+        &req.batch_name,
+        dir,
+        &batch_out_dir,
+        &logs_dir,
+        index_start,
+        args.digits,
+        do_master,
+        do_user,
+        do_txt,
+        do_alto,
+        do_hocr,
+        do_pdf,
+        req.merge_pdf.then_some(merged_pdf.as_path()),
+        lang,
+        alto_version,
+        &req.ocr,
+        &req.profile,
+        &args.checksums,
+        args.review_threshold,
+        dictionary.as_ref(),
+        logs,
+    ) {
+        Ok(manifest) => {
+            log_jp2_checks(&manifest, logs);
+            log_ocr_confidence(&manifest, logs);
+            log_dictionary(&manifest, logs);
+
+            // 5. Zapišeme manifest a log.txt - s přidáním logu
+            if let Err(e) = write_manifest_and_log_with_process_logs(&manifest, &logs_dir, logs.lines()) {
+                logs.push(format!("Chyba při zápisu manifestu/log.txt: {e}"));
+            } else {
+                logs.push("Manifest, checksums.txt a log.txt vytvořeny".to_string());
+            }
+
+            // 6. Zkontrolujeme, zda máme co konvertovat na WebP
+            let has_jp2_files = manifest.pages.iter().any(|p| p.ac_jp2.is_some() || p.uc_jp2.is_some());
+            
+            if has_jp2_files {
+                // 7. WebP náhledy (teprve po vytvoření JP2 a manifestu)
+                logs.push("Generuji WebP náhledy...".to_string());
+                match generate_webp_previews(&logs_dir, logs) {
+                    Ok(()) => {
+                        logs.push(format!(
+                            "WEBP náhledy vygenerovány v {} (z TIFF)",
+                            logs_dir.display()
+                        ));
+                    }
+                    Err(e) => {
+                        logs.push(format!(
+                            "Chyba při generování WEBP náhledů: {e}"
+                        ));
                     }
                 }
+            } else {
+                logs.push("Žádné JP2 soubory pro WebP konverzi".to_string());
+            }
+
+            // 8. HTML report (až po WebP)
+            match write_html_report(&logs_dir) {
+                Ok(()) => {
+                    logs.push(format!(
+                        "HTML report vytvořen: {}",
+                        logs_dir.join("index.html").display()
+                    ));
+                }
                 Err(e) => {
-                    local_logs.push(format!("Chyba při generování manifestu: {e}"));
+                    logs.push(format!(
+                        "Chyba při tvorbě HTML reportu: {e}"
+                    ));
                 }
             }
         }
-
-        for line in local_logs {
-            self.push_log(line);
+        Err(e) => {
+            logs.push(format!("Chyba při generování manifestu: {e}"));
         }
     }
+
+    status
 }
 
 /// Zkontroluje, zda byla dávka již kompletně zpracována
+#[allow(clippy::too_many_arguments)]
 fn check_batch_already_done(
     batch: &BatchJob,
     output_root: &Path,
@@ -1179,6 +2302,9 @@ fn check_batch_already_done(
     do_user: bool,
     do_txt: bool,
     do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    merge_pdf: bool,
     digits: usize,
 ) -> bool {
     let batch_out_dir = batch_output_dir(output_root, input_root, &batch.dir);
@@ -1195,11 +2321,18 @@ fn check_batch_already_done(
     if do_user { required_formats += 1; }
     if do_txt { required_formats += 1; }
     if do_alto { required_formats += 1; }
+    if do_hocr { required_formats += 1; }
+    if do_pdf { required_formats += 1; }
     
     // Pokud nic nemá být vygenerováno, považujeme za hotové
     if required_formats == 0 {
         return true;
     }
+
+    // Spojené PDF dávky
+    if do_pdf && merge_pdf && !batch_out_dir.join(merged_pdf_name(&batch.name)).exists() {
+        return false;
+    }
     
     // Pro každý soubor v dávce kontrolujeme
     for i in 0..batch.file_count {
@@ -1227,6 +2360,16 @@ fn check_batch_already_done(
             let alto_path = batch_out_dir.join(format!("{index_str}.ocr.xml"));
             if alto_path.exists() { file_formats += 1; }
         }
+
+        if do_hocr {
+            let hocr_path = batch_out_dir.join(format!("{index_str}.ocr.hocr"));
+            if hocr_path.exists() { file_formats += 1; }
+        }
+
+        if do_pdf {
+            let pdf_path = batch_out_dir.join(format!("{index_str}.ocr.pdf"));
+            if pdf_path.exists() { file_formats += 1; }
+        }
         
         // Pokud nějaký soubor nemá všechny požadované formáty, dávka není kompletní
         if file_formats < required_formats {
@@ -1238,6 +2381,7 @@ fn check_batch_already_done(
 }
 
 /// Aktualizace stavu všech dávek při startu nebo změně formátů
+#[allow(clippy::too_many_arguments)]
 fn update_jobs_status_on_start(
     jobs: &mut [BatchJob],
     output_root: &Path,
@@ -1246,12 +2390,27 @@ fn update_jobs_status_on_start(
     do_user: bool,
     do_txt: bool,
     do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    merge_pdf: bool,
     digits: usize,
 ) {
     for job in jobs.iter_mut() {
+        // Dávky ve frontě / právě zpracovávané ani zablokované nastavením nepřepisujeme
+        if matches!(
+            job.status,
+            JobStatus::Queued
+                | JobStatus::Processing
+                | JobStatus::Paused
+                | JobStatus::InvalidSettings(_)
+        ) {
+            continue;
+        }
+        job.failed_pages = load_failed_pages(output_root, &job.name);
+        job.quality = BatchQuality::load(&batch_logs_dir(output_root, &job.name));
         if check_batch_already_done(
             job, output_root, input_root, 
-            do_master, do_user, do_txt, do_alto, digits
+            do_master, do_user, do_txt, do_alto, do_hocr, do_pdf, merge_pdf, digits
         ) {
             // Pokud je již Done, zachováme to, jinak nastavíme AlreadyDone
             if !matches!(job.status, JobStatus::Done) {
@@ -1272,7 +2431,7 @@ fn wait_for_jp2_files(
     digits: usize,
     do_master: bool,
     do_user: bool,
-    logs: &mut Vec<String>,
+    logs: &mut JobLog,
 ) {
     let max_attempts = 5;
     let delay = std::time::Duration::from_millis(500);
@@ -1315,47 +2474,81 @@ fn wait_for_jp2_files(
     logs.push("Varování: Některé JP2 soubory nebyly vytvořeny včas".to_string());
 }
 
+/// Spojí PDF stránek (xxxx.ocr.pdf) v pořadí indexů do jednoho PDF dávky
+fn merge_batch_pdf(
+    output_dir: &Path,
+    merged_pdf: &Path,
+    index_start: u32,
+    file_count: usize,
+    digits: usize,
+    dry_run: bool,
+    logs: &mut JobLog,
+) -> Result<()> {
+    let pages: Vec<PathBuf> = (0..file_count)
+        .map(|i| output_dir.join(format!("{:0digits$}.ocr.pdf", index_start + i as u32)))
+        .collect();
+    logs.push(format!(
+        "Spojuji {} PDF stránek do {}",
+        pages.len(),
+        merged_pdf.display()
+    ));
+    if dry_run {
+        logs.push("(dry-run)".to_string());
+        return Ok(());
+    }
+    if let Some(missing) = pages.iter().find(|p| !p.exists()) {
+        bail!("Chybí PDF stránky `{}`", missing.display());
+    }
+    let count = merge_pdfs(&pages, merged_pdf)?;
+    logs.push(format!("✓ PDF dávky vytvořeno ({count} stran)"));
+    Ok(())
+}
+
 fn main() -> Result<()> {
-    let args = Args::parse();
+    // Konfigurace (uživatelská + projektová) se načte před CLI,
+    // hodnoty zadané na příkazové řádce ji přepíšou
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let loaded_config = load_config(args.config.as_deref())?;
+    loaded_config.config.apply_to_args(&mut args, &matches)?;
 
     // defaultní root / output adresáře v rootu programu
     let input_root = args.input.clone().unwrap_or_else(|| PathBuf::from("input"));
     let output_root = args.output.clone().unwrap_or_else(|| PathBuf::from("output"));
 
-    // output root vždy vytvořit
-    fs::create_dir_all(&output_root).with_context(|| {
-        format!(
-            "Nelze vytvořit výstupní kořenový adresář `{}`",
-            output_root.display()
-        )
-    })?;
-
-    // input root vytvoříme taky, pokud neexistuje
-    if !input_root.exists() {
-        fs::create_dir_all(&input_root).with_context(|| {
-            format!(
-                "Nelze vytvořit vstupní adresář `{}`",
-                input_root.display()
-            )
-        })?;
+    // Režim bez TUI; adresáře a nástroje připravuje jen `convert`
+    if let Some(ref command) = args.command {
+        let config = &loaded_config.config;
+        let env = HeadlessEnv {
+            args: &args,
+            input_root: &input_root,
+            output_root: &output_root,
+            formats: [
+                config.do_master.unwrap_or(true),
+                config.do_user.unwrap_or(true),
+                config.do_txt.unwrap_or(true),
+                config.do_alto.unwrap_or(true),
+                config.do_hocr.unwrap_or(false),
+                config.do_pdf.unwrap_or(false),
+            ],
+            merge_pdf: config.merge_pdf.unwrap_or(false),
+        };
+        let code = headless::run_command(command, &env)?;
+        std::process::exit(code);
     }
 
-    // zjištění Grok cesty + status + "verze"
-    let grok_path = resolve_grok_path(&args.grok_bin);
-    let (grok_status, grok_version) = check_grok(&grok_path, args.dry_run);
-
-    // zjištění Tesseract cesty + status s prioritou lokálního
-    let (tess_path, tess_source) = resolve_tess_path_with_priority(&args.tess_bin, args.force_local_tess);
-    let tess_status = check_tesseract(&tess_path, args.dry_run);
-    
-    // Najít nebo použít zadaný tessdata adresář
-    let tessdata_dir = if let Some(ref td) = args.tessdata_dir {
-        Some(td.clone())
-    } else {
-        find_tessdata_parent_dir(&tess_path)
-    };
-
-    let jobs = init_jobs_from_dirs(&input_root, args.start_index)?;
+    create_roots(&input_root, &output_root)?;
+    let Tools {
+        grok_path,
+        grok_status,
+        tess_path,
+        tess_source,
+        tess_status,
+        tessdata_dir,
+        languages: installed_languages,
+        profiles,
+    } = detect_tools(&args)?;
+    let jobs = init_jobs_from_dirs(&input_root, &output_root, &args)?;
 
     let mut app = App::new(
         args,
@@ -1364,11 +2557,15 @@ fn main() -> Result<()> {
         jobs,
         grok_path,
         grok_status,
-        grok_version,
         tess_path,
         tess_source,
         tess_status,
         tessdata_dir,
+        installed_languages,
+        profiles,
+        &loaded_config.config,
+        &loaded_config.sources,
+        loaded_config.save_path.clone(),
     );
 
     // Terminál
@@ -1380,6 +2577,9 @@ fn main() -> Result<()> {
 
     let res = run_app(&mut terminal, &mut app);
 
+    // Ukončení běžících Grok/Tesseract procesů a úklid neúplných výstupů
+    app.shutdown();
+
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -1395,93 +2595,297 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Vytvoří výstupní a (chybějící) vstupní kořenový adresář
+fn create_roots(input_root: &Path, output_root: &Path) -> Result<()> {
+    fs::create_dir_all(output_root).with_context(|| {
+        format!(
+            "Nelze vytvořit výstupní kořenový adresář `{}`",
+            output_root.display()
+        )
+    })?;
+
+    if !input_root.exists() {
+        fs::create_dir_all(input_root).with_context(|| {
+            format!(
+                "Nelze vytvořit vstupní adresář `{}`",
+                input_root.display()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Najde a ověří Grok a Tesseract, jazyky a profily kódování;
+/// neznámý profil z `--profile` je chyba
+fn detect_tools(args: &Args) -> Result<Tools> {
+    // zjištění Grok cesty + status
+    let grok_path = resolve_grok_path(&args.grok_bin);
+    let grok_status = check_grok(&grok_path, args.dry_run);
+
+    // zjištění Tesseract cesty + status s prioritou lokálního
+    let (tess_path, tess_source) = resolve_tess_path_with_priority(&args.tess_bin, args.force_local_tess);
+    let tess_status = check_tesseract(&tess_path, args.dry_run);
+
+    // Najít nebo použít zadaný tessdata adresář
+    let tessdata_dir = if let Some(ref td) = args.tessdata_dir {
+        Some(td.clone())
+    } else {
+        find_tessdata_parent_dir(&tess_path)
+    };
+
+    // Nainstalované jazyky Tesseractu
+    let languages = InstalledLanguages::discover(&tess_path, tessdata_dir.as_deref(), args.dry_run);
+
+    // Profily kódování Grok (vestavěný + ze souboru)
+    let profiles = load_profiles(args.profiles.as_deref())?;
+    if find_profile(&profiles, &args.profile).is_none() {
+        bail!(
+            "Neznámý profil kódování `{}` (dostupné: {})",
+            args.profile,
+            profiles
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    Ok(Tools {
+        grok_path,
+        grok_status,
+        tess_path,
+        tess_source,
+        tess_status,
+        tessdata_dir,
+        languages,
+        profiles,
+    })
+}
+
 /// Spočítá cílový adresář pro dávku:
 /// - pokud dávka je přímo input_root → output_root
-/// - jinak output_root / <název dávky>
+/// - jinak output_root / <cesta dávky od input_root> (titul/2020/01)
 fn batch_output_dir(output_root: &Path, input_root: &Path, batch_dir: &Path) -> PathBuf {
     if batch_dir == input_root {
         output_root.to_path_buf()
     } else {
-        match batch_dir.file_name() {
-            Some(name) => output_root.join(name),
-            None => output_root.to_path_buf(),
+        match batch_dir.strip_prefix(input_root) {
+            Ok(rel) => output_root.join(rel),
+            Err(_) => match batch_dir.file_name() {
+                Some(name) => output_root.join(name),
+                None => output_root.to_path_buf(),
+            },
         }
     }
 }
 
 /// Inicializace dávek podle adresářů v input_root:
-/// - dávka = samotný input_root (pokud obsahuje TIFFy)
-/// - + každý podadresář (jen 1. úroveň) s nějakými TIFFy
-/// Indexy stránek běží sekvenčně přes všechny dávky.
-fn init_jobs_from_dirs(input_root: &Path, start_index: u32) -> Result<Vec<BatchJob>> {
+/// - dávka = samotný input_root (pokud obsahuje vstupní soubory)
+/// - + každý podadresář (jen 1. úroveň) se vstupními soubory
+/// - s `--recursive` každý takový adresář v celém stromu (do `--max-depth`)
+///
+/// Vstupní soubory viz `InputFormat`; první indexy dávek viz `renumber_jobs`.
+fn init_jobs_from_dirs(input_root: &Path, output_root: &Path, args: &Args) -> Result<Vec<BatchJob>> {
     let mut batch_dirs: Vec<PathBuf> = Vec::new();
 
-    // nejdřív samotný root, pokud obsahuje TIFFy
-    if has_tiffs_in_dir(input_root)? {
+    // nejdřív samotný root, pokud obsahuje vstupní soubory
+    if has_inputs_in_dir(input_root)? {
         batch_dirs.push(input_root.to_path_buf());
     }
 
-    // potom první úroveň podadresářů
-    if input_root.is_dir() {
-        for entry in fs::read_dir(input_root)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() && has_tiffs_in_dir(&path)? {
-                batch_dirs.push(path);
-            }
-        }
-    }
+    // potom podadresáře (bez --recursive jen první úroveň)
+    let max_depth = if args.recursive {
+        args.max_depth.unwrap_or(usize::MAX)
+    } else {
+        1
+    };
+    collect_batch_dirs(input_root, max_depth, &mut batch_dirs)?;
 
-    // seřadíme podle názvu pro deterministické pořadí
+    // seřadíme podle cesty (přirozeně, dávka_2 < dávka_10) pro deterministické pořadí;
+    // samotný root (prázdná relativní cesta) zůstane první
     batch_dirs.sort_by(|a, b| {
-        let a_name = a.file_name().and_then(|s| s.to_str()).unwrap_or("");
-        let b_name = b.file_name().and_then(|s| s.to_str()).unwrap_or("");
-        a_name.cmp(b_name)
+        let a_rel = a.strip_prefix(input_root).unwrap_or(a);
+        let b_rel = b.strip_prefix(input_root).unwrap_or(b);
+        ordering::natural_path_cmp(a_rel, b_rel)
     });
 
     let mut jobs = Vec::new();
-    let mut next_index = start_index;
 
     for dir in batch_dirs {
-        let files = collect_tiffs_in_dir(&dir)?;
-        let count = files.len();
+        let name = batch_name(input_root, &dir);
+        // Chybný order.txt zablokuje jen svou dávku, ne načtení ostatních
+        let (order, status) = match collect_page_order(&dir) {
+            Ok(order) => (order, JobStatus::Pending),
+            Err(e) => (
+                with_pages(natural_order(list_inputs_in_dir(&dir)?)),
+                JobStatus::Failed(format!("{e:#}")),
+            ),
+        };
+        let count = order.pages.len();
         if count == 0 {
             continue;
         }
         jobs.push(BatchJob {
             dir: dir.clone(),
-            index_start: next_index,
+            name,
+            index_start: args.start_index,
+            explicit_start: false,
             file_count: count,
-            status: JobStatus::Pending,
+            status,
+            pages_done: 0,
+            control: None,
+            run: 0,
+            force_full: false,
+            failed_pages: Vec::new(),
+            profile: args.profile.clone(),
+            preflight: preflight_batch(&order.files, &args.preflight),
+            order,
+            ocr: OcrSettings::from_args(args),
+            ocr_custom: false,
+            quality: None,
         });
-        next_index += count as u32;
+        // Chybný ocr.json zablokuje jen svou dávku
+        let job = jobs.last_mut().expect("dávka právě přidána");
+        let loaded = load_batch_ocr(output_root, job, args);
+        let _ = set_batch_ocr(job, loaded);
     }
 
+    renumber_jobs(&mut jobs, output_root, args)?;
     Ok(jobs)
 }
 
-/// Vrátí true, pokud v daném adresáři jsou nějaké TIFFy (pouze 1. úroveň, ne rekurzivně).
-fn has_tiffs_in_dir(dir: &Path) -> Result<bool> {
+/// Nastavení OCR dávky: vlastní z `*_logs/ocr_settings.json`, jinak výchozí z CLI / konfigurace
+/// překryté `ocr.json` v adresáři dávky. Druhá hodnota říká, zda jde o nastavení z TUI.
+fn load_batch_ocr(
+    output_root: &Path,
+    batch: &BatchJob,
+    args: &Args,
+) -> Result<(OcrSettings, bool)> {
+    match OcrSettings::load(&batch_logs_dir(output_root, &batch.name)) {
+        Some(settings) => Ok((settings, true)),
+        None => Ok((OcrSettings::for_batch(args, &batch.dir)?, false)),
+    }
+}
+
+/// Převezme načtené nastavení OCR dávky. Chyba dávku zablokuje
+/// (InvalidSettings), platné nastavení blokaci zruší.
+fn set_batch_ocr(job: &mut BatchJob, loaded: Result<(OcrSettings, bool)>) -> Result<()> {
+    match loaded {
+        Ok((ocr, custom)) => {
+            (job.ocr, job.ocr_custom) = (ocr, custom);
+            if matches!(job.status, JobStatus::InvalidSettings(_)) {
+                job.status = JobStatus::Pending;
+            }
+            Ok(())
+        }
+        Err(e) => {
+            job.status = JobStatus::InvalidSettings(format!("{e:#}"));
+            Err(e)
+        }
+    }
+}
+
+/// Přepočítá první indexy dávek podle režimu číslování a uložených začátků
+/// (`*_logs/numbering.json`). Dávky ve frontě / při zpracování si začátek ponechají.
+/// Při přetečení indexů se nic nemění a vrátí se chyba.
+fn renumber_jobs(jobs: &mut [BatchJob], output_root: &Path, args: &Args) -> Result<()> {
+    let batches = numbering_batches(jobs, output_root, args);
+    let starts = assign_starts(args.numbering, args.start_index, &batches)?;
+    for ((job, start), (recorded, _)) in jobs.iter_mut().zip(starts).zip(&batches) {
+        if !is_running(job) {
+            job.index_start = start;
+            job.explicit_start = recorded.as_ref().is_some_and(|r| r.explicit);
+        }
+    }
+    Ok(())
+}
+
+/// Vstup pro `assign_starts`: (uložené číslování, počet stránek) každé dávky.
+/// Dávka ve frontě / při zpracování drží svůj začátek jako ruční.
+fn numbering_batches(
+    jobs: &[BatchJob],
+    output_root: &Path,
+    args: &Args,
+) -> Vec<(Option<BatchNumbering>, usize)> {
+    jobs.iter()
+        .map(|j| {
+            let recorded = if is_running(j) {
+                Some(BatchNumbering {
+                    index_start: j.index_start,
+                    explicit: true,
+                    mode: args.numbering,
+                })
+            } else {
+                BatchNumbering::load(&batch_logs_dir(output_root, &j.name))
+            };
+            (recorded, j.file_count)
+        })
+        .collect()
+}
+
+fn is_running(job: &BatchJob) -> bool {
+    matches!(
+        job.status,
+        JobStatus::Queued | JobStatus::Processing | JobStatus::Paused
+    )
+}
+
+/// Podadresáře `dir` se vstupními soubory do hloubky `depth`. Symbolické odkazy
+/// na adresáře se při rekurzi (hloubka > 1) nenásledují kvůli cyklům.
+fn collect_batch_dirs(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) -> Result<()> {
+    if depth == 0 || !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_dir() || (depth > 1 && entry.file_type()?.is_symlink()) {
+            continue;
+        }
+        if has_inputs_in_dir(&path)? {
+            out.push(path.clone());
+        }
+        collect_batch_dirs(&path, depth - 1, out)?;
+    }
+    Ok(())
+}
+
+/// Vrátí true, pokud v daném adresáři jsou nějaké vstupní soubory (TIFF, JPEG,
+/// PNG, JP2; pouze 1. úroveň, ne rekurzivně).
+fn has_inputs_in_dir(dir: &Path) -> Result<bool> {
     if !dir.is_dir() {
         return Ok(false);
     }
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path.is_file() {
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                let ext_lower = ext.to_lowercase();
-                if ext_lower == "tif" || ext_lower == "tiff" {
-                    return Ok(true);
-                }
-            }
+        if path.is_file() && InputFormat::from_path(&path).is_some() {
+            return Ok(true);
         }
     }
     Ok(false)
 }
 
-/// Najde všechny .tif / .tiff soubory v daném adresáři (pouze 1. úroveň, ne rekurzivně).
-pub fn collect_tiffs_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+/// Najde všechny stránky dávky: vstupní soubory (viz `InputFormat`) v daném adresáři
+/// (pouze 1. úroveň, ne rekurzivně) v pořadí podle order.txt, jinak přirozeným řazením
+/// názvů; vícestránkové TIFFy rozdělené na jednotlivé stránky.
+pub fn collect_tiffs_in_dir(dir: &Path) -> Result<Vec<PageSource>> {
+    Ok(collect_page_order(dir)?.pages)
+}
+
+/// Pořadí stránek dávky včetně informace, odkud pochází
+pub fn collect_page_order(dir: &Path) -> Result<PageOrder> {
+    Ok(with_pages(page_order(dir, list_inputs_in_dir(dir)?)?))
+}
+
+/// Doplní do pořadí stránky (rozdělí vícestránkové TIFFy)
+fn with_pages(mut order: PageOrder) -> PageOrder {
+    order.pages = expand_pages(&order.files);
+    order
+}
+
+/// Vstupní soubory v adresáři v pořadí, jak je vrátí systém
+fn list_inputs_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
     let mut out = Vec::new();
     if !dir.is_dir() {
         return Ok(out);
@@ -1489,16 +2893,10 @@ pub fn collect_tiffs_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path.is_file() {
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                let ext_lower = ext.to_lowercase();
-                if ext_lower == "tif" || ext_lower == "tiff" {
-                    out.push(path);
-                }
-            }
+        if path.is_file() && InputFormat::from_path(&path).is_some() {
+            out.push(path);
         }
     }
-    out.sort();
     Ok(out)
 }
 
@@ -1536,6 +2934,11 @@ fn run_app<B: ratatui::backend::Backend>(
     let mut force_full_redraw = true;
 
     loop {
+        // Zprávy z workeru (log, průběh) – po dokončení dávky překreslíme vše
+        if app.poll_worker() {
+            force_full_redraw = true;
+        }
+
         // Pokud potřebujeme kompletní překreslení, zavoláme clear()
         if force_full_redraw {
             terminal.clear()?;
@@ -1559,7 +2962,12 @@ fn run_app<B: ratatui::backend::Backend>(
                     
                     match app.mode {
                         UiMode::Normal => match key.code {
-                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Char('q') => {
+                                if app.request_quit() {
+                                    return Ok(());
+                                }
+                                needs_full_redraw = true;
+                            }
                             KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => {
                                 // Změna výběru nebo scrollování - vyžaduje kompletní překreslení
                                 match key.code {
@@ -1596,20 +3004,56 @@ fn run_app<B: ratatui::backend::Backend>(
                                 app.toggle_alto();
                                 needs_full_redraw = true;
                             }
+                            KeyCode::Char('h') => {
+                                app.toggle_hocr();
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('d') => {
+                                app.toggle_pdf();
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('D') => {
+                                app.toggle_merge_pdf();
+                                needs_full_redraw = true;
+                            }
                             KeyCode::Char('i') | KeyCode::Char('I') => { 
                                 app.start_edit_input(); 
                                 needs_full_redraw = true;
                             }
-                            KeyCode::Char('o') | KeyCode::Char('O') => { 
-                                app.start_edit_output(); 
+                            KeyCode::Char('o') | KeyCode::Char('O') => { 
+                                app.start_edit_output(); 
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('L') => { 
+                                app.show_language_menu(); 
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('A') => { 
+                                app.show_alto_version_menu(); 
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('S') => {
+                                app.save_config();
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('G') => {
+                                app.show_profile_menu();
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('T') => {
+                                app.show_ocr_menu();
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('N') => {
+                                app.start_edit_start_index();
                                 needs_full_redraw = true;
                             }
-                            KeyCode::Char('L') => { 
-                                app.show_language_menu(); 
+                            KeyCode::Char('M') => {
+                                app.toggle_numbering();
                                 needs_full_redraw = true;
                             }
-                            KeyCode::Char('A') => { 
-                                app.show_alto_version_menu(); 
+                            KeyCode::Char('K') => {
+                                app.page_sort = app.page_sort.next();
                                 needs_full_redraw = true;
                             }
                             KeyCode::Enter => { 
@@ -1628,9 +3072,20 @@ fn run_app<B: ratatui::backend::Backend>(
                                 app.force_rerun_all();
                                 needs_full_redraw = true;
                             }
+                            KeyCode::Char('p') => {
+                                app.toggle_pause_selected();
+                                needs_full_redraw = true;
+                            }
+                            KeyCode::Char('c') => {
+                                app.cancel_selected();
+                                needs_full_redraw = true;
+                            }
                             _ => {}
                         },
-                        UiMode::EditInput | UiMode::EditOutput => {
+                        UiMode::EditInput
+                        | UiMode::EditOutput
+                        | UiMode::EditStartIndex
+                        | UiMode::EditOcrField => {
                             app.handle_edit_key(key.code);
                             needs_full_redraw = true;
                         }
@@ -1642,6 +3097,14 @@ fn run_app<B: ratatui::backend::Backend>(
                             app.handle_alto_version_menu_key(key.code);
                             needs_full_redraw = true;
                         }
+                        UiMode::ProfileMenu => {
+                            app.handle_profile_menu_key(key.code);
+                            needs_full_redraw = true;
+                        }
+                        UiMode::OcrSettingsMenu => {
+                            app.handle_ocr_menu_key(key.code);
+                            needs_full_redraw = true;
+                        }
                         UiMode::CustomLangInput => {
                             app.handle_custom_lang_key(key.code);
                             needs_full_redraw = true;
@@ -1765,11 +3228,18 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
         UiMode::Normal => Span::raw("Normal"),
         UiMode::EditInput => Span::styled("Editace input", Style::default().fg(Color::Yellow)),
         UiMode::EditOutput => Span::styled("Editace output", Style::default().fg(Color::Yellow)),
+        UiMode::EditStartIndex => {
+            Span::styled("Číslování dávky", Style::default().fg(Color::Yellow))
+        }
         UiMode::LanguageMenu => Span::styled("Výběr jazyka", Style::default().fg(Color::Yellow)),
         UiMode::AltoVersionMenu => Span::styled("Výběr ALTO", Style::default().fg(Color::Yellow)),
+        UiMode::ProfileMenu => Span::styled("Výběr profilu", Style::default().fg(Color::Yellow)),
         UiMode::CustomLangInput => {
             Span::styled("Vlastní jazyk", Style::default().fg(Color::Yellow))
         }
+        UiMode::OcrSettingsMenu | UiMode::EditOcrField => {
+            Span::styled("Nastavení OCR", Style::default().fg(Color::Yellow))
+        }
     };
 
     let mode_line = Line::from(vec![
@@ -1808,14 +3278,13 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
         .iter()
         .enumerate()
         .map(|(i, batch)| {
-            let batch_name = batch
-                .dir
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("<root>");
+            let batch_name = batch.name.as_str();
 
             let (status_icon, status_style) = match &batch.status {
                 JobStatus::Pending => ("○", Style::default().fg(Color::DarkGray)),
+                JobStatus::Queued => ("…", Style::default().fg(Color::Yellow)),
+                JobStatus::Paused => ("⏸", Style::default().fg(Color::Yellow)),
+                JobStatus::Cancelled => ("⊘", Style::default().fg(Color::Magenta)),
                 JobStatus::Processing => (
                     "↻",
                     Style::default()
@@ -1834,7 +3303,7 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
                         .fg(Color::Blue)
                         .add_modifier(Modifier::BOLD),
                 ),
-                JobStatus::Failed(_) => (
+                JobStatus::Failed(_) | JobStatus::InvalidSettings(_) => (
                     "✗",
                     Style::default()
                         .fg(Color::Red)
@@ -1871,6 +3340,12 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
                 ),
                 Style::default().fg(Color::DarkGray),
             ));
+            if matches!(batch.status, JobStatus::Processing | JobStatus::Paused) {
+                spans.push(Span::styled(
+                    format!(" {}/{}", batch.pages_done, batch.file_count),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
 
             ListItem::new(Line::from(spans))
         })
@@ -1903,11 +3378,7 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
 
     if !app.jobs.is_empty() {
         let batch = &app.jobs[app.selected];
-        let batch_name = batch
-            .dir
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("<root>");
+        let batch_name = batch.name.as_str();
 
         detail_lines.push(Line::from(vec![
             Span::styled("Dávka: ", Style::default().fg(Color::Cyan)),
@@ -1921,14 +3392,25 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
 
         let status_text = match &batch.status {
             JobStatus::Pending => Span::styled("Čeká", Style::default().fg(Color::DarkGray)),
-            JobStatus::Processing => {
-                Span::styled("Zpracovává se", Style::default().fg(Color::Yellow))
-            }
+            JobStatus::Queued => Span::styled("Ve frontě", Style::default().fg(Color::Yellow)),
+            JobStatus::Processing => Span::styled(
+                format!("Zpracovává se ({}/{} stránek)", batch.pages_done, batch.file_count),
+                Style::default().fg(Color::Yellow),
+            ),
+            JobStatus::Paused => Span::styled(
+                format!("Pozastaveno ({}/{} stránek)", batch.pages_done, batch.file_count),
+                Style::default().fg(Color::Yellow),
+            ),
+            JobStatus::Cancelled => Span::styled("Zrušeno", Style::default().fg(Color::Magenta)),
             JobStatus::Done => Span::styled("Hotovo", Style::default().fg(Color::Green)),
             JobStatus::AlreadyDone => Span::styled("Již hotovo", Style::default().fg(Color::Blue)),
             JobStatus::Failed(e) => {
                 Span::styled(format!("Chyba: {}", e), Style::default().fg(Color::Red))
             }
+            JobStatus::InvalidSettings(e) => Span::styled(
+                format!("Neplatné nastavení OCR: {}", e),
+                Style::default().fg(Color::Red),
+            ),
         };
 
         detail_lines.push(Line::from(vec![
@@ -1953,6 +3435,213 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
                 Style::default().fg(Color::White),
             ),
         ]));
+
+        detail_lines.push(Line::from(vec![
+            Span::styled("Číslování: ", Style::default().fg(Color::Cyan)),
+            Span::styled(
+                if batch.explicit_start {
+                    "ručně"
+                } else {
+                    app.args.numbering.label()
+                },
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(" (N: začátek, M: režim)", Style::default().fg(Color::DarkGray)),
+        ]));
+
+        detail_lines.push(Line::from(vec![
+            Span::styled("Profil: ", Style::default().fg(Color::Cyan)),
+            Span::styled(batch.profile.clone(), Style::default().fg(Color::White)),
+            Span::styled(" (G: změnit)", Style::default().fg(Color::DarkGray)),
+        ]));
+
+        detail_lines.push(Line::from(vec![
+            Span::styled("OCR: ", Style::default().fg(Color::Cyan)),
+            Span::styled(batch.ocr.describe(), Style::default().fg(Color::White)),
+            Span::styled(
+                if batch.ocr_custom { " (vlastní, T: změnit)" } else { " (T: změnit)" },
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+
+        detail_lines.push(Line::from(vec![
+            Span::styled("Pořadí: ", Style::default().fg(Color::Cyan)),
+            Span::styled(batch.order.describe(), Style::default().fg(Color::White)),
+        ]));
+        let digits = app.args.digits;
+        for (i, page) in batch.order.pages.iter().enumerate().take(10) {
+            let name = page.path.file_name().and_then(|s| s.to_str()).unwrap_or("?");
+            let unlisted = batch.order.unlisted.iter().any(|u| u == name);
+            detail_lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:0digits$} ← ", batch.index_start + i as u32),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    page.label(),
+                    Style::default().fg(if unlisted { Color::Yellow } else { Color::White }),
+                ),
+            ]));
+        }
+        if batch.order.pages.len() > 10 {
+            detail_lines.push(Line::from(format!(
+                "  … a dalších {}",
+                batch.order.pages.len() - 10
+            )));
+        }
+
+        let preflight = &batch.preflight;
+        let (preflight_text, preflight_color) = if preflight.is_blocked() {
+            (
+                format!("{} blokujících, {} varování", preflight.blocking, preflight.warnings),
+                Color::Red,
+            )
+        } else if preflight.warnings > 0 {
+            (format!("{} varování", preflight.warnings), Color::Yellow)
+        } else {
+            ("OK".to_string(), Color::Green)
+        };
+        detail_lines.push(Line::from(vec![
+            Span::styled("Kontrola TIFF: ", Style::default().fg(Color::Cyan)),
+            Span::styled(preflight_text, Style::default().fg(preflight_color)),
+        ]));
+        let issue_count = preflight.issues().count();
+        for (path, issue) in preflight.issues().take(10) {
+            let color = if issue.action == RuleAction::Block {
+                Color::Red
+            } else {
+                Color::Yellow
+            };
+            detail_lines.push(Line::from(vec![
+                Span::styled(
+                    format!(
+                        "  {}: ",
+                        path.file_name().and_then(|s| s.to_str()).unwrap_or("?")
+                    ),
+                    Style::default().fg(color),
+                ),
+                Span::raw(issue.message.clone()),
+            ]));
+        }
+        if issue_count > 10 {
+            detail_lines.push(Line::from(format!("  … a dalších {}", issue_count - 10)));
+        }
+
+        if let Some(quality) = &batch.quality
+            && let Some(c) = &quality.ocr_confidence
+        {
+            let review_count = c.review_pages.len();
+            detail_lines.push(Line::from(vec![
+                Span::styled("Konfidence OCR: ", Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    format!(
+                        "průměr {:.1} %, medián {:.1} %, pod prahem {:.1} % z {} slov",
+                        c.mean * 100.0,
+                        c.median * 100.0,
+                        c.low_share * 100.0,
+                        c.words
+                    ),
+                    Style::default().fg(if review_count > 0 { Color::Yellow } else { Color::White }),
+                ),
+            ]));
+            if review_count > 0 {
+                detail_lines.push(Line::from(Span::styled(
+                    format!(
+                        "K ruční kontrole ({}, průměr pod {:.0} %):",
+                        review_count,
+                        c.threshold * 100.0
+                    ),
+                    Style::default().fg(Color::Yellow),
+                )));
+                for (index, page) in quality.review_pages().take(10) {
+                    detail_lines.push(Line::from(vec![
+                        Span::styled(format!("  {index}: "), Style::default().fg(Color::Yellow)),
+                        Span::raw(format!(
+                            "průměr {:.1} %, medián {:.1} %, {} slov, pod prahem {:.1} %",
+                            page.mean * 100.0,
+                            page.median * 100.0,
+                            page.words,
+                            page.low_share * 100.0
+                        )),
+                    ]));
+                }
+                if review_count > 10 {
+                    detail_lines.push(Line::from(format!("  … a dalších {}", review_count - 10)));
+                }
+            }
+        }
+
+        if let Some(quality) = &batch.quality
+            && let Some(d) = &quality.dictionary
+        {
+            detail_lines.push(Line::from(vec![
+                Span::styled("Shoda se slovníkem: ", Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    format!(
+                        "{:.1} % ({} z {} slov, {})",
+                        d.hit_rate * 100.0,
+                        d.hits,
+                        d.words,
+                        d.wordlists.keys().cloned().collect::<Vec<_>>().join("+")
+                    ),
+                    Style::default().fg(Color::White),
+                ),
+            ]));
+        }
+
+        if let Some(quality) = &batch.quality {
+            let pages = quality.sorted_pages(app.page_sort, app.args.dictionary_threshold);
+            if !pages.is_empty() || app.page_sort != PageSort::Index {
+                detail_lines.push(Line::from(vec![
+                    Span::styled("Kvalita stránek: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(app.page_sort.label(), Style::default().fg(Color::White)),
+                    Span::styled(" (K: změnit)", Style::default().fg(Color::DarkGray)),
+                ]));
+            }
+            for page in pages.iter().take(15) {
+                let mut spans = vec![Span::styled(
+                    format!("  {} ", page.index),
+                    Style::default().fg(Color::DarkGray),
+                )];
+                if let Some(d) = &page.dictionary {
+                    let low = d.words > 0 && d.hit_rate < app.args.dictionary_threshold;
+                    spans.push(Span::styled(
+                        format!("slovník {:5.1} % ({}/{})", d.hit_rate * 100.0, d.hits, d.words),
+                        Style::default().fg(if low { Color::Yellow } else { Color::White }),
+                    ));
+                    spans.push(Span::raw("  "));
+                }
+                if let Some(c) = &page.ocr_confidence {
+                    spans.push(Span::styled(
+                        format!("konfidence {:5.1} %", c.mean * 100.0),
+                        Style::default().fg(if c.needs_review { Color::Yellow } else { Color::White }),
+                    ));
+                }
+                detail_lines.push(Line::from(spans));
+            }
+            if pages.len() > 15 {
+                detail_lines.push(Line::from(format!("  … a dalších {}", pages.len() - 15)));
+            }
+        }
+
+        if !batch.failed_pages.is_empty() {
+            detail_lines.push(Line::from(Span::styled(
+                format!("Neúspěšné kroky ({}):", batch.failed_pages.len()),
+                Style::default().fg(Color::Red),
+            )));
+            for (index, step, reason) in batch.failed_pages.iter().take(10) {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(format!("  {index} {step}: "), Style::default().fg(Color::Red)),
+                    Span::raw(reason.clone()),
+                ]));
+            }
+            if batch.failed_pages.len() > 10 {
+                detail_lines.push(Line::from(format!(
+                    "  … a dalších {}",
+                    batch.failed_pages.len() - 10
+                )));
+            }
+        }
     } else {
         detail_lines.push(Line::from("Žádné dávky"));
     }
@@ -2004,6 +3693,40 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
             }),
         ),
     ]));
+    detail_lines.push(Line::from(vec![
+        Span::styled("hOCR:", Style::default().fg(Color::Cyan)),
+        Span::raw(" "),
+        Span::styled(
+            bool_label(app.do_hocr),
+            Style::default().fg(if app.do_hocr {
+                Color::Green
+            } else {
+                Color::DarkGray
+            }),
+        ),
+        Span::raw("  "),
+        Span::styled("PDF:", Style::default().fg(Color::Cyan)),
+        Span::raw(" "),
+        Span::styled(
+            bool_label(app.do_pdf),
+            Style::default().fg(if app.do_pdf {
+                Color::Green
+            } else {
+                Color::DarkGray
+            }),
+        ),
+        Span::raw("  "),
+        Span::styled("Spojené PDF:", Style::default().fg(Color::Cyan)),
+        Span::raw(" "),
+        Span::styled(
+            bool_label(app.do_pdf && app.merge_pdf),
+            Style::default().fg(if app.do_pdf && app.merge_pdf {
+                Color::Green
+            } else {
+                Color::DarkGray
+            }),
+        ),
+    ]));
 
     detail_lines.push(Line::from(""));
     detail_lines.push(Line::from(vec![Span::styled(
@@ -2013,7 +3736,7 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
             .add_modifier(Modifier::BOLD),
     )]));
     detail_lines.push(Line::from(vec![
-        Span::styled("m/u/t/l", Style::default().fg(Color::Yellow)),
+        Span::styled("m/u/t/l/h/d", Style::default().fg(Color::Yellow)),
         Span::raw(": přepínání formátů  "),
         Span::styled("L", Style::default().fg(Color::Yellow)),
         Span::raw("/"),
@@ -2028,12 +3751,36 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
         Span::styled("Enter", Style::default().fg(Color::Yellow)),
         Span::raw(": spustit dávku"),
     ]));
+    detail_lines.push(Line::from(vec![
+        Span::styled("N", Style::default().fg(Color::Yellow)),
+        Span::raw("/"),
+        Span::styled("M", Style::default().fg(Color::Yellow)),
+        Span::raw(": začátek / režim číslování  "),
+        Span::styled("D", Style::default().fg(Color::Yellow)),
+        Span::raw(": spojené PDF  "),
+        Span::styled("K", Style::default().fg(Color::Yellow)),
+        Span::raw(": řazení stránek"),
+    ]));
     detail_lines.push(Line::from(vec![
         Span::styled("F", Style::default().fg(Color::Yellow)),
         Span::raw(": vynutit přepracování  "),
         Span::styled("R", Style::default().fg(Color::Yellow)),
         Span::raw(": vynutit přepracování všech"),
     ]));
+    detail_lines.push(Line::from(vec![
+        Span::styled("p", Style::default().fg(Color::Yellow)),
+        Span::raw(": pauza/pokračovat  "),
+        Span::styled("c", Style::default().fg(Color::Yellow)),
+        Span::raw(": zrušit dávku"),
+    ]));
+    detail_lines.push(Line::from(vec![
+        Span::styled("G", Style::default().fg(Color::Yellow)),
+        Span::raw(": profil kódování  "),
+        Span::styled("T", Style::default().fg(Color::Yellow)),
+        Span::raw(": nastavení OCR  "),
+        Span::styled("S", Style::default().fg(Color::Yellow)),
+        Span::raw(": uložit nastavení"),
+    ]));
     detail_lines.push(Line::from(vec![
         Span::styled("a", Style::default().fg(Color::Yellow)),
         Span::raw(": všechny čekající  "),
@@ -2082,7 +3829,7 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
     let failed_batches = app
         .jobs
         .iter()
-        .filter(|j| matches!(j.status, JobStatus::Failed(_)))
+        .filter(|j| matches!(j.status, JobStatus::Failed(_) | JobStatus::InvalidSettings(_)))
         .count();
 
     let total_pages: usize = app.jobs.iter().map(|j| j.file_count).sum();
@@ -2185,6 +3932,26 @@ fn render_main_ui(f: &mut Frame<'_>, app: &App) {
                 Color::DarkGray
             }),
         ),
+        Span::raw(" "),
+        Span::styled("hOCR:", Style::default().fg(Color::Cyan)),
+        Span::styled(
+            bool_label(app.do_hocr),
+            Style::default().fg(if app.do_hocr {
+                Color::Green
+            } else {
+                Color::DarkGray
+            }),
+        ),
+        Span::raw(" "),
+        Span::styled("PDF:", Style::default().fg(Color::Cyan)),
+        Span::styled(
+            bool_label(app.do_pdf),
+            Style::default().fg(if app.do_pdf {
+                Color::Green
+            } else {
+                Color::DarkGray
+            }),
+        ),
     ]);
 
     let status_bar = Paragraph::new(status_line).block(Block::default().borders(Borders::NONE));
@@ -2264,7 +4031,8 @@ fn render_overlay_ui(f: &mut Frame<'_>, app: &App) {
                 .available_languages
                 .iter()
                 .enumerate()
-                .map(|(i, (code, name))| {
+                .map(|(i, entry)| {
+                    let (code, name) = (&entry.code, &entry.name);
                     let is_selected = Some(i) == app.language_list_state.selected();
                     let is_current = code == &app.args.lang;
 
@@ -2299,7 +4067,7 @@ fn render_overlay_ui(f: &mut Frame<'_>, app: &App) {
                             name,
                             Style::default().fg(Color::Yellow),
                         ));
-                    } else {
+                    } else if entry.installed {
                         spans.push(Span::styled(
                             format!("{:15} ", code),
                             Style::default().fg(Color::White),
@@ -2308,6 +4076,21 @@ fn render_overlay_ui(f: &mut Frame<'_>, app: &App) {
                             name,
                             Style::default().fg(Color::Gray),
                         ));
+                    } else {
+                        spans.push(Span::styled(
+                            format!("{:15} ", code),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                        spans.push(Span::styled(
+                            name,
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    if !entry.installed {
+                        spans.push(Span::styled(
+                            " (nenainstalováno)",
+                            Style::default().fg(Color::Red),
+                        ));
                     }
 
                     ListItem::new(Line::from(spans))
@@ -2404,9 +4187,212 @@ fn render_overlay_ui(f: &mut Frame<'_>, app: &App) {
                         .add_modifier(Modifier::BOLD),
                 );
 
-            let mut state = app.alto_version_list_state.clone();
+            let mut state = app.alto_version_list_state.clone();
+            f.render_stateful_widget(list, inner_area, &mut state);
+        }
+        UiMode::ProfileMenu => {
+            let area = centered_rect(60, 40, f.size());
+
+            let background_block = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Black));
+            f.render_widget(background_block, area);
+
+            let inner_area = Rect {
+                x: area.x + 1,
+                y: area.y + 1,
+                width: area.width.saturating_sub(2),
+                height: area.height.saturating_sub(2),
+            };
+
+            let title = Line::from(vec![
+                Span::styled(
+                    " Profil kódování Grok ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" (Enter vybraná dávka, F2 všechny, Esc zrušit)"),
+            ]);
+
+            let title_block = Block::default().title(title).borders(Borders::NONE);
+
+            let current = app.jobs.get(app.selected).map(|j| j.profile.as_str());
+            let items: Vec<ListItem> = app
+                .profiles
+                .iter()
+                .enumerate()
+                .map(|(i, profile)| {
+                    let is_selected = Some(i) == app.profile_list_state.selected();
+                    let is_current = Some(profile.name.as_str()) == current;
+
+                    let mut spans = vec![];
+                    if is_selected {
+                        spans.push(Span::styled("▶ ", Style::default().fg(Color::Yellow)));
+                    } else {
+                        spans.push(Span::raw("  "));
+                    }
+
+                    let name_style = if is_current {
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD)
+                    } else if is_selected {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    spans.push(Span::styled(profile.name.clone(), name_style));
+                    if is_current {
+                        spans.push(Span::raw(" (aktuální)"));
+                    }
+                    if !profile.description.is_empty() {
+                        spans.push(Span::styled(
+                            format!(" – {}", profile.description),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .block(title_block)
+                .highlight_style(
+                    Style::default()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let mut state = app.profile_list_state.clone();
+            f.render_stateful_widget(list, inner_area, &mut state);
+        }
+        UiMode::OcrSettingsMenu => {
+            let area = centered_rect(70, 40, f.size());
+
+            let background_block = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Black));
+            f.render_widget(background_block, area);
+
+            let inner_area = Rect {
+                x: area.x + 1,
+                y: area.y + 1,
+                width: area.width.saturating_sub(2),
+                height: area.height.saturating_sub(2),
+            };
+
+            let job = app.jobs.get(app.selected);
+            let title = Line::from(vec![
+                Span::styled(
+                    " Nastavení OCR ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(
+                    "{} (Enter upravit, F2 všechny dávky, r výchozí, Esc zavřít)",
+                    job.map(|j| {
+                        if j.ocr_custom {
+                            format!("– {}, vlastní ", j.name)
+                        } else {
+                            format!("– {} ", j.name)
+                        }
+                    })
+                    .unwrap_or_default()
+                )),
+            ]);
+
+            let title_block = Block::default().title(title).borders(Borders::NONE);
+
+            let items: Vec<ListItem> = OcrField::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    let is_selected = Some(i) == app.ocr_list_state.selected();
+                    let value = job.map(|j| j.ocr.value(*field)).unwrap_or_default();
+
+                    let mut spans = vec![];
+                    if is_selected {
+                        spans.push(Span::styled("▶ ", Style::default().fg(Color::Yellow)));
+                    } else {
+                        spans.push(Span::raw("  "));
+                    }
+                    spans.push(Span::styled(
+                        format!("{:<18}", field.label()),
+                        if is_selected {
+                            Style::default().fg(Color::Yellow)
+                        } else {
+                            Style::default().fg(Color::Cyan)
+                        },
+                    ));
+                    if value.is_empty() {
+                        spans.push(Span::styled("výchozí", Style::default().fg(Color::DarkGray)));
+                    } else {
+                        spans.push(Span::styled(value, Style::default().fg(Color::White)));
+                    }
+
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .block(title_block)
+                .highlight_style(
+                    Style::default()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let mut state = app.ocr_list_state.clone();
             f.render_stateful_widget(list, inner_area, &mut state);
         }
+        UiMode::EditOcrField => {
+            let area = centered_rect(60, 20, f.size());
+
+            let background_block = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Black));
+            f.render_widget(background_block, area);
+
+            let inner_area = Rect {
+                x: area.x + 1,
+                y: area.y + 1,
+                width: area.width.saturating_sub(2),
+                height: area.height.saturating_sub(2),
+            };
+
+            let field = app.selected_ocr_field();
+            let edit_block = Block::default()
+                .title(Span::styled(
+                    format!(" {} ", field.label()),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .borders(Borders::NONE);
+
+            let edit_text = vec![
+                Line::from(format!("{} (prázdné = výchozí):", field.hint())),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("> "),
+                    Span::styled(
+                        &app.edit_buffer,
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled("_", Style::default().fg(Color::Yellow)),
+                ]),
+                Line::from(""),
+                Line::from("Enter: potvrdit, Esc: zrušit"),
+            ];
+
+            let edit_paragraph =
+                Paragraph::new(edit_text).block(edit_block).alignment(Alignment::Left);
+
+            f.render_widget(edit_paragraph, inner_area);
+        }
         UiMode::EditInput => {
             let area = centered_rect(60, 20, f.size());
 
@@ -2495,6 +4481,50 @@ fn render_overlay_ui(f: &mut Frame<'_>, app: &App) {
 
             f.render_widget(edit_paragraph, inner_area);
         }
+        UiMode::EditStartIndex => {
+            let area = centered_rect(60, 20, f.size());
+
+            let background_block = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Black));
+            f.render_widget(background_block, area);
+
+            let inner_area = Rect {
+                x: area.x + 1,
+                y: area.y + 1,
+                width: area.width.saturating_sub(2),
+                height: area.height.saturating_sub(2),
+            };
+
+            let edit_block = Block::default()
+                .title(Span::styled(
+                    " Počáteční index dávky ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .borders(Borders::NONE);
+
+            let edit_text = vec![
+                Line::from("Zadejte první index (prázdné = podle režimu):"),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("> "),
+                    Span::styled(
+                        &app.edit_buffer,
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled("_", Style::default().fg(Color::Yellow)),
+                ]),
+                Line::from(""),
+                Line::from("Enter: potvrdit, Esc: zrušit"),
+            ];
+
+            let edit_paragraph =
+                Paragraph::new(edit_text).block(edit_block).alignment(Alignment::Left);
+
+            f.render_widget(edit_paragraph, inner_area);
+        }
         UiMode::CustomLangInput => {
             let area = centered_rect(60, 20, f.size());
 
@@ -2572,6 +4602,7 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 }
 
 /// Zpracuje jednu dávku = adresář.
+#[allow(clippy::too_many_arguments)]
 fn process_batch(
     args: &Args,
     grok_path: &Path,
@@ -2583,89 +4614,414 @@ fn process_batch(
     do_user: bool,
     do_txt: bool,
     do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    lang: &str,
     alto_version: &str,
     tessdata_dir: Option<&Path>,
-    logs: &mut Vec<String>,
+    ocr: &OcrSettings,
+    profile: &EncodingProfile,
+    work_dir: &Path,
+    control: &Arc<JobControl>,
+    tracker: &StateTracker,
+    logs: &mut JobLog,
 ) -> Result<()> {
-    let tiffs = collect_tiffs_in_dir(batch_dir)?;
-    if tiffs.is_empty() {
-        logs.push("Dávka neobsahuje žádné TIFF soubory – přeskočeno.".to_string());
+    let pages = collect_tiffs_in_dir(batch_dir)?;
+    if pages.is_empty() {
+        logs.push("Dávka neobsahuje žádné vstupní soubory – přeskočeno.".to_string());
         return Ok(());
     }
 
-    if !(do_master || do_user || do_txt || do_alto) {
-        logs.push("Nic není zapnuto (AC/UC/TXT/ALTO) – dávka přeskočena.".to_string());
+    if !(do_master || do_user || do_txt || do_alto || do_hocr || do_pdf) {
+        logs.push("Nic není zapnuto (AC/UC/TXT/ALTO/hOCR/PDF) – dávka přeskočena.".to_string());
         return Ok(());
     }
 
-    let mut idx = index_start;
-    let mut first_error: Option<anyhow::Error> = None;
+    // Hash a DPI zdroje jednou na soubor, ne pro každou stránku vícestránkového TIFFu
+    let mut sources: BTreeMap<&Path, SourceInfo> = BTreeMap::new();
+    if do_alto && !args.dry_run {
+        for page in &pages {
+            let path = page.path.as_path();
+            if !sources.contains_key(path) {
+                sources.insert(path, SourceInfo::read(path)?);
+            }
+        }
+    }
 
-    for tif in tiffs {
-        let digits = args.digits;
-        let index_str = format!("{:0digits$}", idx);
-        logs.push(format!(
-            "--- Soubor {} (index {}) ---",
-            tif.display(),
-            index_str
-        ));
+    let limits = Limits::new(args.jobs, args.grok_jobs, args.tess_jobs, control.clone());
 
-        if do_master {
-            let out_jp2 = output_dir.join(format!("{index_str}.ac.jp2"));
-            if let Err(e) = run_grok_master(grok_path, &tif, &out_jp2, args.dry_run, logs)
-                .with_context(|| format!("Master JP2 selhalo pro `{}`", tif.display()))
-            {
-                logs.push(format!("Chyba Master: {e}"));
-                if first_error.is_none() {
-                    first_error = Some(e);
-                }
+    let results = run_pages(pages.len(), &limits, logs, |i| {
+        let idx = index_start + i as u32;
+        process_page(
+            args,
+            grok_path,
+            tess_path,
+            &pages[i],
+            sources.get(pages[i].path.as_path()),
+            idx,
+            output_dir,
+            do_master,
+            do_user,
+            do_txt,
+            do_alto,
+            do_hocr,
+            do_pdf,
+            lang,
+            alto_version,
+            tessdata_dir,
+            ocr,
+            profile,
+            work_dir,
+            &limits,
+            tracker,
+        )
+    });
+
+    // První chyba v pořadí stránek
+    match results.into_iter().find(|r| r.is_err()) {
+        Some(err) => err,
+        None => Ok(()),
+    }
+}
+
+/// Zpracuje jednu stránku. Kroky AC, UC a OCR na sobě nezávisí,
+/// takže běží souběžně (v rámci limitů); log stránky je vždy v pořadí AC, UC, OCR.
+/// Kroky, které podle stavu dávky už úspěšně proběhly a jejich výstupy
+/// existují, se přeskočí. Zdroj, který nástroje nečtou přímo (JP2), se nejdřív
+/// převede do dočasného TIFFu ve `work_dir`.
+#[allow(clippy::too_many_arguments)]
+fn process_page(
+    args: &Args,
+    grok_path: &Path,
+    tess_path: &Path,
+    page: &PageSource,
+    source_info: Option<&SourceInfo>,
+    idx: u32,
+    output_dir: &Path,
+    do_master: bool,
+    do_user: bool,
+    do_txt: bool,
+    do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    lang: &str,
+    alto_version: &str,
+    tessdata_dir: Option<&Path>,
+    ocr: &OcrSettings,
+    profile: &EncodingProfile,
+    work_dir: &Path,
+    limits: &Limits,
+    tracker: &StateTracker,
+) -> PageOutcome {
+    let digits = args.digits;
+    let index_str = format!("{:0digits$}", idx);
+    let source = page.path.as_path();
+
+    let mut lines = vec![match page.page {
+        Some(n) => format!("--- Soubor {} stránka {n} (index {index_str}) ---", source.display()),
+        None => format!("--- Soubor {} (index {index_str}) ---", source.display()),
+    }];
+
+    let ac_jp2 = output_dir.join(format!("{index_str}.ac.jp2"));
+    let uc_jp2 = output_dir.join(format!("{index_str}.uc.jp2"));
+    let mut ocr_outputs = Vec::new();
+    if do_txt {
+        ocr_outputs.push(output_dir.join(format!("{index_str}.ocr.txt")));
+    }
+    if do_alto {
+        ocr_outputs.push(output_dir.join(format!("{index_str}.ocr.xml")));
+    }
+    if do_hocr {
+        ocr_outputs.push(output_dir.join(format!("{index_str}.ocr.hocr")));
+    }
+    if do_pdf {
+        ocr_outputs.push(output_dir.join(format!("{index_str}.ocr.pdf")));
+    }
+
+    // Hotovo = úspěch v minulém běhu a všechny výstupy kroku jsou na disku
+    let mut should_run = |enabled: bool, step: Step, outputs: &[PathBuf]| -> bool {
+        if !enabled {
+            return false;
+        }
+        if tracker.is_done(&index_str, step) && outputs.iter().all(|p| p.exists()) {
+            lines.push(format!("{} přeskočeno (hotovo z předchozího běhu)", step.label()));
+            return false;
+        }
+        true
+    };
+    let run_master = should_run(do_master, Step::Master, std::slice::from_ref(&ac_jp2));
+    let run_user = should_run(do_user, Step::User, std::slice::from_ref(&uc_jp2));
+    let run_ocr = should_run(do_txt || do_alto || do_hocr || do_pdf, Step::Ocr, &ocr_outputs);
+
+    let prepared = if run_master || run_user || run_ocr {
+        let mut logs = JobLog::buffered();
+        let prepared = prepare_input(
+            page,
+            grok_path,
+            work_dir,
+            &index_str,
+            args.dry_run,
+            limits.control(),
+            &mut logs,
+        );
+        lines.extend(logs.into_lines());
+        match prepared {
+            Ok(prepared) => Some(prepared),
+            Err(e) => {
+                lines.push(format!("Chyba převodu vstupu: {e}"));
+                return PageOutcome { lines, result: Err(e) };
             }
         }
+    } else {
+        None
+    };
+    let tif = prepared.as_ref().map_or(source, |p| p.path.as_path());
+
+    let steps: Vec<(Step, JobLog, Result<()>)> = std::thread::scope(|s| {
+        let mut handles = Vec::new();
+
+        if run_master {
+            let out_jp2 = &ac_jp2;
+            handles.push((Step::Master, s.spawn(move || {
+                let mut logs = JobLog::buffered();
+                let res = limits
+                    .run(Tool::Grok, || {
+                        run_grok(grok_path, tif, out_jp2, &profile.master, "Master", args.dry_run, limits.control(), &mut logs)
+                    })
+                    .with_context(|| format!("Master JP2 selhalo pro `{}`", source.display()));
+                if let Err(ref e) = res {
+                    logs.push(format!("Chyba Master: {e}"));
+                    if !not_started(e) {
+                        remove_partial_outputs(std::slice::from_ref(out_jp2), &mut logs);
+                    }
+                }
+                (logs, res)
+            })));
+        }
 
-        if do_user {
-            let out_jp2 = output_dir.join(format!("{index_str}.uc.jp2"));
-            if let Err(e) = run_grok_user(grok_path, &tif, &out_jp2, args.dry_run, logs)
-                .with_context(|| format!("User JP2 selhalo pro `{}`", tif.display()))
-            {
-                logs.push(format!("Chyba User: {e}"));
-                if first_error.is_none() {
-                    first_error = Some(e);
+        if run_user {
+            let out_jp2 = &uc_jp2;
+            handles.push((Step::User, s.spawn(move || {
+                let mut logs = JobLog::buffered();
+                let res = limits
+                    .run(Tool::Grok, || {
+                        run_grok(grok_path, tif, out_jp2, &profile.user, "User", args.dry_run, limits.control(), &mut logs)
+                    })
+                    .with_context(|| format!("User JP2 selhalo pro `{}`", source.display()));
+                if let Err(ref e) = res {
+                    logs.push(format!("Chyba User: {e}"));
+                    if !not_started(e) {
+                        remove_partial_outputs(std::slice::from_ref(out_jp2), &mut logs);
+                    }
                 }
-            }
+                (logs, res)
+            })));
         }
 
-        // OCR - jednotné zpracování pro TXT i ALTO
-        if do_txt || do_alto {
-            if let Err(e) = run_tess_unified(
-                tess_path,
-                &args.lang,
-                alto_version,
-                args.dry_run,
-                &tif,
-                output_dir,
-                &index_str,
-                do_txt,
-                do_alto,
-                tessdata_dir,
-                logs,
-            )
-            .with_context(|| format!("Tesseract selhalo pro `{}`", tif.display()))
-            {
-                logs.push(format!("Chyba OCR: {e}"));
-                if first_error.is_none() {
-                    first_error = Some(e);
+        // OCR - jednotné zpracování pro TXT, ALTO, hOCR i PDF
+        if run_ocr {
+            let index_str = &index_str;
+            let ocr_outputs = &ocr_outputs;
+            handles.push((Step::Ocr, s.spawn(move || {
+                let mut logs = JobLog::buffered();
+                let res = limits
+                    .run(Tool::Tesseract, || {
+                        run_tess_unified(
+                            tess_path,
+                            lang,
+                            alto_version,
+                            args.dry_run,
+                            tif,
+                            output_dir,
+                            index_str,
+                            do_txt,
+                            do_alto,
+                            do_hocr,
+                            do_pdf,
+                            tessdata_dir,
+                            ocr,
+                            limits.control(),
+                            &mut logs,
+                        )
+                    })
+                    .with_context(|| format!("Tesseract selhalo pro `{}`", source.display()))
+                    .and_then(|()| {
+                        if do_alto && !args.dry_run && let Some(source_info) = source_info {
+                            let alto_path = output_dir.join(format!("{index_str}.ocr.xml"));
+                            postprocess_alto(&alto_path, source_info, tif, index_str, lang, alto_version, ocr, &mut logs)
+                        } else {
+                            Ok(())
+                        }
+                    });
+                if let Err(ref e) = res {
+                    logs.push(format!("Chyba OCR: {e}"));
+                    if !not_started(e) {
+                        remove_partial_outputs(ocr_outputs, &mut logs);
+                    }
                 }
+                (logs, res)
+            })));
+        }
+
+        handles
+            .into_iter()
+            .map(|(step, h)| {
+                let (logs, res) = h.join().unwrap_or_else(|_| {
+                    (JobLog::buffered(), Err(anyhow!("Krok stránky zpanikařil")))
+                });
+                (step, logs, res)
+            })
+            .collect()
+    });
+
+    // Krok, který se nespustil, nemění stav z minulého běhu
+    let results: Vec<(Step, &Result<()>)> = steps
+        .iter()
+        .filter(|(_, _, res)| !matches!(res, Err(e) if not_started(e)))
+        .map(|(step, _, res)| (*step, res))
+        .collect();
+    if !results.is_empty()
+        && let Err(e) = tracker.record_page(&index_str, &page.key(), &results)
+    {
+        lines.push(format!("Varování: {e}"));
+    }
+
+    let mut result = Ok(());
+    for (_, logs, res) in steps {
+        lines.extend(logs.into_lines());
+        if result.is_ok() {
+            result = res;
+        }
+    }
+
+    PageOutcome { lines, result }
+}
+
+/// Zdrojový soubor stránek pro úpravu ALTO
+struct SourceInfo {
+    name: String,
+    blake3: String,
+    /// DPI první stránky, pokud jde o TIFF
+    dpi: Option<(f64, f64)>,
+}
+
+impl SourceInfo {
+    fn read(path: &Path) -> Result<Self> {
+        Ok(Self {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            blake3: compute_digests(path, &[HashAlgorithm::Blake3])?
+                .remove(&HashAlgorithm::Blake3)
+                .unwrap_or_default(),
+            dpi: read_tiff_info(path).ok().and_then(|info| info.dpi),
+        })
+    }
+}
+
+/// Upraví ALTO stránky po Tesseractu (sourceImageInformation, Processing,
+/// ID, jednotky) a zkontroluje jeho strukturu (`alto::validate`)
+#[allow(clippy::too_many_arguments)]
+fn postprocess_alto(
+    alto_path: &Path,
+    source: &SourceInfo,
+    tif: &Path,
+    index_str: &str,
+    lang: &str,
+    alto_version: &str,
+    ocr: &OcrSettings,
+    logs: &mut JobLog,
+) -> Result<()> {
+    let version = AltoVersion::parse(alto_version)?;
+    // DPI z nastavení OCR, jinak z TIFFu, který četl Tesseract, nebo ze zdroje
+    let dpi = ocr
+        .dpi
+        .map(|d| (d as f64, d as f64))
+        .or_else(|| read_tiff_info(tif).ok()?.dpi)
+        .or(source.dpi);
+    let unit = ocr.alto_unit.unwrap_or_default();
+    let pp = PostProcess {
+        version,
+        index: index_str,
+        source_name: source.name.clone(),
+        source_blake3: source.blake3.clone(),
+        unit,
+        dpi,
+        ocr_settings: format!("lang={lang}; {}", ocr.describe()),
+    };
+    alto::postprocess_file(alto_path, &pp)?;
+    logs.push(format!(
+        "✓ ALTO upraveno (ALTO {version}, jednotky {}, kontrola struktury OK)",
+        unit.name()
+    ));
+    Ok(())
+}
+
+/// Smaže výstupy kroku, jehož proces selhal nebo byl přerušen, aby je
+/// `check_batch_already_done` nepočítal jako hotové. Krok, který se vůbec
+/// nespustil (`NotStarted`), nechává výstupy z dřívějšího běhu na místě.
+fn remove_partial_outputs(paths: &[PathBuf], logs: &mut JobLog) {
+    for path in paths {
+        if path.exists() {
+            match fs::remove_file(path) {
+                Ok(()) => logs.push(format!("Odstraněn neúplný výstup: {}", path.display())),
+                Err(e) => logs.push(format!(
+                    "Nelze odstranit neúplný výstup {}: {e}",
+                    path.display()
+                )),
             }
         }
+    }
+}
 
-        idx += 1;
+/// Spustí externí příkaz a počká na něj. Při zrušení dávky proces ukončí
+/// (kill) a vrátí chybu. stdout/stderr se čtou v samostatných vláknech,
+/// aby se proces nezablokoval na plné rouře. Pokud se proces vůbec
+/// nespustí (zrušeno předem, chyba spuštění), vrací chybu `NotStarted`.
+fn run_command(cmd: &mut Command, what: &str, control: &JobControl) -> Result<Output> {
+    fn read_pipe<R: io::Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut p) = pipe {
+                let _ = p.read_to_end(&mut buf);
+            }
+            buf
+        })
     }
 
-    if let Some(err) = first_error {
-        Err(err)
-    } else {
-        Ok(())
+    if control.is_cancelled() {
+        return Err(NotStarted(format!("{what} nespuštěn – zpracování zrušeno uživatelem")).into());
     }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| NotStarted(format!("Nelze spustit {what}: {e}")))?;
+
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if control.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("{what} ukončen – zpracování zrušeno uživatelem"));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
 }
 
 fn command_to_string(label: &str, cmd: &Command) -> String {
@@ -2687,43 +5043,43 @@ fn resolve_grok_path(grok_bin: &str) -> PathBuf {
     }
 
     // 1. Lokální složka grok v kořenovém adresáři programu
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            let grok_dir = exe_dir.join("grok");
-            let grok_bin_dir = grok_dir.join("bin");
+    if let Ok(exe_path) = std::env::current_exe()
+        && let Some(exe_dir) = exe_path.parent()
+    {
+        let grok_dir = exe_dir.join("grok");
+        let grok_bin_dir = grok_dir.join("bin");
 
-            if cfg!(windows) {
-                // Zkusíme ./grok/bin/grk_compress.exe
-                let candidate1 = grok_bin_dir.join("grk_compress.exe");
-                if candidate1.exists() {
-                    return candidate1;
-                }
-                // Zkusíme ./grok/grk_compress.exe
-                let candidate2 = grok_dir.join("grk_compress.exe");
-                if candidate2.exists() {
-                    return candidate2;
-                }
-                // Linux/Unix varianta v bin složce
-                let candidate3 = grok_bin_dir.join("grk_compress");
-                if candidate3.exists() {
-                    return candidate3;
-                }
-                // Linux/Unix varianta přímo v grok složce
-                let candidate4 = grok_dir.join("grk_compress");
-                if candidate4.exists() {
-                    return candidate4;
-                }
-            } else {
-                // Linux/Unix: ./grok/bin/grk_compress
-                let candidate1 = grok_bin_dir.join("grk_compress");
-                if candidate1.exists() {
-                    return candidate1;
-                }
-                // Linux/Unix: ./grok/grk_compress
-                let candidate2 = grok_dir.join("grk_compress");
-                if candidate2.exists() {
-                    return candidate2;
-                }
+        if cfg!(windows) {
+            // Zkusíme ./grok/bin/grk_compress.exe
+            let candidate1 = grok_bin_dir.join("grk_compress.exe");
+            if candidate1.exists() {
+                return candidate1;
+            }
+            // Zkusíme ./grok/grk_compress.exe
+            let candidate2 = grok_dir.join("grk_compress.exe");
+            if candidate2.exists() {
+                return candidate2;
+            }
+            // Linux/Unix varianta v bin složce
+            let candidate3 = grok_bin_dir.join("grk_compress");
+            if candidate3.exists() {
+                return candidate3;
+            }
+            // Linux/Unix varianta přímo v grok složce
+            let candidate4 = grok_dir.join("grk_compress");
+            if candidate4.exists() {
+                return candidate4;
+            }
+        } else {
+            // Linux/Unix: ./grok/bin/grk_compress
+            let candidate1 = grok_bin_dir.join("grk_compress");
+            if candidate1.exists() {
+                return candidate1;
+            }
+            // Linux/Unix: ./grok/grk_compress
+            let candidate2 = grok_dir.join("grk_compress");
+            if candidate2.exists() {
+                return candidate2;
             }
         }
     }
@@ -2746,33 +5102,33 @@ fn resolve_tess_path_with_priority(tess_bin: &str, force_local_tess: bool) -> (P
     }
 
     // 1. Lokální složka tesseract v kořenovém adresáři programu - PRVNÍ PRIORITA
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            let tess_dir = exe_dir.join("tesseract");
-            let tess_bin_dir = tess_dir.join("bin");
+    if let Ok(exe_path) = std::env::current_exe()
+        && let Some(exe_dir) = exe_path.parent()
+    {
+        let tess_dir = exe_dir.join("tesseract");
+        let tess_bin_dir = tess_dir.join("bin");
 
-            if cfg!(windows) {
-                // Zkusíme ./tesseract/bin/tesseract.exe
-                let candidate1 = tess_bin_dir.join("tesseract.exe");
-                if candidate1.exists() {
-                    return (candidate1, "lokální-složka/bin".to_string());
-                }
-                // Zkusíme ./tesseract/tesseract.exe
-                let candidate2 = tess_dir.join("tesseract.exe");
-                if candidate2.exists() {
-                    return (candidate2, "lokální-složka".to_string());
-                }
-            } else {
-                // Linux/Unix: ./tesseract/bin/tesseract
-                let candidate1 = tess_bin_dir.join("tesseract");
-                if candidate1.exists() {
-                    return (candidate1, "lokální-složka/bin".to_string());
-                }
-                // Linux/Unix: ./tesseract/tesseract
-                let candidate2 = tess_dir.join("tesseract");
-                if candidate2.exists() {
-                    return (candidate2, "lokální-složka".to_string());
-                }
+        if cfg!(windows) {
+            // Zkusíme ./tesseract/bin/tesseract.exe
+            let candidate1 = tess_bin_dir.join("tesseract.exe");
+            if candidate1.exists() {
+                return (candidate1, "lokální-složka/bin".to_string());
+            }
+            // Zkusíme ./tesseract/tesseract.exe
+            let candidate2 = tess_dir.join("tesseract.exe");
+            if candidate2.exists() {
+                return (candidate2, "lokální-složka".to_string());
+            }
+        } else {
+            // Linux/Unix: ./tesseract/bin/tesseract
+            let candidate1 = tess_bin_dir.join("tesseract");
+            if candidate1.exists() {
+                return (candidate1, "lokální-složka/bin".to_string());
+            }
+            // Linux/Unix: ./tesseract/tesseract
+            let candidate2 = tess_dir.join("tesseract");
+            if candidate2.exists() {
+                return (candidate2, "lokální-složka".to_string());
             }
         }
     }
@@ -2856,9 +5212,9 @@ fn find_tessdata_parent_dir(tesseract_path: &Path) -> Option<PathBuf> {
     None
 }
 
-fn check_grok(path: &Path, dry_run: bool) -> (ToolStatus, String) {
+fn check_grok(path: &Path, dry_run: bool) -> ToolStatus {
     if dry_run {
-        return (ToolStatus::Ok("(dry-run)".to_string()), "?".to_string());
+        return ToolStatus::Ok("(dry-run)".to_string());
     }
 
     let mut cmd = Command::new(path);
@@ -2866,18 +5222,15 @@ fn check_grok(path: &Path, dry_run: bool) -> (ToolStatus, String) {
     match cmd.output() {
         Ok(output) => {
             if output.status.success() {
-                (ToolStatus::Ok("OK".to_string()), "?".to_string())
+                ToolStatus::Ok("OK".to_string())
             } else {
                 let code = output.status.code().unwrap_or(-1);
                 let stderr = String::from_utf8_lossy(&output.stderr);
                 let first = stderr.lines().next().unwrap_or("");
-                (
-                    ToolStatus::Error(format!("exit {code}: {first}")),
-                    "?".to_string(),
-                )
+                ToolStatus::Error(format!("exit {code}: {first}"))
             }
         }
-        Err(e) => (ToolStatus::Error(format!("nelze spustit: {e}")), "?".to_string()),
+        Err(e) => ToolStatus::Error(format!("nelze spustit: {e}")),
     }
 }
 
@@ -2905,91 +5258,26 @@ fn check_tesseract(path: &Path, dry_run: bool) -> ToolStatus {
     }
 }
 
-fn run_grok_master(
+/// Spustí grk_compress pro jednu variantu profilu (`label` = "Master" / "User")
+#[allow(clippy::too_many_arguments)]
+fn run_grok(
     grok_path: &Path,
     input: &Path,
     output_jp2: &Path,
+    params: &GrokParams,
+    label: &str,
     dry_run: bool,
-    logs: &mut Vec<String>,
+    control: &JobControl,
+    logs: &mut JobLog,
 ) -> Result<()> {
     let mut cmd = Command::new(grok_path);
     cmd.arg("-i")
         .arg(input)
         .arg("-o")
         .arg(output_jp2)
-        .arg("-t")
-        .arg("4096,4096")
-        .arg("-p")
-        .arg("RPCL")
-        .arg("-n")
-        .arg("6")
-        .arg("-c")
-        .arg("[256,256],[256,256],[128,128],[128,128],[128,128],[128,128]")
-        .arg("-b")
-        .arg("64,64")
-        .arg("-X")
-        .arg("-M")
-        .arg("1")
-        .arg("-S")
-        .arg("-E")
-        .arg("-u")
-        .arg("R");
-
-    let cmd_str = command_to_string("Grok Master", &cmd);
-    logs.push(cmd_str);
-
-    if dry_run {
-        logs.push("(dry-run)".to_string());
-        return Ok(());
-    }
-
-    let output = cmd
-        .output()
-        .context("Nelze spustit grk_compress (Master)")?;
-    if output.status.success() {
-        logs.push("OK".to_string());
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        logs.push(format!("FAILED: {stderr}"));
-        Err(anyhow!("grk_compress Master selhalo"))
-    }
-}
+        .args(params.to_args());
 
-fn run_grok_user(
-    grok_path: &Path,
-    input: &Path,
-    output_jp2: &Path,
-    dry_run: bool,
-    logs: &mut Vec<String>,
-) -> Result<()> {
-    let mut cmd = Command::new(grok_path);
-    cmd.arg("-i")
-        .arg(input)
-        .arg("-o")
-        .arg(output_jp2)
-        .arg("-r")
-        .arg("362,256,181,128,90,64,45,32,22,16,11,8")
-        .arg("-I")
-        .arg("-t")
-        .arg("1024,1024")
-        .arg("-p")
-        .arg("RPCL")
-        .arg("-n")
-        .arg("6")
-        .arg("-c")
-        .arg("[256,256],[256,256],[128,128],[128,128],[128,128],[128,128]")
-        .arg("-b")
-        .arg("64,64")
-        .arg("-X")
-        .arg("-M")
-        .arg("1")
-        .arg("-u")
-        .arg("R")
-        .arg("-H")
-        .arg("4");
-
-    let cmd_str = command_to_string("Grok User", &cmd);
+    let cmd_str = command_to_string(&format!("Grok {label}"), &cmd);
     logs.push(cmd_str);
 
     if dry_run {
@@ -2997,20 +5285,19 @@ fn run_grok_user(
         return Ok(());
     }
 
-    let output = cmd
-        .output()
-        .context("Nelze spustit grk_compress (User)")?;
+    let output = run_command(&mut cmd, &format!("grk_compress ({label})"), control)?;
     if output.status.success() {
         logs.push("OK".to_string());
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         logs.push(format!("FAILED: {stderr}"));
-        Err(anyhow!("grk_compress User selhalo"))
+        Err(anyhow!("grk_compress {label} selhalo"))
     }
 }
 
-/// Zjednodušená verze - spustí Tesseract pro TXT i ALTO
+/// Zjednodušená verze - spustí Tesseract pro TXT, ALTO, hOCR i PDF najednou
+#[allow(clippy::too_many_arguments)]
 fn run_tess_unified(
     tess_path: &Path,
     lang: &str,
@@ -3021,8 +5308,12 @@ fn run_tess_unified(
     index_str: &str,
     do_txt: bool,
     do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
     tessdata_dir: Option<&Path>,
-    logs: &mut Vec<String>,
+    ocr: &OcrSettings,
+    control: &JobControl,
+    logs: &mut JobLog,
 ) -> Result<()> {
     // Základní informace
     logs.push(format!("Tesseract: {}", tess_path.display()));
@@ -3064,16 +5355,11 @@ fn run_tess_unified(
         }
     }
     
-    // 2. Mapujeme ALTO verzi
-    let v = if alto_version.starts_with('4') { "4" } 
-            else if alto_version.starts_with('3') { "3" } 
-            else if alto_version.starts_with('2') { "2" } 
-            else { "4" };
-
-    // 3. Sestavíme příkaz
+    // 2. Sestavíme příkaz (volby musí být před názvy výstupních konfigurací)
     cmd.arg(input)
         .arg(&out_base)
         .arg("-l").arg(lang);
+    ocr.apply(&mut cmd);
     
     // Přidáme výstupní formáty
     let mut output_formats = Vec::new();
@@ -3083,6 +5369,12 @@ fn run_tess_unified(
     if do_txt {
         output_formats.push("txt");
     }
+    if do_hocr {
+        output_formats.push("hocr");
+    }
+    if do_pdf {
+        output_formats.push("pdf");
+    }
     
     if output_formats.is_empty() {
         logs.push("Žádné výstupní formáty nenastaveny - přeskočeno".to_string());
@@ -3093,10 +5385,9 @@ fn run_tess_unified(
         cmd.arg(format);
     }
     
-    // Přidáme konfiguraci pro ALTO
+    // Přidáme konfiguraci pro ALTO; na přesnou verzi ho převede postprocess_alto
     if do_alto {
-        cmd.arg("-c").arg("tessedit_create_alto=1")
-            .arg("-c").arg(format!("alto_version={v}"));
+        cmd.arg("-c").arg("tessedit_create_alto=1");
     }
 
     let cmd_str = command_to_string("Tesseract", &cmd);
@@ -3108,7 +5399,7 @@ fn run_tess_unified(
     }
 
     logs.push("Spouštím Tesseract...".to_string());
-    let output = cmd.output().context("Nelze spustit tesseract")?;
+    let output = run_command(&mut cmd, "tesseract", control)?;
     
     if output.status.success() {
         logs.push("✓ Tesseract OK".to_string());
@@ -3131,6 +5422,24 @@ fn run_tess_unified(
                 logs.push("✗ TXT soubor nebyl vytvořen".to_string());
             }
         }
+
+        if do_hocr {
+            let hocr_file = out_dir.join(format!("{index_str}.ocr.hocr"));
+            if hocr_file.exists() {
+                logs.push(format!("✓ hOCR vytvořen: {}", hocr_file.display()));
+            } else {
+                logs.push("✗ hOCR soubor nebyl vytvořen".to_string());
+            }
+        }
+
+        if do_pdf {
+            let pdf_file = out_dir.join(format!("{index_str}.ocr.pdf"));
+            if pdf_file.exists() {
+                logs.push(format!("✓ PDF vytvořeno: {}", pdf_file.display()));
+            } else {
+                logs.push("✗ PDF soubor nebyl vytvořen".to_string());
+            }
+        }
         
         Ok(())
     } else {
@@ -3161,10 +5470,8 @@ fn write_manifest_and_log_with_process_logs(
     // Vytvoříme log_dir pokud neexistuje
     fs::create_dir_all(logs_dir)?;
     
-    // Manifest
-    let manifest_path = logs_dir.join("manifest.json");
-    let manifest_json = serde_json::to_string_pretty(&manifest)?;
-    fs::write(&manifest_path, manifest_json)?;
+    // Manifest a checksums.txt – čte je příkaz verify
+    manifest::write_manifest_and_checksums(manifest, logs_dir)?;
     
     // Log.txt
     let log_path = logs_dir.join("log.txt");
@@ -3179,13 +5486,19 @@ fn write_manifest_and_log_with_process_logs(
     log_content.push_str(&format!("Generated: {}\n", manifest.generated));
     log_content.push_str(&format!("Language: {}\n", manifest.lang));
     log_content.push_str(&format!("ALTO version: {}\n", manifest.alto_version));
-    log_content.push_str(&format!("Formats: AC={}, UC={}, TXT={}, ALTO={}\n", 
+    log_content.push_str(&format!("OCR settings: {}\n", manifest.ocr_settings.describe()));
+    log_content.push_str(&format!("Formats: AC={}, UC={}, TXT={}, ALTO={}, hOCR={}, PDF={}\n", 
         manifest.pages.iter().any(|p| p.ac_jp2.is_some()),
         manifest.pages.iter().any(|p| p.uc_jp2.is_some()),
         manifest.pages.iter().any(|p| p.txt.is_some()),
-        manifest.pages.iter().any(|p| p.alto.is_some())));
+        manifest.pages.iter().any(|p| p.alto.is_some()),
+        manifest.pages.iter().any(|p| p.hocr.is_some()),
+        manifest.pages.iter().any(|p| p.pdf.is_some())));
+    if let Some(ref merged) = manifest.merged_pdf {
+        log_content.push_str(&format!("Merged PDF: {} = {}\n", merged.path, merged.blake3));
+    }
     
-    log_content.push_str("\n");
+    log_content.push('\n');
     log_content.push_str(&"=".repeat(80));
     log_content.push_str("\nPROCESS EXECUTION LOG\n");
     log_content.push_str(&"=".repeat(80));
@@ -3194,10 +5507,10 @@ fn write_manifest_and_log_with_process_logs(
     // Přidáme všechny logy z procesu
     for log_line in process_logs {
         log_content.push_str(log_line);
-        log_content.push_str("\n");
+        log_content.push('\n');
     }
     
-    log_content.push_str("\n");
+    log_content.push('\n');
     log_content.push_str(&"=".repeat(80));
     log_content.push_str("\nFILE CHECKSUMS\n");
     log_content.push_str(&"=".repeat(80));
@@ -3206,8 +5519,8 @@ fn write_manifest_and_log_with_process_logs(
     // Přidáme checksumy
     for page in &manifest.pages {
         log_content.push_str(&format!("[Page {}]\n", page.index));
-        log_content.push_str(&format!("  Original TIFF: {}\n", 
-            page.original_tiff.path));
+        log_content.push_str(&format!("  Original {}: {}\n",
+            page.original_format.name(), page.original_tiff.path));
         
         if let Some(ref ac_jp2) = page.ac_jp2 {
             log_content.push_str(&format!("  AC JP2: {} = {}\n",
@@ -3228,7 +5541,17 @@ fn write_manifest_and_log_with_process_logs(
             log_content.push_str(&format!("  ALTO: {} = {}\n",
                 alto.path, alto.blake3));
         }
-        log_content.push_str("\n");
+
+        if let Some(ref hocr) = page.hocr {
+            log_content.push_str(&format!("  hOCR: {} = {}\n",
+                hocr.path, hocr.blake3));
+        }
+
+        if let Some(ref pdf) = page.pdf {
+            log_content.push_str(&format!("  PDF: {} = {}\n",
+                pdf.path, pdf.blake3));
+        }
+        log_content.push('\n');
     }
     
     fs::write(&log_path, log_content)?;
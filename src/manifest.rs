@@ -1,15 +1,26 @@
 // src/manifest.rs
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
 use chrono::Local;
 use serde::Serialize;
 
-use crate::blake3::compute_blake3;
-use crate::collect_tiffs_in_dir;
+use crate::hashing::{compute_digests, with_blake3, HashAlgorithm};
+use crate::collect_page_order;
+use crate::inputs::InputFormat;
+use crate::jp2check::{check_jp2, Jp2Check};
+use crate::ocr::OcrSettings;
+use crate::ordering::PageOrder;
+use crate::profiles::EncodingProfile;
+use crate::quality::{
+    read_word_confidences, BatchConfidence, BatchDictionary, Dictionary, DictionaryHitRate,
+    PageConfidence,
+};
+use crate::worker::JobLog;
 
 /// Informace o jednom souboru
 #[derive(Debug, Serialize, Clone)]
@@ -17,17 +28,50 @@ pub struct FileInfo {
     pub path: String,   // plná filesystem cesta
     pub size: u64,
     pub blake3: String,
+    /// Všechny spočítané hashe (zvolené algoritmy + BLAKE3)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub digests: BTreeMap<HashAlgorithm, String>,
 }
 
 /// Informace o jedné stránce / indexu
 #[derive(Debug, Serialize, Clone)]
 pub struct PageEntry {
     pub index: String,           // např. "0001"
+    /// Zdrojový soubor stránky (TIFF, JPEG, PNG nebo JP2) a jeho hashe
     pub original_tiff: FileInfo, // ZMĚNA: z 'tiff' na 'original_tiff'
+    pub original_format: InputFormat,
+    /// Stránka vícestránkového TIFFu (od 1); `original_tiff` je pak celý soubor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_page: Option<usize>,
     pub ac_jp2: Option<FileInfo>,
     pub uc_jp2: Option<FileInfo>,
     pub txt: Option<FileInfo>,
     pub alto: Option<FileInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hocr: Option<FileInfo>,
+    /// PDF stránky s textovou vrstvou z Tesseractu
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf: Option<FileInfo>,
+    /// Kontrola AC/UC JP2 proti profilu kódování
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ac_jp2_check: Option<Jp2Check>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uc_jp2_check: Option<Jp2Check>,
+    /// Konfidence slov z ALTO (WC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ocr_confidence: Option<PageConfidence>,
+    /// Podíl slov z TXT nalezených ve slovníku jazyka OCR
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<DictionaryHitRate>,
+}
+
+impl PageEntry {
+    /// Existující výstupy stránky (AC, UC, TXT, ALTO, hOCR, PDF)
+    fn outputs(&self) -> impl Iterator<Item = &FileInfo> {
+        [&self.ac_jp2, &self.uc_jp2, &self.txt, &self.alto, &self.hocr, &self.pdf]
+            .into_iter()
+            .flatten()
+    }
 }
 
 /// Manifest celé dávky - PŘIDÁNÁ NOVÁ POLE podle main.rs
@@ -43,11 +87,27 @@ pub struct BatchManifest {
     pub generated: String,   // PŘIDÁNO: timestamp generování
     pub lang: String,
     pub alto_version: String,
+    /// Parametry Tesseractu (PSM, OEM, DPI, …) pro reprodukovatelnost OCR
+    pub ocr_settings: OcrSettings,
+    pub encoding_profile: EncodingProfile, // profil kódování Grok použitý pro JP2
+    pub checksum_algorithms: Vec<HashAlgorithm>,
+    /// Odkud pochází pořadí stránek (order.txt / přirozené řazení)
+    pub page_order: PageOrder,
     pub pages: Vec<PageEntry>,
+    /// PDF celé dávky spojené ze stránek
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merged_pdf: Option<FileInfo>,
+    /// Konfidence slov za celou dávku a stránky k ruční kontrole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ocr_confidence: Option<BatchConfidence>,
+    /// Shoda se slovníkem za celou dávku
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<BatchDictionary>,
 }
 
 /// Postaví manifest pro jednu dávku a vrátí ho.
 /// Logs adresář se vytvoří, ale manifest/checksums se zatím nezapisují.
+#[allow(clippy::too_many_arguments)]
 pub fn build_manifest_for_batch(
     batch_name: &str,
     input_dir: &Path,
@@ -59,81 +119,136 @@ pub fn build_manifest_for_batch(
     do_user: bool,
     do_txt: bool,
     do_alto: bool,
+    do_hocr: bool,
+    do_pdf: bool,
+    merged_pdf: Option<&Path>,
     lang: &str,
     alto_version: &str,
+    ocr_settings: &OcrSettings,
+    encoding_profile: &EncodingProfile,
+    checksums: &[HashAlgorithm],
+    review_threshold: f64,
+    dictionary: Option<&Dictionary>,
+    logs: &mut JobLog,
 ) -> Result<BatchManifest> {
+    let algorithms = with_blake3(checksums);
+
     fs::create_dir_all(logs_dir)
         .with_context(|| format!("Nelze vytvořit logs adresář `{}`", logs_dir.display()))?;
 
-    // TIFFy ve stejné logice jako v process_batch
-    let tiffs = collect_tiffs_in_dir(input_dir)
-        .with_context(|| format!("Nelze znovu načíst TIFFy z `{}`", input_dir.display()))?;
+    // Vstupní soubory ve stejné logice jako v process_batch
+    let mut page_order = collect_page_order(input_dir)
+        .with_context(|| format!("Nelze znovu načíst vstupní soubory z `{}`", input_dir.display()))?;
+    let sources = std::mem::take(&mut page_order.pages);
 
     let mut pages = Vec::new();
-    let mut idx = index_start;
+    // Vícestránkový TIFF se hashuje jen jednou
+    let mut source_infos: BTreeMap<PathBuf, FileInfo> = BTreeMap::new();
+    // Konfidence všech slov dávky pro souhrn
+    let mut all_confidences = Vec::new();
+    let mut review_pages = Vec::new();
 
-    for tif in tiffs {
+    for (i, source) in sources.into_iter().enumerate() {
+        let idx = index_start + i as u32;
         let index_str = format!("{idx:0digits$}");
+        let tif = source.path;
 
-        let tiff_info = file_info(&tif)
-            .with_context(|| format!("BLAKE3 pro `{}` selhal", tif.display()))?;
-
-        let ac_jp2 = if do_master {
-            let p = output_dir.join(format!("{index_str}.ac.jp2"));  // ZMĚNA: xxxx.ac.jp2
-            if p.exists() {
-                Some(file_info(&p).with_context(|| format!("BLAKE3 pro `{}` selhal", p.display()))?)
-            } else {
-                None
+        let tiff_info = match source_infos.get(&tif) {
+            Some(info) => info.clone(),
+            None => {
+                let info = file_info(&tif, &algorithms)
+                    .with_context(|| format!("Hash pro `{}` selhal", tif.display()))?;
+                source_infos.insert(tif.clone(), info.clone());
+                info
             }
-        } else {
-            None
         };
+        let original_format = InputFormat::from_path(&tif).unwrap_or(InputFormat::Tiff);
 
-        let uc_jp2 = if do_user {
-            let p = output_dir.join(format!("{index_str}.uc.jp2"));  // ZMĚNA: xxxx.uc.jp2
-            if p.exists() {
-                Some(file_info(&p).with_context(|| format!("BLAKE3 pro `{}` selhal", p.display()))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        // Výstupy stránky (přípona, zda se vytvářejí); chybějící soubor = None
+        let [ac_jp2, uc_jp2, txt, alto, hocr, pdf] = [
+            ("ac.jp2", do_master),
+            ("uc.jp2", do_user),
+            ("ocr.txt", do_txt),
+            ("ocr.xml", do_alto),
+            ("ocr.hocr", do_hocr),
+            ("ocr.pdf", do_pdf),
+        ]
+        .map(|(suffix, enabled)| {
+            let p = output_dir.join(format!("{index_str}.{suffix}"));
+            (enabled && p.exists())
+                .then(|| file_info(&p, &algorithms).with_context(|| format!("Hash pro `{}` selhal", p.display())))
+                .transpose()
+        });
+        let (ac_jp2, uc_jp2, txt, alto, hocr, pdf) = (ac_jp2?, uc_jp2?, txt?, alto?, hocr?, pdf?);
 
-        let txt = if do_txt {
-            let p = output_dir.join(format!("{index_str}.ocr.txt"));  // ZMĚNA: xxxx.ocr.txt
-            if p.exists() {
-                Some(file_info(&p).with_context(|| format!("BLAKE3 pro `{}` selhal", p.display()))?)
-            } else {
-                None
+        let ac_jp2_check = ac_jp2
+            .as_ref()
+            .map(|f| check_jp2(Path::new(&f.path), &encoding_profile.master));
+        let uc_jp2_check = uc_jp2
+            .as_ref()
+            .map(|f| check_jp2(Path::new(&f.path), &encoding_profile.user));
+
+        // Nečitelné ALTO jedné stránky nezastaví celý manifest
+        let confidence_values = alto.as_ref().and_then(|f| {
+            read_word_confidences(Path::new(&f.path))
+                .map_err(|e| logs.push(format!("Konfidence OCR z `{}` selhala: {e:#}", f.path)))
+                .ok()
+        });
+        let ocr_confidence = match confidence_values {
+            Some(values) => {
+                let confidence = PageConfidence::from_values(&values, review_threshold);
+                if confidence.needs_review {
+                    review_pages.push(index_str.clone());
+                }
+                all_confidences.extend(values);
+                Some(confidence)
             }
-        } else {
-            None
+            None => None,
         };
 
-        let alto = if do_alto {
-            let p = output_dir.join(format!("{index_str}.ocr.xml"));  // ZMĚNA: xxxx.ocr.xml
-            if p.exists() {
-                Some(file_info(&p).with_context(|| format!("BLAKE3 pro `{}` selhal", p.display()))?)
-            } else {
-                None
+        let dictionary_hits = match (&txt, dictionary) {
+            (Some(f), Some(d)) => {
+                let text = fs::read_to_string(&f.path)
+                    .with_context(|| format!("Nelze načíst `{}`", f.path))?;
+                Some(d.hit_rate(&text))
             }
-        } else {
-            None
+            _ => None,
         };
 
         pages.push(PageEntry {
             index: index_str,
             original_tiff: tiff_info, // ZMĚNA: z 'tiff' na 'original_tiff'
+            original_format,
+            source_page: source.page,
             ac_jp2,
             uc_jp2,
             txt,
             alto,
+            hocr,
+            pdf,
+            ac_jp2_check,
+            uc_jp2_check,
+            ocr_confidence,
+            dictionary: dictionary_hits,
         });
-
-        idx += 1;
     }
 
+    let merged_pdf = match merged_pdf {
+        Some(p) if p.exists() => {
+            Some(file_info(p, &algorithms).with_context(|| format!("Hash pro `{}` selhal", p.display()))?)
+        }
+        _ => None,
+    };
+
+    let ocr_confidence = do_alto.then(|| {
+        BatchConfidence::from_values(&all_confidences, review_threshold, review_pages)
+    });
+
+    let dictionary = dictionary.filter(|_| do_txt).map(|d| {
+        let hits: Vec<_> = pages.iter().filter_map(|p| p.dictionary.as_ref()).collect();
+        d.summarize(&hits)
+    });
+
     let manifest = BatchManifest {
         batch_name: batch_name.to_string(),
         start_index: index_start, // PŘIDÁNO
@@ -145,21 +260,29 @@ pub fn build_manifest_for_batch(
         generated: Local::now().to_rfc3339(), // PŘIDÁNO
         lang: lang.to_string(),
         alto_version: alto_version.to_string(),
+        ocr_settings: ocr_settings.clone(),
+        encoding_profile: encoding_profile.clone(),
+        checksum_algorithms: algorithms,
+        page_order,
         pages,
+        merged_pdf,
+        ocr_confidence,
+        dictionary,
     };
 
     Ok(manifest)
 }
 
-/// Spočítá FileInfo pro daný soubor
-fn file_info(path: &Path) -> Result<FileInfo> {
+/// Spočítá FileInfo pro daný soubor (všechny hashe jedním čtením)
+fn file_info(path: &Path, algorithms: &[HashAlgorithm]) -> Result<FileInfo> {
     let meta = fs::metadata(path)?;
     let size = meta.len();
-    let hash = compute_blake3(path)?;
+    let digests = compute_digests(path, algorithms)?;
     Ok(FileInfo {
         path: path.to_string_lossy().to_string(),
         size,
-        blake3: hash,
+        blake3: digests[&HashAlgorithm::Blake3].clone(),
+        digests,
     })
 }
 
@@ -172,28 +295,118 @@ pub fn write_manifest_and_checksums(
     let manifest_json = serde_json::to_string_pretty(manifest)?;
     fs::write(logs_dir.join("manifest.json"), manifest_json)?;
 
-    // checksums.txt (jednoduchá verze, bez logu)
-    let mut checksums = String::new();
-    let mut add = |fi: &FileInfo| {
-        checksums.push_str(&format!("{}  {}\n", fi.blake3, fi.path));
-    };
+    write_checksums(manifest, logs_dir)
+}
 
-    for page in &manifest.pages {
-        add(&page.original_tiff); // ZMĚNA: z 'tiff' na 'original_tiff'
-        if let Some(ref f) = page.ac_jp2 {
-            add(f);
+/// Zapíše checksums.txt (BLAKE3) a checksums-<algoritmus>.txt pro další
+/// zvolené algoritmy ("<hash>  <cesta>" na řádek) do logs_dir
+pub fn write_checksums(manifest: &BatchManifest, logs_dir: &Path) -> Result<()> {
+    for &algorithm in &manifest.checksum_algorithms {
+        let mut checksums = String::new();
+        // Zdroj sdílený stránkami vícestránkového TIFFu jen jednou
+        let mut seen = BTreeSet::new();
+        let mut add = |fi: &FileInfo| {
+            if !seen.insert(fi.path.clone()) {
+                return;
+            }
+            if let Some(hash) = fi.digests.get(&algorithm) {
+                checksums.push_str(&format!("{}  {}\n", hash, fi.path));
+            }
+        };
+
+        for page in &manifest.pages {
+            add(&page.original_tiff); // ZMĚNA: z 'tiff' na 'original_tiff'
+            page.outputs().for_each(&mut add);
         }
-        if let Some(ref f) = page.uc_jp2 {
+        if let Some(ref f) = manifest.merged_pdf {
             add(f);
         }
-        if let Some(ref f) = page.txt {
-            add(f);
+
+        fs::write(logs_dir.join(algorithm.checksum_file_name()), checksums)?;
+    }
+    Ok(())
+}
+
+/// Po úpravě výstupů (např. převod ALTO) přepočítá jejich záznamy
+/// v manifest.json a řádky v checksums souborech. `changed` je
+/// (cesta v manifestu, skutečná cesta); `update` upraví další pole manifestu.
+pub fn refresh_manifest_files(
+    logs_dir: &Path,
+    changed: &[(String, PathBuf)],
+    update: impl FnOnce(&mut serde_json::Value),
+) -> Result<()> {
+    let manifest_path = logs_dir.join("manifest.json");
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Nelze načíst `{}`", manifest_path.display()))?;
+    let mut manifest: serde_json::Value = serde_json::from_str(&text)
+        .with_context(|| format!("Nelze parsovat `{}`", manifest_path.display()))?;
+    let selected: Vec<HashAlgorithm> =
+        serde_json::from_value(manifest["checksum_algorithms"].clone()).unwrap_or_default();
+    let algorithms = with_blake3(&selected);
+
+    let mut infos = BTreeMap::new();
+    for (recorded, actual) in changed {
+        let mut info = file_info(actual, &algorithms)
+            .with_context(|| format!("Hash pro `{}` selhal", actual.display()))?;
+        info.path = recorded.clone();
+        infos.insert(recorded.clone(), info);
+    }
+
+    replace_file_infos(&mut manifest, &infos)?;
+    update(&mut manifest);
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Nelze zapsat `{}`", manifest_path.display()))?;
+
+    for algorithm in algorithms {
+        let path = logs_dir.join(algorithm.checksum_file_name());
+        if !path.exists() {
+            continue;
         }
-        if let Some(ref f) = page.alto {
-            add(f);
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Nelze načíst `{}`", path.display()))?;
+        let mut checksums = String::new();
+        for line in text.lines() {
+            let updated = line.split_once("  ").and_then(|(_, file)| {
+                Some((file, infos.get(file.trim())?.digests.get(&algorithm)?))
+            });
+            match updated {
+                Some((file, hash)) => checksums.push_str(&format!("{hash}  {file}\n")),
+                None => {
+                    checksums.push_str(line);
+                    checksums.push('\n');
+                }
+            }
         }
+        fs::write(&path, checksums)
+            .with_context(|| format!("Nelze zapsat `{}`", path.display()))?;
     }
+    Ok(())
+}
 
-    fs::write(logs_dir.join("checksums.txt"), checksums)?;
+/// Nahradí v manifestu objekty souborů (s polem `path`) novými záznamy
+fn replace_file_infos(value: &mut serde_json::Value, infos: &BTreeMap<String, FileInfo>) -> Result<()> {
+    match value {
+        serde_json::Value::Object(map) => {
+            let info = map
+                .get("path")
+                .and_then(|p| p.as_str())
+                .filter(|_| map.contains_key("blake3"))
+                .and_then(|p| infos.get(p));
+            match info {
+                Some(info) => *value = serde_json::to_value(info)?,
+                None => {
+                    for v in map.values_mut() {
+                        replace_file_infos(v, infos)?;
+                    }
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for v in items {
+                replace_file_infos(v, infos)?;
+            }
+        }
+        _ => {}
+    }
     Ok(())
 }
\ No newline at end of file
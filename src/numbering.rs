@@ -0,0 +1,145 @@
+// src/numbering.rs
+// Číslování stránek: první index každé dávky.
+// Použitý začátek se ukládá do `*_logs/numbering.json`, takže přidání
+// nebo odebrání jiné dávky už zpracované dávky nepřečísluje.
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Soubor s uloženým číslováním v `*_logs` adresáři dávky
+pub const NUMBERING_FILE: &str = "numbering.json";
+
+/// Jak se počítá první index dávky
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum NumberingMode {
+    /// Indexy běží souvisle přes všechny dávky od --start-index
+    #[default]
+    Global,
+    /// Každá dávka začíná znovu od --start-index
+    PerBatch,
+}
+
+impl NumberingMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            NumberingMode::Global => "souvisle přes dávky",
+            NumberingMode::PerBatch => "každá dávka od začátku",
+        }
+    }
+}
+
+/// Uložené číslování jedné dávky
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchNumbering {
+    pub index_start: u32,
+    /// Začátek zadaný ručně – platí v každém režimu
+    #[serde(default)]
+    pub explicit: bool,
+    /// Režim, kterým byl začátek spočítán
+    pub mode: NumberingMode,
+}
+
+impl BatchNumbering {
+    /// Načte číslování z `logs_dir`; pokud neexistuje nebo je poškozené, vrátí None.
+    pub fn load(logs_dir: &Path) -> Option<Self> {
+        let text = fs::read_to_string(logs_dir.join(NUMBERING_FILE)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn save(&self, logs_dir: &Path) -> Result<()> {
+        fs::create_dir_all(logs_dir)?;
+        let path = logs_dir.join(NUMBERING_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)
+            .with_context(|| format!("Nelze zapsat číslování dávky `{}`", path.display()))
+    }
+
+    /// Smaže uložené číslování (dávka se znovu očísluje podle režimu)
+    pub fn remove(logs_dir: &Path) -> Result<()> {
+        let path = logs_dir.join(NUMBERING_FILE);
+        if path.exists() {
+            fs::remove_file(&path)
+                .with_context(|| format!("Nelze smazat `{}`", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Spočítá první indexy dávek v daném pořadí. `batches` = (uložené číslování, počet stránek).
+/// Ruční začátek platí vždy; uložený začátek se použije, pokud vznikl stejným režimem;
+/// jinak se začátek spočítá podle režimu. V režimu `Global` další dávka navazuje
+/// na konec předchozí. Chyba, pokud by indexy některé dávky přetekly u32.
+pub fn assign_starts(
+    mode: NumberingMode,
+    start_index: u32,
+    batches: &[(Option<BatchNumbering>, usize)],
+) -> Result<Vec<u32>> {
+    let mut next = start_index;
+    let mut starts = Vec::with_capacity(batches.len());
+    for (recorded, count) in batches {
+        let start = match recorded {
+            Some(r) if r.explicit || r.mode == mode => r.index_start,
+            _ => match mode {
+                NumberingMode::Global => next,
+                NumberingMode::PerBatch => start_index,
+            },
+        };
+        next = u32::try_from(*count)
+            .ok()
+            .and_then(|count| start.checked_add(count))
+            .ok_or_else(|| anyhow!("Indexy od {start} pro {count} stránek přesahují {}", u32::MAX))?;
+        starts.push(start);
+    }
+    Ok(starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(index_start: u32, explicit: bool, mode: NumberingMode) -> Option<BatchNumbering> {
+        Some(BatchNumbering {
+            index_start,
+            explicit,
+            mode,
+        })
+    }
+
+    #[test]
+    fn global_continues_across_batches() {
+        let starts = assign_starts(NumberingMode::Global, 1, &[(None, 3), (None, 0), (None, 2)]).unwrap();
+        assert_eq!(starts, vec![1, 4, 4]);
+    }
+
+    #[test]
+    fn per_batch_restarts() {
+        let starts = assign_starts(NumberingMode::PerBatch, 5, &[(None, 3), (None, 2)]).unwrap();
+        assert_eq!(starts, vec![5, 5]);
+    }
+
+    #[test]
+    fn recorded_starts_are_kept() {
+        let batches = [
+            (recorded(100, true, NumberingMode::PerBatch), 10),
+            (recorded(7, false, NumberingMode::Global), 2),
+            (recorded(50, false, NumberingMode::PerBatch), 1),
+            (None, 1),
+        ];
+        // Ruční začátek platí vždy, uložený jen ze stejného režimu
+        let starts = assign_starts(NumberingMode::Global, 1, &batches).unwrap();
+        assert_eq!(starts, vec![100, 7, 9, 10]);
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert!(assign_starts(NumberingMode::Global, u32::MAX - 2, &[(None, 1), (None, 1)]).is_ok());
+        assert!(assign_starts(NumberingMode::Global, u32::MAX - 1, &[(None, 2), (None, 1)]).is_err());
+        assert!(assign_starts(NumberingMode::PerBatch, 1, &[(None, usize::MAX)]).is_err());
+        let explicit = [(recorded(u32::MAX, true, NumberingMode::Global), 5)];
+        assert!(assign_starts(NumberingMode::Global, 1, &explicit).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+// src/ocr.rs
+// Nastavení Tesseractu (jazyk, verze ALTO, PSM, OEM, DPI, user-words/patterns,
+// -c proměnné) a jednotky souřadnic upraveného ALTO. Priorita: `*_logs/ocr_settings.json` uložený z TUI (klávesa T),
+// jinak výchozí hodnoty z CLI / konfigurace překryté souborem `ocr.json`
+// v adresáři dávky. Použité nastavení se zapisuje do manifestu a do
+// state.json (změna = OCR znovu).
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::alto::{AltoUnit, AltoVersion};
+use crate::Args;
+
+/// Soubor s nastavením OCR dávky v `*_logs` adresáři
+pub const OCR_SETTINGS_FILE: &str = "ocr_settings.json";
+
+/// Volitelný soubor s nastavením OCR ve vstupním adresáři dávky
+/// (stejný formát, uvedené položky přepíšou výchozí hodnoty)
+pub const SIDECAR_FILE: &str = "ocr.json";
+
+/// Parametry Tesseractu; None = výchozí hodnota Tesseractu
+/// (u jazyka a verze ALTO globální `--lang` / `--alto-version`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OcrSettings {
+    /// Jazyk dávky (např. `deu+frk`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alto_version: Option<String>,
+    /// Jednotka souřadnic ALTO po úpravě (výchozí pixel)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alto_unit: Option<AltoUnit>,
+    /// Režim segmentace stránky (--psm 0–13)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub psm: Option<u8>,
+    /// OCR engine (--oem 0–3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oem: Option<u8>,
+    /// Rozlišení vstupu, pokud ho obrázek neuvádí nebo je chybné (--dpi)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_words: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_patterns: Option<PathBuf>,
+    /// Další proměnné (-c název=hodnota)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Položka nastavení v TUI panelu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcrField {
+    Lang,
+    AltoVersion,
+    AltoUnit,
+    Psm,
+    Oem,
+    Dpi,
+    UserWords,
+    UserPatterns,
+    Variables,
+}
+
+impl OcrField {
+    pub const ALL: [OcrField; 9] = [
+        OcrField::Lang,
+        OcrField::AltoVersion,
+        OcrField::AltoUnit,
+        OcrField::Psm,
+        OcrField::Oem,
+        OcrField::Dpi,
+        OcrField::UserWords,
+        OcrField::UserPatterns,
+        OcrField::Variables,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OcrField::Lang => "Jazyk",
+            OcrField::AltoVersion => "ALTO verze",
+            OcrField::AltoUnit => "ALTO jednotky",
+            OcrField::Psm => "PSM (segmentace)",
+            OcrField::Oem => "OEM (engine)",
+            OcrField::Dpi => "DPI",
+            OcrField::UserWords => "User words",
+            OcrField::UserPatterns => "User patterns",
+            OcrField::Variables => "Proměnné -c",
+        }
+    }
+
+    /// Nápověda k zadání hodnoty
+    pub fn hint(self) -> &'static str {
+        match self {
+            OcrField::Lang => "kódy jazyků oddělené '+', např. deu+frk",
+            OcrField::AltoVersion => "2.0, 2.1, 3.0, 3.1 nebo 4.0–4.4",
+            OcrField::AltoUnit => "pixel, mm10 nebo inch1200 (převod podle DPI obrázku)",
+            OcrField::Psm => "0–13, např. 1 = auto s OSD, 3 = auto, 4 = sloupce, 6 = jeden blok",
+            OcrField::Oem => "0 = legacy, 1 = LSTM, 2 = obojí, 3 = výchozí",
+            OcrField::Dpi => "rozlišení vstupu, např. 300",
+            OcrField::UserWords => "cesta k souboru se slovy (jedno na řádek)",
+            OcrField::UserPatterns => "cesta k souboru se vzory",
+            OcrField::Variables => "název=hodnota oddělené mezerou",
+        }
+    }
+}
+
+impl OcrSettings {
+    /// Výchozí nastavení z CLI / konfigurace
+    pub fn from_args(args: &Args) -> Self {
+        Self {
+            lang: None,
+            alto_version: None,
+            alto_unit: args.alto_unit,
+            psm: args.psm,
+            oem: args.oem,
+            dpi: args.dpi,
+            user_words: args.user_words.clone(),
+            user_patterns: args.user_patterns.clone(),
+            variables: args.tess_vars.iter().cloned().collect(),
+        }
+    }
+
+    /// Výchozí nastavení překryté souborem `ocr.json` v adresáři dávky
+    pub fn for_batch(args: &Args, batch_dir: &Path) -> Result<Self> {
+        let mut settings = Self::from_args(args);
+        let path = batch_dir.join(SIDECAR_FILE);
+        if !path.exists() {
+            return Ok(settings);
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Nelze načíst `{}`", path.display()))?;
+        let sidecar: OcrSettings = serde_json::from_str(&text)
+            .with_context(|| format!("Neplatný `{}`", path.display()))?;
+        let checked = match (&sidecar.alto_version, sidecar.psm, sidecar.oem) {
+            (Some(v), _, _) if check_alto_version(v).is_err() => check_alto_version(v),
+            (_, Some(psm), _) if psm > 13 => Err(anyhow!("psm {psm} není 0–13")),
+            (_, _, Some(oem)) if oem > 3 => Err(anyhow!("oem {oem} není 0–3")),
+            _ => Ok(()),
+        };
+        checked.with_context(|| format!("Neplatný `{}`", path.display()))?;
+        settings.overlay(sidecar);
+        Ok(settings)
+    }
+
+    /// Položky nastavené v `other` přepíšou tyto
+    fn overlay(&mut self, other: OcrSettings) {
+        if other.lang.is_some() {
+            self.lang = other.lang;
+        }
+        if other.alto_version.is_some() {
+            self.alto_version = other.alto_version;
+        }
+        if other.alto_unit.is_some() {
+            self.alto_unit = other.alto_unit;
+        }
+        if other.psm.is_some() {
+            self.psm = other.psm;
+        }
+        if other.oem.is_some() {
+            self.oem = other.oem;
+        }
+        if other.dpi.is_some() {
+            self.dpi = other.dpi;
+        }
+        if other.user_words.is_some() {
+            self.user_words = other.user_words;
+        }
+        if other.user_patterns.is_some() {
+            self.user_patterns = other.user_patterns;
+        }
+        self.variables.extend(other.variables);
+    }
+
+    /// Jazyk dávky, jinak globální
+    pub fn lang_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.lang.as_deref().unwrap_or(default)
+    }
+
+    /// Verze ALTO dávky, jinak globální
+    pub fn alto_version_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.alto_version.as_deref().unwrap_or(default)
+    }
+
+    /// Nastavení s doplněným jazykem a verzí ALTO, se kterými OCR skutečně
+    /// poběží (pro porovnání se stavem minulého běhu)
+    pub fn resolved(&self, lang: &str, alto_version: &str) -> Self {
+        Self {
+            lang: Some(lang.to_string()),
+            alto_version: Some(alto_version.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Načte nastavení dávky z `logs_dir`; pokud neexistuje nebo je poškozené, vrátí None.
+    pub fn load(logs_dir: &Path) -> Option<Self> {
+        let text = fs::read_to_string(logs_dir.join(OCR_SETTINGS_FILE)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn save(&self, logs_dir: &Path) -> Result<()> {
+        fs::create_dir_all(logs_dir)?;
+        let path = logs_dir.join(OCR_SETTINGS_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)
+            .with_context(|| format!("Nelze zapsat nastavení OCR `{}`", path.display()))
+    }
+
+    /// Smaže nastavení dávky (platí opět výchozí z konfigurace)
+    pub fn remove(logs_dir: &Path) -> Result<()> {
+        let path = logs_dir.join(OCR_SETTINGS_FILE);
+        if path.exists() {
+            fs::remove_file(&path)
+                .with_context(|| format!("Nelze smazat `{}`", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Přidá volby do příkazu Tesseractu (před názvy výstupních konfigurací).
+    /// Jazyk a verzi ALTO nastavuje volající.
+    pub fn apply(&self, cmd: &mut Command) {
+        if let Some(psm) = self.psm {
+            cmd.arg("--psm").arg(psm.to_string());
+        }
+        if let Some(oem) = self.oem {
+            cmd.arg("--oem").arg(oem.to_string());
+        }
+        if let Some(dpi) = self.dpi {
+            cmd.arg("--dpi").arg(dpi.to_string());
+        }
+        if let Some(ref path) = self.user_words {
+            cmd.arg("--user-words").arg(path);
+        }
+        if let Some(ref path) = self.user_patterns {
+            cmd.arg("--user-patterns").arg(path);
+        }
+        for (name, value) in &self.variables {
+            cmd.arg("-c").arg(format!("{name}={value}"));
+        }
+    }
+
+    /// Chybějící soubory user-words / user-patterns
+    pub fn missing_files(&self) -> Vec<&Path> {
+        [&self.user_words, &self.user_patterns]
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+            .filter(|p| !p.is_file())
+            .collect()
+    }
+
+    /// Krátký popis pro log a TUI ("výchozí Tesseract" bez voleb)
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for field in OcrField::ALL {
+            let value = self.value(field);
+            if !value.is_empty() {
+                parts.push(format!("{}: {value}", field.label()));
+            }
+        }
+        if parts.is_empty() {
+            "výchozí Tesseract".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// Hodnota položky jako text (prázdný = výchozí)
+    pub fn value(&self, field: OcrField) -> String {
+        let path = |p: &Option<PathBuf>| {
+            p.as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        };
+        match field {
+            OcrField::Lang => self.lang.clone().unwrap_or_default(),
+            OcrField::AltoVersion => self.alto_version.clone().unwrap_or_default(),
+            OcrField::AltoUnit => self.alto_unit.map(|u| u.name().to_string()).unwrap_or_default(),
+            OcrField::Psm => self.psm.map(|v| v.to_string()).unwrap_or_default(),
+            OcrField::Oem => self.oem.map(|v| v.to_string()).unwrap_or_default(),
+            OcrField::Dpi => self.dpi.map(|v| v.to_string()).unwrap_or_default(),
+            OcrField::UserWords => path(&self.user_words),
+            OcrField::UserPatterns => path(&self.user_patterns),
+            OcrField::Variables => self
+                .variables
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    /// Nastaví položku z textu; prázdný text = výchozí hodnota
+    pub fn set(&mut self, field: OcrField, text: &str) -> Result<()> {
+        let text = text.trim();
+        let path = || (!text.is_empty()).then(|| PathBuf::from(text));
+        let string = || (!text.is_empty()).then(|| text.to_string());
+        match field {
+            OcrField::Lang => {
+                if text.split('+').any(|code| code.trim().is_empty()) && !text.is_empty() {
+                    bail!("`{text}` není platná kombinace jazyků");
+                }
+                self.lang = string();
+            }
+            OcrField::AltoVersion => {
+                if !text.is_empty() {
+                    check_alto_version(text)?;
+                }
+                self.alto_version = string();
+            }
+            OcrField::AltoUnit => {
+                self.alto_unit = if text.is_empty() {
+                    None
+                } else {
+                    Some(AltoUnit::parse(text).ok_or_else(|| {
+                        anyhow!("`{text}` není jednotka ALTO (pixel, mm10, inch1200)")
+                    })?)
+                }
+            }
+            OcrField::Psm => self.psm = parse_ranged(text, 13)?,
+            OcrField::Oem => self.oem = parse_ranged(text, 3)?,
+            OcrField::Dpi => {
+                self.dpi = if text.is_empty() {
+                    None
+                } else {
+                    Some(text.parse().map_err(|_| anyhow!("`{text}` není platné DPI"))?)
+                }
+            }
+            OcrField::UserWords => self.user_words = path(),
+            OcrField::UserPatterns => self.user_patterns = path(),
+            OcrField::Variables => {
+                self.variables = text
+                    .split_whitespace()
+                    .map(|s| parse_variable(s).map_err(|e| anyhow!(e)))
+                    .collect::<Result<_>>()?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verze ALTO, na kterou umí výstup převést alto.rs
+fn check_alto_version(text: &str) -> Result<()> {
+    AltoVersion::parse(text).map(|_| ())
+}
+
+fn parse_ranged(text: &str, max: u8) -> Result<Option<u8>> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<u8>() {
+        Ok(v) if v <= max => Ok(Some(v)),
+        _ => bail!("`{text}` není číslo 0–{max}"),
+    }
+}
+
+/// Proměnná Tesseractu ve tvaru `název=hodnota` (pro --tess-var i TUI)
+pub fn parse_variable(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("`{s}` není ve tvaru název=hodnota")),
+    }
+}
@@ -0,0 +1,252 @@
+// src/ordering.rs
+// Pořadí stránek v dávce: přirozené řazení názvů (scan_2 < scan_10),
+// nebo explicitní pořadí ze souboru order.txt v adresáři dávky.
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::inputs::PageSource;
+
+/// Soubor s pořadím v adresáři dávky: jeden název souboru na řádek,
+/// prázdné řádky a řádky začínající `#` se ignorují
+pub const ORDER_FILE: &str = "order.txt";
+
+/// Podle čeho bylo pořadí určeno (zapisuje se do manifestu)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSource {
+    /// Přirozené řazení názvů souborů
+    #[default]
+    Natural,
+    /// Pořadí z order.txt
+    File,
+}
+
+/// Výsledné pořadí stránek dávky
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PageOrder {
+    pub source: OrderSource,
+    /// Cesta k souboru s pořadím (jen u `File`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_file: Option<PathBuf>,
+    /// Soubory, které v order.txt chybí – jsou zařazeny na konec (přirozeně)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unlisted: Vec<String>,
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+    /// Stránky po rozdělení vícestránkových TIFFů (viz `inputs::expand_pages`)
+    #[serde(skip)]
+    pub pages: Vec<PageSource>,
+}
+
+impl PageOrder {
+    /// Krátký popis pro TUI a log
+    pub fn describe(&self) -> String {
+        match self.source {
+            OrderSource::Natural => "přirozené řazení názvů".to_string(),
+            OrderSource::File if self.unlisted.is_empty() => ORDER_FILE.to_string(),
+            OrderSource::File => format!(
+                "{} (+{} souborů mimo seznam na konci)",
+                ORDER_FILE,
+                self.unlisted.len()
+            ),
+        }
+    }
+}
+
+/// Přirozené pořadí bez ohledu na order.txt
+pub fn natural_order(mut files: Vec<PathBuf>) -> PageOrder {
+    files.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)));
+    PageOrder {
+        files,
+        ..PageOrder::default()
+    }
+}
+
+/// Seřadí soubory dávky: order.txt, pokud v `dir` existuje, jinak přirozeně.
+/// Soubor z order.txt, který v dávce není, nebo duplicita je chyba.
+pub fn page_order(dir: &Path, files: Vec<PathBuf>) -> Result<PageOrder> {
+    let natural = natural_order(files);
+    let order_path = dir.join(ORDER_FILE);
+    if !order_path.is_file() {
+        return Ok(natural);
+    }
+    let files = natural.files;
+
+    let text = fs::read_to_string(&order_path)
+        .with_context(|| format!("Nelze načíst `{}`", order_path.display()))?;
+
+    let mut ordered = Vec::with_capacity(files.len());
+    let mut seen = BTreeSet::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let name = line.trim();
+        if name.is_empty() || name.starts_with('#') {
+            continue;
+        }
+        if !seen.insert(name.to_string()) {
+            bail!("`{}` je v {} dvakrát (řádek {})", name, order_path.display(), line_no + 1);
+        }
+        match files.iter().find(|f| file_name(f) == name) {
+            Some(f) => ordered.push(f.clone()),
+            None => bail!(
+                "`{}` z {} (řádek {}) v dávce není",
+                name,
+                order_path.display(),
+                line_no + 1
+            ),
+        }
+    }
+
+    let mut unlisted = Vec::new();
+    for f in &files {
+        let name = file_name(f);
+        if !seen.contains(&name) {
+            unlisted.push(name);
+            ordered.push(f.clone());
+        }
+    }
+
+    Ok(PageOrder {
+        source: OrderSource::File,
+        order_file: Some(order_path),
+        unlisted,
+        files: ordered,
+        pages: Vec::new(),
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Přirozené porovnání: čísla v názvu se porovnávají jako čísla,
+/// text bez ohledu na velikost písmen (při shodě rozhoduje přesný název)
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut ai = a.chars().peekable();
+    let mut bi = b.chars().peekable();
+
+    loop {
+        match (ai.peek().copied(), bi.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let na = take_digits(&mut ai);
+                let nb = take_digits(&mut bi);
+                let (ta, tb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+                let ord = ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ord = x.to_lowercase().cmp(y.to_lowercase());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                ai.next();
+                bi.next();
+            }
+        }
+    }
+}
+
+/// Přirozené porovnání cest po komponentách (kratší prefix je dřív)
+pub fn natural_path_cmp(a: &Path, b: &Path) -> Ordering {
+    let mut ai = a.components();
+    let mut bi = b.components();
+    loop {
+        match (ai.next(), bi.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ord = natural_cmp(&x.as_os_str().to_string_lossy(), &y.as_os_str().to_string_lossy());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+fn take_digits(it: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut s = String::new();
+    while let Some(c) = it.peek().copied().filter(char::is_ascii_digit) {
+        s.push(c);
+        it.next();
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(
+            sorted(&["scan_10.tif", "scan_2.tif", "scan_1.tif"]),
+            vec!["scan_1.tif", "scan_2.tif", "scan_10.tif"]
+        );
+        assert_eq!(sorted(&["a10b2", "a10b10", "a9"]), vec!["a9", "a10b2", "a10b10"]);
+    }
+
+    #[test]
+    fn leading_zeros_and_case_break_ties_only() {
+        assert_eq!(natural_cmp("p007", "p7"), "p007".cmp("p7"));
+        assert_eq!(natural_cmp("p007", "p8"), Ordering::Less);
+        assert_eq!(natural_cmp("B1", "a2"), Ordering::Greater);
+        assert_eq!(natural_cmp("A1", "a1"), "A1".cmp("a1"));
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+    }
+
+    #[test]
+    fn huge_numbers_do_not_overflow() {
+        let big = "x99999999999999999999999999999999";
+        assert_eq!(natural_cmp(big, "x100000000000000000000000000000000"), Ordering::Less);
+        assert_eq!(natural_cmp(big, "x2"), Ordering::Greater);
+    }
+
+    #[test]
+    fn paths_compare_by_component() {
+        assert_eq!(natural_path_cmp(Path::new("a/b2"), Path::new("a/b10")), Ordering::Less);
+        assert_eq!(natural_path_cmp(Path::new("a2/z"), Path::new("a10/a")), Ordering::Less);
+        assert_eq!(natural_path_cmp(Path::new("a"), Path::new("a/b")), Ordering::Less);
+    }
+
+    #[test]
+    fn order_file_is_validated() {
+        let dir = std::env::temp_dir().join(format!("lazyproarc-{}-order", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let files: Vec<PathBuf> = ["p1.tif", "p2.tif", "p10.tif"].iter().map(|f| dir.join(f)).collect();
+
+        let natural = page_order(&dir, files.clone()).unwrap();
+        assert_eq!(natural.source, OrderSource::Natural);
+
+        fs::write(dir.join(ORDER_FILE), "# obálka\np10.tif\n\np1.tif\n").unwrap();
+        let order = page_order(&dir, files.clone()).unwrap();
+        let names: Vec<_> = order.files.iter().map(|f| file_name(f)).collect();
+        assert_eq!(names, vec!["p10.tif", "p1.tif", "p2.tif"]);
+        assert_eq!(order.unlisted, vec!["p2.tif"]);
+
+        fs::write(dir.join(ORDER_FILE), "p1.tif\np1.tif\n").unwrap();
+        let duplicate = page_order(&dir, files.clone()).is_err();
+        fs::write(dir.join(ORDER_FILE), "p3.tif\n").unwrap();
+        let unknown = page_order(&dir, files).is_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(duplicate && unknown);
+    }
+}
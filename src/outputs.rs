@@ -0,0 +1,34 @@
+// src/outputs.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// `*_logs` adresáře s manifestem v output rootu (i ve vnořených
+/// adresářích dávek), seřazené podle cesty
+pub fn find_logs_dirs(output_root: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let mut pending = vec![output_root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("Nelze číst adresář `{}`", dir.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let is_logs = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with("_logs"));
+            if is_logs && path.join("manifest.json").exists() {
+                dirs.push(path);
+            } else if !is_logs {
+                pending.push(path);
+            }
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
@@ -0,0 +1,95 @@
+// src/pdf.rs
+// Spojení PDF stránek z Tesseractu (xxxx.ocr.pdf) do jednoho PDF dávky.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use lopdf::{Document, Object, ObjectId};
+
+/// Název spojeného PDF ve výstupním adresáři dávky (`t/2020/01` → `t_2020_01.pdf`)
+pub fn merged_pdf_name(batch_name: &str) -> String {
+    format!("{}.pdf", batch_name.replace(['/', '\\'], "_"))
+}
+
+/// Spojí PDF v daném pořadí do `output`. Vrací počet stránek výsledku.
+pub fn merge_pdfs(inputs: &[PathBuf], output: &Path) -> Result<usize> {
+    if inputs.is_empty() {
+        return Err(anyhow!("Žádná PDF ke spojení"));
+    }
+
+    let mut merged = Document::with_version("1.5");
+    let mut max_id = 1;
+    // Stránky v pořadí souborů a v rámci souboru podle čísla stránky
+    let mut pages: Vec<(ObjectId, Object)> = Vec::new();
+    let mut objects: BTreeMap<ObjectId, Object> = BTreeMap::new();
+
+    for path in inputs {
+        let mut doc = Document::load(path)
+            .with_context(|| format!("Nelze načíst PDF `{}`", path.display()))?;
+        // Čísla objektů navazují, aby se objekty různých souborů nepřekrývaly
+        doc.renumber_objects_with(max_id);
+        max_id = doc.max_id + 1;
+
+        for id in doc.get_pages().into_values() {
+            let page = doc
+                .get_object(id)
+                .with_context(|| format!("Poškozená stránka v `{}`", path.display()))?
+                .to_owned();
+            pages.push((id, page));
+        }
+        objects.extend(doc.objects);
+    }
+
+    // Katalog a strom stránek z prvního dokumentu, ostatní objekty beze změny
+    let mut catalog: Option<(ObjectId, Object)> = None;
+    let mut pages_root: Option<(ObjectId, Object)> = None;
+    for (id, object) in objects {
+        match object.type_name().unwrap_or(b"") {
+            b"Catalog" => {
+                catalog.get_or_insert((id, object));
+            }
+            b"Pages" => {
+                pages_root.get_or_insert((id, object));
+            }
+            b"Page" | b"Outlines" | b"Outline" => {}
+            _ => {
+                merged.objects.insert(id, object);
+            }
+        }
+    }
+    let (catalog_id, catalog) = catalog.context("PDF nemá katalog")?;
+    let (pages_id, pages_root) = pages_root.context("PDF nemá strom stránek")?;
+
+    let page_count = pages.len();
+    for (id, page) in &pages {
+        let mut dict = page.as_dict()?.clone();
+        dict.set("Parent", pages_id);
+        merged.objects.insert(*id, Object::Dictionary(dict));
+    }
+
+    let mut root = pages_root.as_dict()?.clone();
+    root.set("Count", page_count as u32);
+    root.set(
+        "Kids",
+        pages
+            .into_iter()
+            .map(|(id, _)| Object::Reference(id))
+            .collect::<Vec<_>>(),
+    );
+    merged.objects.insert(pages_id, Object::Dictionary(root));
+
+    let mut catalog = catalog.as_dict()?.clone();
+    catalog.set("Pages", pages_id);
+    catalog.remove(b"Outlines");
+    merged.objects.insert(catalog_id, Object::Dictionary(catalog));
+
+    merged.trailer.set("Root", catalog_id);
+    merged.max_id = merged.objects.len() as u32;
+    merged.renumber_objects();
+    merged.compress();
+
+    merged
+        .save(output)
+        .with_context(|| format!("Nelze zapsat `{}`", output.display()))?;
+    Ok(page_count)
+}
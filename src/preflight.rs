@@ -0,0 +1,1025 @@
+// src/preflight.rs
+// Kontrola vstupních TIFFů před konverzí.
+// Čte jen hlavičku a IFD (tagy), obrazová data nedekóduje – jen ověří,
+// že strip/tile offsety leží uvnitř souboru.
+// Stejným parserem se z vícestránkového TIFFu vyjímá jedna stránka.
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::InputFormat;
+
+/// Soubor se zprávou o kontrole v `*_logs` adresáři
+pub const PREFLIGHT_REPORT_FILE: &str = "preflight.json";
+
+// Tagy, které nás zajímají
+const NEW_SUBFILE_TYPE: u16 = 254;
+const IMAGE_WIDTH: u16 = 256;
+const IMAGE_LENGTH: u16 = 257;
+const BITS_PER_SAMPLE: u16 = 258;
+const COMPRESSION: u16 = 259;
+const PHOTOMETRIC: u16 = 262;
+const STRIP_OFFSETS: u16 = 273;
+const SAMPLES_PER_PIXEL: u16 = 277;
+const STRIP_BYTE_COUNTS: u16 = 279;
+const X_RESOLUTION: u16 = 282;
+const Y_RESOLUTION: u16 = 283;
+const RESOLUTION_UNIT: u16 = 296;
+const TILE_OFFSETS: u16 = 324;
+const TILE_BYTE_COUNTS: u16 = 325;
+const SUB_IFDS: u16 = 330;
+const EXTRA_SAMPLES: u16 = 338;
+const ICC_PROFILE: u16 = 34675;
+const EXIF_IFD: u16 = 34665;
+const GPS_IFD: u16 = 34853;
+const INTEROP_IFD: u16 = 40965;
+
+/// Co dělat při porušení pravidla
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Ignore,
+    Warn,
+    /// Dávka se nespustí
+    Block,
+}
+
+/// Pravidla kontroly (v konfiguraci pod klíčem "preflight").
+/// Nečitelný / poškozený soubor dávku blokuje vždy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreflightRules {
+    /// Bitová hloubka kanálu mimo `bit_depths`
+    pub bit_depth: RuleAction,
+    pub bit_depths: Vec<u16>,
+    /// Barevný prostor jiný než RGB / odstíny šedi (CMYK, paleta, Lab…) nebo alfa kanál
+    pub color_space: RuleAction,
+    /// Chybějící rozlišení nebo méně než `min_dpi`
+    pub resolution: RuleAction,
+    pub min_dpi: u32,
+    /// Komprese mimo `compressions` ("none", "lzw", "deflate", "packbits", "jpeg", …)
+    pub compression: RuleAction,
+    pub compressions: Vec<String>,
+    /// Více stránek v jednom TIFFu (stránky se zpracují jako samostatné indexy)
+    pub page_count: RuleAction,
+    /// Chybí vložený ICC profil
+    pub icc_profile: RuleAction,
+}
+
+impl Default for PreflightRules {
+    fn default() -> Self {
+        Self {
+            bit_depth: RuleAction::Warn,
+            bit_depths: vec![8],
+            color_space: RuleAction::Warn,
+            resolution: RuleAction::Warn,
+            min_dpi: 300,
+            compression: RuleAction::Warn,
+            compressions: vec!["none".to_string()],
+            page_count: RuleAction::Ignore,
+            icc_profile: RuleAction::Warn,
+        }
+    }
+}
+
+/// Tagy jedné stránky TIFFu
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TiffInfo {
+    pub width: u32,
+    pub height: u32,
+    pub bits_per_sample: Vec<u16>,
+    pub samples_per_pixel: u16,
+    pub extra_samples: u16,
+    /// PhotometricInterpretation jako text ("rgb", "min-is-black", "cmyk", …)
+    pub photometric: String,
+    pub compression: String,
+    /// (x, y) v DPI; None = chybí nebo je bez jednotky
+    pub dpi: Option<(f64, f64)>,
+    pub icc_profile: bool,
+    /// Počet stránek (IFD bez náhledů / zmenšenin)
+    pub pages: usize,
+    pub big_tiff: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightIssue {
+    /// Název pravidla ("bit_depth", …, "corrupt")
+    pub rule: String,
+    pub action: RuleAction,
+    pub message: String,
+}
+
+/// Výsledek kontroly jednoho souboru
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiffReport {
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<TiffInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<PreflightIssue>,
+}
+
+/// Výsledek kontroly celé dávky
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchPreflight {
+    pub warnings: usize,
+    pub blocking: usize,
+    pub files: Vec<TiffReport>,
+}
+
+impl BatchPreflight {
+    /// Načte zprávu z `*_logs/preflight.json` (None, pokud není)
+    pub fn load(logs_dir: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(logs_dir.join(PREFLIGHT_REPORT_FILE)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.blocking > 0
+    }
+
+    /// Všechna porušení pravidel: (soubor, problém)
+    pub fn issues(&self) -> impl Iterator<Item = (&Path, &PreflightIssue)> {
+        self.files
+            .iter()
+            .flat_map(|f| f.issues.iter().map(move |i| (f.path.as_path(), i)))
+    }
+}
+
+/// Zkontroluje všechny TIFFy dávky podle pravidel (ostatní vstupní formáty se přeskočí)
+pub fn preflight_batch(tiffs: &[PathBuf], rules: &PreflightRules) -> BatchPreflight {
+    let mut batch = BatchPreflight::default();
+    for path in tiffs
+        .iter()
+        .filter(|p| InputFormat::from_path(p) == Some(InputFormat::Tiff))
+    {
+        let report = check_tiff(path, rules);
+        for issue in &report.issues {
+            match issue.action {
+                RuleAction::Block => batch.blocking += 1,
+                RuleAction::Warn => batch.warnings += 1,
+                RuleAction::Ignore => {}
+            }
+        }
+        batch.files.push(report);
+    }
+    batch
+}
+
+/// Zapíše zprávu do `*_logs/preflight.json`
+pub fn write_preflight_report(report: &BatchPreflight, logs_dir: &Path) -> Result<()> {
+    let path = logs_dir.join(PREFLIGHT_REPORT_FILE);
+    let json = serde_json::to_string_pretty(report)?;
+    std::fs::write(&path, json).with_context(|| format!("Nelze zapsat `{}`", path.display()))
+}
+
+/// Přečte tagy všech stránek TIFFu a porovná je s pravidly.
+/// U vícestránkového TIFFu nese každý problém čísla stránek, kterých se týká.
+pub fn check_tiff(path: &Path, rules: &PreflightRules) -> TiffReport {
+    let pages = match read_tiff_pages(path) {
+        Ok(pages) => pages,
+        Err(e) => {
+            return TiffReport {
+                path: path.to_path_buf(),
+                info: None,
+                issues: vec![corrupt(&e)],
+            };
+        }
+    };
+
+    let count = pages.len();
+    let mut info = None;
+    // Stejný problém na více stránkách se vypíše jednou
+    let mut found: Vec<(PreflightIssue, Vec<usize>)> = Vec::new();
+    for (i, page) in pages.into_iter().enumerate() {
+        let page_issues = match page {
+            Ok(page_info) => {
+                let issues = validate(&page_info, rules);
+                if i == 0 {
+                    info = Some(page_info);
+                }
+                issues
+            }
+            Err(e) => vec![corrupt(&e)],
+        };
+        for issue in page_issues {
+            match found
+                .iter_mut()
+                .find(|(f, _)| f.rule == issue.rule && f.message == issue.message)
+            {
+                Some((_, pages)) => pages.push(i + 1),
+                None => found.push((issue, vec![i + 1])),
+            }
+        }
+    }
+
+    let mut issues: Vec<_> = found
+        .into_iter()
+        .map(|(mut issue, pages)| {
+            if count > 1 {
+                issue.message = format!("{}: {}", page_list(&pages), issue.message);
+            }
+            issue
+        })
+        .collect();
+    if count > 1 && rules.page_count != RuleAction::Ignore {
+        issues.push(PreflightIssue {
+            rule: "page_count".to_string(),
+            action: rules.page_count,
+            message: format!("{count} stránek v jednom souboru"),
+        });
+    }
+
+    TiffReport {
+        path: path.to_path_buf(),
+        info,
+        issues,
+    }
+}
+
+fn corrupt(e: &anyhow::Error) -> PreflightIssue {
+    PreflightIssue {
+        rule: "corrupt".to_string(),
+        action: RuleAction::Block,
+        message: format!("Nečitelný TIFF: {e:#}"),
+    }
+}
+
+/// "stránka 2", "stránky 1–3, 5"
+fn page_list(pages: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &page in pages {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == page => *end = page,
+            _ => ranges.push((page, page)),
+        }
+    }
+    let list = ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}–{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if pages.len() == 1 {
+        format!("stránka {list}")
+    } else {
+        format!("stránky {list}")
+    }
+}
+
+fn validate(info: &TiffInfo, rules: &PreflightRules) -> Vec<PreflightIssue> {
+    let mut issues = Vec::new();
+    let mut add = |rule: &str, action: RuleAction, message: String| {
+        if action != RuleAction::Ignore {
+            issues.push(PreflightIssue {
+                rule: rule.to_string(),
+                action,
+                message,
+            });
+        }
+    };
+
+    if let Some(&bits) = info
+        .bits_per_sample
+        .iter()
+        .find(|b| !rules.bit_depths.contains(b))
+    {
+        add("bit_depth", rules.bit_depth, format!("{bits} bitů na kanál"));
+    }
+
+    let colour_ok = matches!(info.photometric.as_str(), "min-is-white" | "min-is-black" | "rgb");
+    if !colour_ok {
+        add("color_space", rules.color_space, format!("barevný prostor {}", info.photometric));
+    } else if info.extra_samples > 0 {
+        add(
+            "color_space",
+            rules.color_space,
+            format!("{} kanál(y) navíc (alfa)", info.extra_samples),
+        );
+    }
+
+    match info.dpi {
+        None => add("resolution", rules.resolution, "chybí rozlišení (DPI)".to_string()),
+        Some((x, y)) if x.min(y) < rules.min_dpi as f64 => add(
+            "resolution",
+            rules.resolution,
+            format!("rozlišení {x:.0}×{y:.0} DPI (min. {})", rules.min_dpi),
+        ),
+        Some(_) => {}
+    }
+
+    if !rules
+        .compressions
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(&info.compression))
+    {
+        add("compression", rules.compression, format!("komprese {}", info.compression));
+    }
+
+    if !info.icc_profile {
+        add("icc_profile", rules.icc_profile, "chybí ICC profil".to_string());
+    }
+
+    issues
+}
+
+/// Jedna položka IFD (hodnota se čte až podle potřeby)
+#[derive(Clone, Copy)]
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u64,
+    /// Pozice hodnoty v souboru (inline hodnota = pozice v IFD)
+    value_pos: u64,
+}
+
+struct TiffReader<R> {
+    r: R,
+    little_endian: bool,
+    big_tiff: bool,
+    file_len: u64,
+}
+
+/// Otevře TIFF a přečte hlavičku; vrátí čtečku a offset prvního IFD
+fn open_tiff(path: &Path) -> Result<(TiffReader<BufReader<File>>, u64)> {
+    let file = File::open(path).with_context(|| format!("Nelze otevřít `{}`", path.display()))?;
+    let file_len = file.metadata()?.len();
+    let mut t = TiffReader {
+        r: BufReader::new(file),
+        little_endian: true,
+        big_tiff: false,
+        file_len,
+    };
+
+    let mut order = [0u8; 2];
+    t.r.read_exact(&mut order).context("Soubor je příliš krátký")?;
+    t.little_endian = match &order {
+        b"II" => true,
+        b"MM" => false,
+        _ => bail!("Chybí TIFF hlavička (II/MM)"),
+    };
+    let first_ifd = match t.u16()? {
+        42 => t.u32()? as u64,
+        43 => {
+            t.big_tiff = true;
+            let _offset_size = t.u16()?;
+            let _reserved = t.u16()?;
+            t.u64()?
+        }
+        other => bail!("Neplatné TIFF magic číslo {other}"),
+    };
+    Ok((t, first_ifd))
+}
+
+/// IFD všech stránek v pořadí (bez zmenšenin / náhledů)
+fn page_ifds<R: Read + Seek>(t: &mut TiffReader<R>, first_ifd: u64) -> Result<Vec<Vec<IfdEntry>>> {
+    let mut pages = Vec::new();
+    let mut next_ifd = first_ifd;
+    let mut ifd_count = 0;
+
+    while next_ifd != 0 {
+        ifd_count += 1;
+        if ifd_count > 10_000 {
+            bail!("Příliš mnoho IFD (zacyklený řetězec?)");
+        }
+        if next_ifd >= t.file_len {
+            bail!("IFD mimo soubor (offset {next_ifd})");
+        }
+        let (entries, following) = t.read_ifd(next_ifd)?;
+        next_ifd = following;
+
+        let subfile_type = match find(&entries, NEW_SUBFILE_TYPE) {
+            Some(e) => t.first_value(e)?,
+            None => 0,
+        };
+        // Zmenšeniny / náhledy se jako stránky nepočítají
+        if subfile_type & 1 != 0 {
+            continue;
+        }
+        pages.push(entries);
+    }
+
+    if pages.is_empty() {
+        bail!("TIFF neobsahuje žádnou stránku");
+    }
+    Ok(pages)
+}
+
+/// Projde hlavičku a řetězec IFD, tagy první stránky
+pub fn read_tiff_info(path: &Path) -> Result<TiffInfo> {
+    let (mut t, first_ifd) = open_tiff(path)?;
+    let pages = page_ifds(&mut t, first_ifd)?;
+
+    let mut info = TiffInfo {
+        big_tiff: t.big_tiff,
+        pages: pages.len(),
+        ..TiffInfo::default()
+    };
+    read_page(&mut t, &pages[0], &mut info)?;
+    Ok(info)
+}
+
+/// Tagy každé stránky zvlášť – chybná stránka nezneplatní ostatní.
+/// Chyba celku = nečitelná hlavička nebo řetězec IFD.
+pub fn read_tiff_pages(path: &Path) -> Result<Vec<Result<TiffInfo>>> {
+    let (mut t, first_ifd) = open_tiff(path)?;
+    let pages = page_ifds(&mut t, first_ifd)?;
+
+    let mut out = Vec::with_capacity(pages.len());
+    for entries in &pages {
+        let mut info = TiffInfo {
+            big_tiff: t.big_tiff,
+            pages: pages.len(),
+            ..TiffInfo::default()
+        };
+        out.push(read_page(&mut t, entries, &mut info).map(|()| info));
+    }
+    Ok(out)
+}
+
+/// Počet stránek TIFFu (bez zmenšenin / náhledů)
+pub fn tiff_page_count(path: &Path) -> Result<usize> {
+    let (mut t, first_ifd) = open_tiff(path)?;
+    Ok(page_ifds(&mut t, first_ifd)?.len())
+}
+
+/// Zapíše stránku `page` (od 1) vícestránkového TIFFu do samostatného TIFFu.
+/// Tagy i obrazová data se kopírují beze změny (bez dekódování), přepočítají
+/// se jen offsety; odkazy na pod-IFD (SubIFDs, EXIF, GPS) se vynechají.
+pub fn extract_tiff_page(path: &Path, page: usize, output: &Path) -> Result<()> {
+    let (mut t, first_ifd) = open_tiff(path)?;
+    let pages = page_ifds(&mut t, first_ifd)?;
+    let entries = match page.checked_sub(1).and_then(|i| pages.get(i)) {
+        Some(entries) => entries,
+        None => bail!("`{}` nemá stránku {page} (stránek: {})", path.display(), pages.len()),
+    };
+
+    let big = t.big_tiff;
+    let (header_len, count_len, entry_len, inline_len): (u64, u64, u64, u64) =
+        if big { (16, 8, 20, 8) } else { (8, 2, 12, 4) };
+
+    let (offsets_tag, counts_tag) = if find(entries, STRIP_OFFSETS).is_some() {
+        (STRIP_OFFSETS, STRIP_BYTE_COUNTS)
+    } else {
+        (TILE_OFFSETS, TILE_BYTE_COUNTS)
+    };
+    let offsets = t.values(find(entries, offsets_tag).context("Chybí offsety obrazových dat")?)?;
+    let counts = t.values(find(entries, counts_tag).context("Chybí délky obrazových dat")?)?;
+    if offsets.len() != counts.len() {
+        bail!("Nesouhlasí počet offsetů ({}) a délek ({}) dat", offsets.len(), counts.len());
+    }
+
+    // (tag, typ, počet, data) – data offsetů se doplní po rozvržení souboru
+    let offset_type: u16 = if big { 16 } else { 4 };
+    let mut out_entries = Vec::with_capacity(entries.len());
+    for e in entries {
+        if matches!(e.tag, SUB_IFDS | EXIF_IFD | GPS_IFD | INTEROP_IFD) {
+            continue;
+        }
+        if e.tag == offsets_tag {
+            out_entries.push((e.tag, offset_type, e.count, Vec::new()));
+        } else {
+            out_entries.push((e.tag, e.field_type, e.count, t.raw(e)?));
+        }
+    }
+    out_entries.sort_by_key(|(tag, ..)| *tag);
+
+    // Rozvržení: hlavička, IFD, hodnoty mimo IFD, obrazová data
+    let offset_size = type_size(offset_type);
+    let ifd_len = count_len + out_entries.len() as u64 * entry_len + inline_len;
+    let mut pos = header_len + ifd_len;
+    let mut value_pos = Vec::with_capacity(out_entries.len());
+    for (tag, _, count, data) in &out_entries {
+        let len = if *tag == offsets_tag {
+            offset_size.checked_mul(*count).context("Stránka je příliš velká")?
+        } else {
+            data.len() as u64
+        };
+        if len > inline_len {
+            pos += pos % 2;
+            value_pos.push(Some(pos));
+            pos = pos.checked_add(len).context("Stránka je příliš velká")?;
+        } else {
+            value_pos.push(None);
+        }
+    }
+    pos += pos % 2;
+    let mut new_offsets = Vec::with_capacity(counts.len());
+    for (&offset, &count) in offsets.iter().zip(&counts) {
+        if offset.checked_add(count).is_none_or(|end| end > t.file_len) {
+            bail!("Obrazová data přesahují konec souboru (zkrácený soubor?)");
+        }
+        new_offsets.push(pos);
+        pos = pos.checked_add(count).context("Stránka je příliš velká")?;
+    }
+    if !big && pos > u32::MAX as u64 {
+        bail!("Stránka je pro klasický TIFF příliš velká");
+    }
+    for (tag, _, _, data) in out_entries.iter_mut() {
+        if *tag == offsets_tag {
+            for &o in &new_offsets {
+                data.extend(t.encode(o, offset_size));
+            }
+        }
+    }
+
+    let file = File::create(output)
+        .with_context(|| format!("Nelze vytvořit `{}`", output.display()))?;
+    let mut w = BufWriter::new(file);
+    let le = t.little_endian;
+    w.write_all(if le { b"II" } else { b"MM" })?;
+    if big {
+        w.write_all(&t.encode(43, 2))?;
+        w.write_all(&t.encode(8, 2))?;
+        w.write_all(&t.encode(0, 2))?;
+        w.write_all(&t.encode(header_len, 8))?;
+    } else {
+        w.write_all(&t.encode(42, 2))?;
+        w.write_all(&t.encode(header_len, 4))?;
+    }
+
+    w.write_all(&t.encode(out_entries.len() as u64, count_len))?;
+    for ((tag, field_type, count, data), at) in out_entries.iter().zip(&value_pos) {
+        w.write_all(&t.encode(*tag as u64, 2))?;
+        w.write_all(&t.encode(*field_type as u64, 2))?;
+        w.write_all(&t.encode(*count, if big { 8 } else { 4 }))?;
+        match at {
+            Some(at) => w.write_all(&t.encode(*at, inline_len))?,
+            None => {
+                let mut inline = data.clone();
+                inline.resize(inline_len as usize, 0);
+                w.write_all(&inline)?;
+            }
+        }
+    }
+    w.write_all(&t.encode(0, inline_len))?; // další IFD není
+
+    let mut written = header_len + ifd_len;
+    for ((_, _, _, data), at) in out_entries.iter().zip(&value_pos) {
+        if let Some(at) = at {
+            w.write_all(&vec![0u8; (at - written) as usize])?;
+            w.write_all(data)?;
+            written = at + data.len() as u64;
+        }
+    }
+    for ((&offset, &count), &at) in offsets.iter().zip(&counts).zip(&new_offsets) {
+        w.write_all(&vec![0u8; (at - written) as usize])?;
+        t.r.seek(SeekFrom::Start(offset))?;
+        let copied = io::copy(&mut (&mut t.r).take(count), &mut w)?;
+        if copied != count {
+            bail!("Obrazová data stránky {page} jsou neúplná");
+        }
+        written = at + count;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+fn read_page<R: Read + Seek>(
+    t: &mut TiffReader<R>,
+    entries: &[IfdEntry],
+    info: &mut TiffInfo,
+) -> Result<()> {
+    let required = |tag: u16, name: &str| -> Result<&IfdEntry> {
+        find(entries, tag).with_context(|| format!("Chybí povinný tag {name}"))
+    };
+
+    info.width = t.first_value(required(IMAGE_WIDTH, "ImageWidth")?)? as u32;
+    info.height = t.first_value(required(IMAGE_LENGTH, "ImageLength")?)? as u32;
+    if info.width == 0 || info.height == 0 {
+        bail!("Nulové rozměry obrázku");
+    }
+    info.samples_per_pixel = match find(entries, SAMPLES_PER_PIXEL) {
+        Some(e) => t.first_value(e)? as u16,
+        None => 1,
+    };
+    info.bits_per_sample = match find(entries, BITS_PER_SAMPLE) {
+        Some(e) => t.values(e)?.into_iter().map(|v| v as u16).collect(),
+        None => vec![1],
+    };
+    info.extra_samples = find(entries, EXTRA_SAMPLES).map_or(0, |e| e.count as u16);
+    info.compression = compression_name(match find(entries, COMPRESSION) {
+        Some(e) => t.first_value(e)?,
+        None => 1,
+    });
+    info.photometric = photometric_name(t.first_value(required(PHOTOMETRIC, "PhotometricInterpretation")?)?);
+    info.icc_profile = find(entries, ICC_PROFILE).is_some_and(|e| e.count > 0);
+
+    let unit = match find(entries, RESOLUTION_UNIT) {
+        Some(e) => t.first_value(e)?,
+        None => 2,
+    };
+    if let (Some(x), Some(y)) = (find(entries, X_RESOLUTION), find(entries, Y_RESOLUTION)) {
+        let (x, y) = (t.rational(x)?, t.rational(y)?);
+        info.dpi = match unit {
+            2 => Some((x, y)),
+            3 => Some((x * 2.54, y * 2.54)),
+            _ => None,
+        };
+        if info.dpi.is_some_and(|(x, y)| x <= 0.0 || y <= 0.0 || !x.is_finite() || !y.is_finite()) {
+            info.dpi = None;
+        }
+    }
+
+    // Data obrázku musí ležet uvnitř souboru
+    let (offsets, counts) = match (find(entries, STRIP_OFFSETS), find(entries, STRIP_BYTE_COUNTS)) {
+        (Some(o), Some(c)) => (o, c),
+        _ => match (find(entries, TILE_OFFSETS), find(entries, TILE_BYTE_COUNTS)) {
+            (Some(o), Some(c)) => (o, c),
+            _ => bail!("Chybí offsety obrazových dat (strips / tiles)"),
+        },
+    };
+    let offsets = t.values(offsets)?;
+    let counts = t.values(counts)?;
+    if offsets.len() != counts.len() {
+        bail!("Nesouhlasí počet offsetů ({}) a délek ({}) dat", offsets.len(), counts.len());
+    }
+    for (offset, count) in offsets.iter().zip(&counts) {
+        if offset.saturating_add(*count) > t.file_len {
+            bail!("Obrazová data přesahují konec souboru (zkrácený soubor?)");
+        }
+    }
+    Ok(())
+}
+
+fn find(entries: &[IfdEntry], tag: u16) -> Option<&IfdEntry> {
+    entries.iter().find(|e| e.tag == tag)
+}
+
+impl<R: Read + Seek> TiffReader<R> {
+    fn read_ifd(&mut self, offset: u64) -> Result<(Vec<IfdEntry>, u64)> {
+        self.r.seek(SeekFrom::Start(offset))?;
+        let count = if self.big_tiff { self.u64()? } else { self.u16()? as u64 };
+        let entry_len: u64 = if self.big_tiff { 20 } else { 12 };
+        if self.span(offset, count, entry_len).is_none() {
+            bail!("IFD přesahuje konec souboru");
+        }
+
+        let mut entries = Vec::with_capacity(count.min(4096) as usize);
+        for _ in 0..count {
+            let tag = self.u16()?;
+            let field_type = self.u16()?;
+            let count = if self.big_tiff { self.u64()? } else { self.u32()? as u64 };
+            let inline_len: u64 = if self.big_tiff { 8 } else { 4 };
+            let here = self.r.stream_position()?;
+            let inline = type_size(field_type)
+                .checked_mul(count)
+                .is_some_and(|len| len <= inline_len);
+            let value_pos = if inline {
+                here
+            } else if self.big_tiff {
+                self.u64()?
+            } else {
+                self.u32()? as u64
+            };
+            self.r.seek(SeekFrom::Start(here + inline_len))?;
+            entries.push(IfdEntry {
+                tag,
+                field_type,
+                count,
+                value_pos,
+            });
+        }
+        let next = if self.big_tiff { self.u64()? } else { self.u32()? as u64 };
+        Ok((entries, next))
+    }
+
+    /// Délka `count` hodnot po `size` bajtech od `start`.
+    /// None, pokud výpočet přeteče nebo data přesahují konec souboru.
+    fn span(&self, start: u64, count: u64, size: u64) -> Option<u64> {
+        let len = count.checked_mul(size)?;
+        (start.checked_add(len)? <= self.file_len).then_some(len)
+    }
+
+    /// Celočíselné hodnoty položky (BYTE, SHORT, LONG, LONG8)
+    fn values(&mut self, e: &IfdEntry) -> Result<Vec<u64>> {
+        let size = type_size(e.field_type);
+        if !matches!(e.field_type, 1 | 3 | 4 | 13 | 16 | 18) {
+            bail!("Tag {} má nečíselný typ {}", e.tag, e.field_type);
+        }
+        if self.span(e.value_pos, e.count, size).is_none() {
+            bail!("Hodnota tagu {} přesahuje konec souboru", e.tag);
+        }
+        self.r.seek(SeekFrom::Start(e.value_pos))?;
+        let mut out = Vec::with_capacity(e.count.min(1 << 20) as usize);
+        for _ in 0..e.count {
+            out.push(match size {
+                1 => self.u8()? as u64,
+                2 => self.u16()? as u64,
+                4 => self.u32()? as u64,
+                _ => self.u64()?,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Surové bajty hodnoty položky (v pořadí bajtů souboru)
+    fn raw(&mut self, e: &IfdEntry) -> Result<Vec<u8>> {
+        let Some(len) = self.span(e.value_pos, e.count, type_size(e.field_type)) else {
+            bail!("Hodnota tagu {} přesahuje konec souboru", e.tag);
+        };
+        self.r.seek(SeekFrom::Start(e.value_pos))?;
+        let mut buf = vec![0u8; len as usize];
+        self.r.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Číslo jako `size` bajtů v pořadí bajtů souboru
+    fn encode(&self, value: u64, size: u64) -> Vec<u8> {
+        let bytes = if self.little_endian { value.to_le_bytes() } else { value.to_be_bytes() };
+        if self.little_endian {
+            bytes[..size as usize].to_vec()
+        } else {
+            bytes[8 - size as usize..].to_vec()
+        }
+    }
+
+    fn first_value(&mut self, e: &IfdEntry) -> Result<u64> {
+        if e.count == 0 {
+            bail!("Tag {} nemá hodnotu", e.tag);
+        }
+        Ok(self.values(&IfdEntry { count: 1, ..*e })?[0])
+    }
+
+    /// RATIONAL (5) jako f64
+    fn rational(&mut self, e: &IfdEntry) -> Result<f64> {
+        if e.field_type != 5 || e.count == 0 {
+            return Ok(self.first_value(e)? as f64);
+        }
+        self.r.seek(SeekFrom::Start(e.value_pos))?;
+        let num = self.u32()? as f64;
+        let den = self.u32()? as f64;
+        Ok(if den == 0.0 { 0.0 } else { num / den })
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        let mut b = [0u8; 1];
+        self.r.read_exact(&mut b)?;
+        Ok(b[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let mut b = [0u8; 2];
+        self.r.read_exact(&mut b)?;
+        Ok(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut b = [0u8; 4];
+        self.r.read_exact(&mut b)?;
+        Ok(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut b = [0u8; 8];
+        self.r.read_exact(&mut b)?;
+        Ok(if self.little_endian { u64::from_le_bytes(b) } else { u64::from_be_bytes(b) })
+    }
+}
+
+/// Velikost jedné hodnoty daného typu v bajtech
+fn type_size(field_type: u16) -> u64 {
+    match field_type {
+        1 | 2 | 6 | 7 => 1,
+        3 | 8 => 2,
+        4 | 9 | 11 | 13 => 4,
+        5 | 10 | 12 | 16 | 17 | 18 => 8,
+        _ => 1,
+    }
+}
+
+fn compression_name(code: u64) -> String {
+    match code {
+        1 => "none",
+        2 => "ccitt-rle",
+        3 => "ccitt-g3",
+        4 => "ccitt-g4",
+        5 => "lzw",
+        6 => "ojpeg",
+        7 => "jpeg",
+        8 | 32946 => "deflate",
+        32773 => "packbits",
+        34712 => "jpeg2000",
+        34925 => "lzma",
+        50000 => "zstd",
+        50001 => "webp",
+        other => return format!("neznámá ({other})"),
+    }
+    .to_string()
+}
+
+fn photometric_name(code: u64) -> String {
+    match code {
+        0 => "min-is-white",
+        1 => "min-is-black",
+        2 => "rgb",
+        3 => "palette",
+        4 => "mask",
+        5 => "cmyk",
+        6 => "ycbcr",
+        8 => "cielab",
+        9 => "icclab",
+        10 => "itulab",
+        other => return format!("neznámý ({other})"),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Klasický little-endian TIFF, stránka = jeden řádek 8bit šedi
+    /// (data, deklarovaná délka stripu)
+    fn tiff(pages: &[(&[u8], u32)]) -> Vec<u8> {
+        let mut out = b"II*\0\0\0\0\0".to_vec();
+        let mut link = 4;
+        for &(data, byte_count) in pages {
+            let data_pos = out.len() as u32;
+            out.extend(data);
+            if out.len() % 2 == 1 {
+                out.push(0);
+            }
+            let ifd = out.len() as u32;
+            out[link..link + 4].copy_from_slice(&ifd.to_le_bytes());
+            let entries: [(u16, u16, u32); 7] = [
+                (IMAGE_WIDTH, 4, data.len() as u32),
+                (IMAGE_LENGTH, 4, 1),
+                (BITS_PER_SAMPLE, 3, 8),
+                (PHOTOMETRIC, 3, 1),
+                (STRIP_OFFSETS, 4, data_pos),
+                (SAMPLES_PER_PIXEL, 3, 1),
+                (STRIP_BYTE_COUNTS, 4, byte_count),
+            ];
+            out.extend((entries.len() as u16).to_le_bytes());
+            for (tag, field_type, value) in entries {
+                out.extend(tag.to_le_bytes());
+                out.extend(field_type.to_le_bytes());
+                out.extend(1u32.to_le_bytes());
+                out.extend(value.to_le_bytes());
+            }
+            link = out.len();
+            out.extend([0; 4]);
+        }
+        out
+    }
+
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("lazyproarc-{}-{name}", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn reader(data: Vec<u8>, big_tiff: bool) -> TiffReader<Cursor<Vec<u8>>> {
+        TiffReader {
+            file_len: data.len() as u64,
+            r: Cursor::new(data),
+            little_endian: true,
+            big_tiff,
+        }
+    }
+
+    #[test]
+    fn read_ifd_reads_inline_and_offset_values() {
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend(2u16.to_le_bytes());
+        // SHORT ×2 inline
+        data.extend([0x02, 0x01, 3, 0, 2, 0, 0, 0, 7, 0, 9, 0]);
+        // LONG ×2 mimo IFD (za IFD, offset 38)
+        data.extend([0x11, 0x01, 4, 0, 2, 0, 0, 0, 38, 0, 0, 0]);
+        data.extend([0; 4]);
+        data.extend([1, 0, 0, 0, 2, 0, 0, 0]);
+        let mut t = reader(data, false);
+        let (entries, next) = t.read_ifd(8).unwrap();
+        assert_eq!(next, 0);
+        assert_eq!(t.values(&entries[0]).unwrap(), vec![7, 9]);
+        assert_eq!(t.values(&entries[1]).unwrap(), vec![1, 2]);
+        assert_eq!(t.raw(&entries[1]).unwrap(), vec![1, 0, 0, 0, 2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn read_ifd_rejects_malformed() {
+        // Počet položek přesahuje soubor
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend(0xFFFFu16.to_le_bytes());
+        data.extend([0; 12]);
+        assert!(reader(data, false).read_ifd(8).is_err());
+
+        // BigTIFF: počet × délka položky přeteče u64
+        let mut data = b"II+\0\x08\0\0\0".to_vec();
+        data.extend(8u64.to_le_bytes());
+        data.extend(u64::MAX.to_le_bytes());
+        assert!(reader(data, true).read_ifd(16).is_err());
+
+        // Zkrácený soubor uprostřed IFD
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend(1u16.to_le_bytes());
+        data.extend([0x00, 0x01, 4, 0]);
+        assert!(reader(data, false).read_ifd(8).is_err());
+    }
+
+    #[test]
+    fn values_reject_huge_count() {
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend(1u16.to_le_bytes());
+        data.extend([0x11, 0x01, 4, 0]);
+        data.extend(u32::MAX.to_le_bytes());
+        data.extend(8u32.to_le_bytes());
+        data.extend([0; 4]);
+        let mut t = reader(data, false);
+        let (entries, _) = t.read_ifd(8).unwrap();
+        assert!(t.values(&entries[0]).is_err());
+        assert!(t.raw(&entries[0]).is_err());
+
+        // Počet × velikost typu přeteče u64
+        let entry = IfdEntry {
+            tag: STRIP_OFFSETS,
+            field_type: 16,
+            count: u64::MAX,
+            value_pos: 8,
+        };
+        assert!(t.values(&entry).is_err());
+        assert!(t.raw(&entry).is_err());
+    }
+
+    #[test]
+    fn reads_every_page() {
+        let path = temp_file("pages.tif", &tiff(&[(&[1, 2, 3], 3), (&[4, 5], 2)]));
+        let pages = read_tiff_pages(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let widths: Vec<_> = pages.iter().map(|p| p.as_ref().unwrap().width).collect();
+        assert_eq!(widths, vec![3, 2]);
+        let first = pages[0].as_ref().unwrap();
+        assert_eq!(first.pages, 2);
+        assert_eq!(first.photometric, "min-is-black");
+        assert_eq!(first.bits_per_sample, vec![8]);
+    }
+
+    #[test]
+    fn check_tiff_groups_issues_by_page() {
+        let path = temp_file("check.tif", &tiff(&[(&[1, 2], 2), (&[3, 4], 2), (&[5, 6], 200)]));
+        let report = check_tiff(&path, &PreflightRules::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let messages: Vec<_> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(messages.contains(&"stránky 1–2: chybí rozlišení (DPI)"), "{messages:?}");
+        assert!(messages.contains(&"stránky 1–2: chybí ICC profil"), "{messages:?}");
+        let corrupt = report.issues.iter().find(|i| i.rule == "corrupt").unwrap();
+        assert!(corrupt.message.starts_with("stránka 3: "));
+        assert_eq!(corrupt.action, RuleAction::Block);
+        assert_eq!(report.info.unwrap().width, 2);
+    }
+
+    #[test]
+    fn check_tiff_rejects_garbage() {
+        let path = temp_file("garbage.tif", b"not a tiff");
+        let report = check_tiff(&path, &PreflightRules::default());
+        std::fs::remove_file(&path).unwrap();
+        assert!(report.info.is_none());
+        assert_eq!(report.issues[0].rule, "corrupt");
+    }
+
+    #[test]
+    fn extract_tiff_page_copies_one_page() {
+        let path = temp_file("extract.tif", &tiff(&[(&[1, 2, 3], 3), (&[4, 5, 6, 7, 8], 5)]));
+        let output = temp_file("extract-2.tif", b"");
+        extract_tiff_page(&path, 2, &output).unwrap();
+        let missing = extract_tiff_page(&path, 3, &output).is_err()
+            && extract_tiff_page(&path, 0, &output).is_err();
+        std::fs::remove_file(&path).unwrap();
+
+        let info = read_tiff_info(&output).unwrap();
+        let data = std::fs::read(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(missing);
+        assert_eq!((info.width, info.pages), (5, 1));
+        assert!(data.windows(5).any(|w| w == [4, 5, 6, 7, 8]));
+    }
+
+    #[test]
+    fn extract_tiff_page_rejects_truncated_strip() {
+        let path = temp_file("truncated.tif", &tiff(&[(&[1, 2, 3], 1000)]));
+        let output = std::env::temp_dir().join(format!("lazyproarc-{}-truncated-1.tif", std::process::id()));
+        let result = extract_tiff_page(&path, 1, &output);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn page_list_joins_ranges() {
+        assert_eq!(page_list(&[2]), "stránka 2");
+        assert_eq!(page_list(&[1, 2, 3, 5]), "stránky 1–3, 5");
+        assert_eq!(page_list(&[1, 3, 4]), "stránky 1, 3–4");
+    }
+}
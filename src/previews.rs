@@ -1,16 +1,17 @@
 use anyhow::{anyhow, Context, Result};
 use image::GenericImageView;
 use serde::Deserialize;
-use webp;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::worker::JobLog;
+
+use crate::preflight::extract_tiff_page;
+
 /// Struktura jednoho souboru v manifestu
 #[derive(Debug, Deserialize)]
 struct ManifestFile {
     path: String,
-    size: u64,
-    blake3: String,
 }
 
 /// Jedna stránka v manifestu
@@ -21,15 +22,14 @@ struct ManifestPage {
     // Podpora pro starý i nový název
     #[serde(alias = "original_tiff", rename = "tiff")]
     tiff: ManifestFile,
-    
-    #[serde(rename = "ac_jp2")]
-    ac_jp2: Option<ManifestFile>,
-    
-    #[serde(rename = "uc_jp2")]
-    uc_jp2: Option<ManifestFile>,
-    
-    txt: Option<ManifestFile>,
-    alto: Option<ManifestFile>,
+
+    /// Formát zdroje ("tiff", "jpeg", "png", "jp2"); starší manifesty ho nemají
+    #[serde(default)]
+    original_format: Option<String>,
+
+    /// Stránka vícestránkového TIFFu (od 1)
+    #[serde(default)]
+    source_page: Option<usize>,
 }
 
 /// Minimalní manifest pro náhledy
@@ -38,8 +38,8 @@ struct Manifest {
     pages: Vec<ManifestPage>,
 }
 
-/// Vygeneruje WebP náhledy přímo z TIFF souborů
-pub fn generate_webp_previews(logs_dir: &Path) -> Result<()> {
+/// Vygeneruje WebP náhledy přímo z TIFF souborů; chyby stránek a souhrn jdou do `logs`
+pub fn generate_webp_previews(logs_dir: &Path, logs: &mut JobLog) -> Result<()> {
     let manifest_path = logs_dir.join("manifest.json");
 
     if !manifest_path.exists() {
@@ -57,51 +57,73 @@ pub fn generate_webp_previews(logs_dir: &Path) -> Result<()> {
 
     let mut generated = 0usize;
     let mut errors = 0usize;
+    let mut skipped_jp2 = 0usize;
 
     for page in &manifest.pages {
         // Používáme přímo TIFF soubory z manifestu
         let tiff_path = PathBuf::from(&page.tiff.path);
         
         if !tiff_path.exists() {
-            eprintln!(
+            logs.push(format!(
                 "WebP: TIFF pro stránku {} neexistuje: {} – přeskočeno.",
                 page.index,
                 tiff_path.display()
-            );
+            ));
             errors += 1;
             continue;
         }
 
+        // JP2 zdroj knihovna image nedekóduje
+        if page.original_format.as_deref() == Some("jp2") {
+            skipped_jp2 += 1;
+            continue;
+        }
+
         // Cíl: page_XXXX.webp v logs_dir
         let webp_name = format!("page_{}.webp", page.index);
         let webp_path = logs_dir.join(&webp_name);
 
         if webp_path.exists() {
-            eprintln!(
-                "WebP: Náhled pro stránku {} už existuje – přeskočeno.",
-                page.index
-            );
             continue;
         }
 
-        // Spustíme konverzi TIFF → WebP
-        match convert_tiff_to_webp(&tiff_path, &webp_path) {
-            Ok(()) => {
-                generated += 1;
-                eprintln!("WebP: Strana {} → {} (z TIFF)", page.index, webp_path.display());
+        // Stránku vícestránkového TIFFu nejdřív vyjmeme do dočasného souboru
+        let page_tiff = match page.source_page {
+            Some(n) => {
+                let tmp = logs_dir.join(format!("page_{}.tmp.tif", page.index));
+                if let Err(e) = extract_tiff_page(&tiff_path, n, &tmp) {
+                    logs.push(format!("WebP: Nelze vyjmout stránku {} z {}: {}", n, tiff_path.display(), e));
+                    let _ = fs::remove_file(&tmp);
+                    errors += 1;
+                    continue;
+                }
+                Some(tmp)
             }
+            None => None,
+        };
+
+        // Spustíme konverzi TIFF → WebP
+        let converted = convert_tiff_to_webp(page_tiff.as_deref().unwrap_or(&tiff_path), &webp_path);
+        if let Some(tmp) = &page_tiff {
+            let _ = fs::remove_file(tmp);
+        }
+        match converted {
+            Ok(()) => generated += 1,
             Err(e) => {
-                eprintln!("WebP: Chyba u stránky {}: {}", page.index, e);
+                logs.push(format!("WebP: Chyba u stránky {}: {}", page.index, e));
                 errors += 1;
                 // Pokračujeme s další stránkou
             }
         }
     }
 
-    if generated == 0 {
-        eprintln!("WebP: Nepodařilo se vytvořit žádný náhled. Chyb: {}", errors);
+    if skipped_jp2 > 0 {
+        logs.push(format!("WebP: {} stránek má zdroj JP2 – náhledy se nevytváří.", skipped_jp2));
+    }
+    if generated == 0 && errors > 0 {
+        logs.push(format!("WebP: Nepodařilo se vytvořit žádný náhled. Chyb: {}", errors));
     } else {
-        eprintln!("WebP: Hotovo, vytvořeno {} náhledů, chyb: {}", generated, errors);
+        logs.push(format!("WebP: Vytvořeno {} náhledů, chyb: {}", generated, errors));
     }
 
     Ok(())
@@ -113,12 +135,6 @@ fn convert_tiff_to_webp(tiff_path: &Path, webp_path: &Path) -> Result<()> {
     let img = image::open(tiff_path)
         .with_context(|| format!("Nelze otevřít TIFF soubor: {}", tiff_path.display()))?;
 
-    eprintln!("DEBUG: Načten TIFF '{}': {}x{}", 
-        tiff_path.file_name().unwrap_or_default().to_string_lossy(),
-        img.width(), 
-        img.height()
-    );
-
     // Zmenšení obrázku pro náhled (max 1024px na delší straně)
     let max_dimension = 1024;
     let (width, height) = img.dimensions();
@@ -131,17 +147,13 @@ fn convert_tiff_to_webp(tiff_path: &Path, webp_path: &Path) -> Result<()> {
             ((max_dimension as f32 * ratio) as u32, max_dimension)
         };
         
-        let resized = img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3);
-        eprintln!("DEBUG: Zmenšeno na: {}x{}", new_width, new_height);
-        resized
+        img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3)
     } else {
-        eprintln!("DEBUG: Ponechána původní velikost");
         img
     };
 
     // Konverze na RGB(A) pro WebP encoder
     let rgb_img = resized_img.to_rgba8();
-    eprintln!("DEBUG: Konvertováno na RGBA: {}x{}", rgb_img.width(), rgb_img.height());
     
     // Vytvoření WebP encoderu
     let encoder = webp::Encoder::from_rgba(&rgb_img, rgb_img.width(), rgb_img.height());
@@ -152,9 +164,8 @@ fn convert_tiff_to_webp(tiff_path: &Path, webp_path: &Path) -> Result<()> {
     // Získání dat jako &[u8] - TOTO JE KLÍČOVÁ OPRAVA
     let webp_bytes = webp_data.as_ref();
     
-    // DEBUG: Zkontroluj velikost dat
+    // Kontrola velikosti dat
     let data_len = webp_bytes.len();
-    eprintln!("DEBUG: WebP data velikost: {} bytes", data_len);
     
     if data_len == 0 {
         return Err(anyhow!("WebP encoder vrátil prázdná data (0 bytes)"));
@@ -163,8 +174,6 @@ fn convert_tiff_to_webp(tiff_path: &Path, webp_path: &Path) -> Result<()> {
     // Uložení WebP souboru
     std::fs::write(webp_path, webp_bytes)
         .with_context(|| format!("Nelze uložit WebP soubor: {}", webp_path.display()))?;
-        
-    eprintln!("DEBUG: WebP úspěšně uložen: {} bytes do {}", data_len, webp_path.display());
 
     // Ověření, že soubor skutečně existuje a má data
     match std::fs::metadata(webp_path) {
@@ -173,7 +182,6 @@ fn convert_tiff_to_webp(tiff_path: &Path, webp_path: &Path) -> Result<()> {
             if file_size == 0 {
                 return Err(anyhow!("WebP soubor byl vytvořen, ale má 0 bytes"));
             }
-            eprintln!("DEBUG: Ověřeno: WebP soubor má {} bytes", file_size);
         }
         Err(e) => {
             return Err(anyhow!("Nelze ověřit vytvořený WebP soubor: {}", e));
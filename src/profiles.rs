@@ -0,0 +1,203 @@
+// src/profiles.rs
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Výchozí soubor s profily (v pracovním adresáři), pokud není zadán `--profiles`
+pub const DEFAULT_PROFILES_FILE: &str = "profiles.json";
+
+/// Název vestavěného profilu (původní pevně zadané parametry)
+pub const BUILTIN_PROFILE: &str = "ndk-default";
+
+/// Parametry jednoho volání grk_compress.
+/// Volitelné položky se při `None` / `false` vůbec nepředávají.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GrokParams {
+    /// -t, velikost dlaždice ("4096,4096")
+    pub tile_size: String,
+    /// -p, pořadí progrese (RPCL, LRCP, …)
+    pub progression: String,
+    /// -n, počet rozlišení
+    pub resolutions: u32,
+    /// -c, velikosti precinktů
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precincts: Option<String>,
+    /// -b, velikost code-blocku ("64,64")
+    pub code_block: String,
+    /// -r, kompresní poměry vrstev
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rates: Option<String>,
+    /// -I, ztrátová (ireverzibilní) transformace
+    #[serde(default)]
+    pub irreversible: bool,
+    /// -X, TLM markery
+    #[serde(default)]
+    pub tlm: bool,
+    /// -M, režim code-blocků
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    /// -S, SOP markery
+    #[serde(default)]
+    pub sop: bool,
+    /// -E, EPH markery
+    #[serde(default)]
+    pub eph: bool,
+    /// -u, dělení tile-parts (R, L, C)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tile_parts: Option<String>,
+    /// -H, počet vláken Groku
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    /// Další argumenty předané beze změny
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+}
+
+impl GrokParams {
+    /// Argumenty pro grk_compress (bez -i / -o)
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(ref rates) = self.rates {
+            args.extend(["-r".to_string(), rates.clone()]);
+        }
+        if self.irreversible {
+            args.push("-I".to_string());
+        }
+        args.extend(["-t".to_string(), self.tile_size.clone()]);
+        args.extend(["-p".to_string(), self.progression.clone()]);
+        args.extend(["-n".to_string(), self.resolutions.to_string()]);
+        if let Some(ref precincts) = self.precincts {
+            args.extend(["-c".to_string(), precincts.clone()]);
+        }
+        args.extend(["-b".to_string(), self.code_block.clone()]);
+        if self.tlm {
+            args.push("-X".to_string());
+        }
+        if let Some(mode) = self.mode {
+            args.extend(["-M".to_string(), mode.to_string()]);
+        }
+        if self.sop {
+            args.push("-S".to_string());
+        }
+        if self.eph {
+            args.push("-E".to_string());
+        }
+        if let Some(ref tile_parts) = self.tile_parts {
+            args.extend(["-u".to_string(), tile_parts.clone()]);
+        }
+        if let Some(threads) = self.threads {
+            args.extend(["-H".to_string(), threads.to_string()]);
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+}
+
+/// Pojmenovaný profil kódování: parametry pro AC (master) a UC (user) JP2
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncodingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "builtin_master")]
+    pub master: GrokParams,
+    #[serde(default = "builtin_user")]
+    pub user: GrokParams,
+}
+
+const NDK_PRECINCTS: &str = "[256,256],[256,256],[128,128],[128,128],[128,128],[128,128]";
+
+fn builtin_master() -> GrokParams {
+    GrokParams {
+        tile_size: "4096,4096".to_string(),
+        progression: "RPCL".to_string(),
+        resolutions: 6,
+        precincts: Some(NDK_PRECINCTS.to_string()),
+        code_block: "64,64".to_string(),
+        rates: None,
+        irreversible: false,
+        tlm: true,
+        mode: Some(1),
+        sop: true,
+        eph: true,
+        tile_parts: Some("R".to_string()),
+        threads: None,
+        extra_args: Vec::new(),
+    }
+}
+
+fn builtin_user() -> GrokParams {
+    GrokParams {
+        tile_size: "1024,1024".to_string(),
+        progression: "RPCL".to_string(),
+        resolutions: 6,
+        precincts: Some(NDK_PRECINCTS.to_string()),
+        code_block: "64,64".to_string(),
+        rates: Some("362,256,181,128,90,64,45,32,22,16,11,8".to_string()),
+        irreversible: true,
+        tlm: true,
+        mode: Some(1),
+        sop: false,
+        eph: false,
+        tile_parts: Some("R".to_string()),
+        threads: Some(4),
+        extra_args: Vec::new(),
+    }
+}
+
+/// Vestavěný profil – NDK parametry, které program používal odjakživa
+pub fn builtin_profile() -> EncodingProfile {
+    EncodingProfile {
+        name: BUILTIN_PROFILE.to_string(),
+        description: "NDK výchozí (AC bezeztrátově, UC ztrátově)".to_string(),
+        master: builtin_master(),
+        user: builtin_user(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfilesFile {
+    profiles: Vec<EncodingProfile>,
+}
+
+/// Načte profily: vestavěný profil + profily ze souboru.
+/// Profil ze souboru se stejným názvem vestavěný profil přepíše.
+/// Bez `path` se zkusí `profiles.json`; pokud neexistuje, zůstane jen vestavěný.
+pub fn load_profiles(path: Option<&Path>) -> Result<Vec<EncodingProfile>> {
+    let mut profiles = vec![builtin_profile()];
+
+    let path = match path {
+        Some(p) => p.to_path_buf(),
+        None => {
+            let default = Path::new(DEFAULT_PROFILES_FILE);
+            if !default.exists() {
+                return Ok(profiles);
+            }
+            default.to_path_buf()
+        }
+    };
+
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Nelze načíst profily `{}`", path.display()))?;
+    let file: ProfilesFile = serde_json::from_str(&text)
+        .with_context(|| format!("Neplatný soubor s profily `{}`", path.display()))?;
+
+    for profile in file.profiles {
+        if profile.name.trim().is_empty() {
+            bail!("Profil bez názvu v `{}`", path.display());
+        }
+        match profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// Najde profil podle názvu
+pub fn find_profile<'a>(profiles: &'a [EncodingProfile], name: &str) -> Option<&'a EncodingProfile> {
+    profiles.iter().find(|p| p.name == name)
+}
@@ -0,0 +1,469 @@
+// src/quality.rs
+// Odhad kvality OCR: statistiky konfidence slov (atribut WC elementů
+// String v ALTO) po stránkách a za dávku. Stránky s průměrnou konfidencí
+// pod prahem se označí k ruční kontrole (TUI, HTML report, lazyalto).
+// Druhý odhad je podíl slov z TXT nalezených ve slovníku jazyka OCR
+// (konfidence Tesseractu u historických tisků často přeceňuje).
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+/// Výchozí práh konfidence pro ruční kontrolu stránky
+pub const DEFAULT_REVIEW_THRESHOLD: f64 = 0.7;
+
+/// Výchozí práh shody se slovníkem pro filtr stránek v TUI
+pub const DEFAULT_DICTIONARY_THRESHOLD: f64 = 0.7;
+
+/// Konfidence slov jedné stránky
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageConfidence {
+    /// Počet slov s konfidencí (String s atributem WC)
+    pub words: usize,
+    pub mean: f64,
+    pub median: f64,
+    /// Podíl slov s konfidencí pod prahem (0–1)
+    pub low_share: f64,
+    /// Průměrná konfidence je pod prahem – stránku zkontrolovat ručně
+    pub needs_review: bool,
+}
+
+/// Konfidence slov celé dávky
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchConfidence {
+    /// Práh pro slova s nízkou konfidencí a stránky ke kontrole
+    pub threshold: f64,
+    pub words: usize,
+    /// Průměr přes všechna slova dávky (vážený počtem slov na stránce)
+    pub mean: f64,
+    pub median: f64,
+    pub low_share: f64,
+    /// Indexy stránek k ruční kontrole
+    pub review_pages: Vec<String>,
+}
+
+/// Práh konfidence z příkazové řádky (0–1)
+pub fn parse_threshold(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(v) if (0.0..=1.0).contains(&v) => Ok(v),
+        _ => Err(format!("`{s}` není práh 0–1")),
+    }
+}
+
+/// Konfidence všech slov (WC) v ALTO souboru v pořadí dokumentu
+pub fn read_word_confidences(path: &Path) -> Result<Vec<f64>> {
+    let xml = fs::read_to_string(path)
+        .with_context(|| format!("Nelze načíst ALTO `{}`", path.display()))?;
+    let mut reader = Reader::from_str(&xml);
+    let mut values = Vec::new();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| anyhow!("Chybné XML na pozici {}: {e}", reader.error_position()))?;
+        let e = match event {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+        if e.local_name().as_ref() != b"String" {
+            continue;
+        }
+        for a in e.attributes() {
+            let a = a?;
+            if a.key.local_name().as_ref() == b"WC"
+                && let Ok(wc) = a.unescape_value()?.trim().parse::<f64>()
+            {
+                values.push(wc.clamp(0.0, 1.0));
+            }
+        }
+    }
+    Ok(values)
+}
+
+impl PageConfidence {
+    pub fn from_values(values: &[f64], threshold: f64) -> Self {
+        let (mean, median, low_share) = summary(values, threshold);
+        PageConfidence {
+            words: values.len(),
+            mean,
+            median,
+            low_share,
+            // Stránka bez slov (prázdná) se nekontroluje
+            needs_review: !values.is_empty() && mean < threshold,
+        }
+    }
+}
+
+impl BatchConfidence {
+    /// `values` = konfidence všech slov dávky, `review_pages` = stránky pod prahem
+    pub fn from_values(values: &[f64], threshold: f64, review_pages: Vec<String>) -> Self {
+        let (mean, median, low_share) = summary(values, threshold);
+        BatchConfidence {
+            threshold,
+            words: values.len(),
+            mean,
+            median,
+            low_share,
+            review_pages,
+        }
+    }
+}
+
+/// Průměr, medián a podíl hodnot pod prahem, zaokrouhlené na 4 místa
+fn summary(values: &[f64], threshold: f64) -> (f64, f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    };
+    let low = values.iter().filter(|&&v| v < threshold).count() as f64 / n;
+    (round4(mean), round4(median), round4(low))
+}
+
+fn round4(v: f64) -> f64 {
+    (v * 10_000.0).round() / 10_000.0
+}
+
+/// Shoda slov stránky se slovníkem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryHitRate {
+    /// Počet slov v TXT (souvislé úseky písmen)
+    pub words: usize,
+    /// Z toho nalezeno ve slovníku
+    pub hits: usize,
+    /// hits / words (0–1), u prázdné stránky 0
+    pub hit_rate: f64,
+}
+
+/// Shoda se slovníkem za celou dávku
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchDictionary {
+    /// Jazyky OCR se slovníkem → cesta ke slovníku
+    pub wordlists: BTreeMap<String, String>,
+    /// Jazyky OCR bez nastaveného slovníku
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_languages: Vec<String>,
+    pub words: usize,
+    pub hits: usize,
+    pub hit_rate: f64,
+}
+
+/// Slovník `jazyk=soubor` z příkazové řádky (lze opakovat)
+pub fn parse_wordlist(s: &str) -> Result<(String, PathBuf), String> {
+    match s.split_once('=') {
+        Some((lang, path)) if !lang.trim().is_empty() && !path.trim().is_empty() => {
+            Ok((lang.trim().to_string(), PathBuf::from(path.trim())))
+        }
+        _ => Err(format!("`{s}` není ve tvaru jazyk=soubor (např. ces=slova_ces.txt)")),
+    }
+}
+
+/// Sjednocené slovníky jazyků OCR dávky (`ces+eng` → ces a eng)
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    wordlists: Vec<(String, PathBuf)>,
+    missing: Vec<String>,
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Načte slovníky pro jazyky v `lang`. None, pokud žádný jazyk slovník nemá.
+    /// Soubor: jedno slovo na řádek (případně s četností za mezerou), `#` = komentář.
+    pub fn load(wordlists: &[(String, PathBuf)], lang: &str) -> Result<Option<Self>> {
+        let mut dictionary = Dictionary {
+            wordlists: Vec::new(),
+            missing: Vec::new(),
+            words: HashSet::new(),
+        };
+        for code in lang.split('+').map(str::trim).filter(|c| !c.is_empty()) {
+            let Some((_, path)) = wordlists.iter().find(|(l, _)| l == code) else {
+                dictionary.missing.push(code.to_string());
+                continue;
+            };
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("Nelze načíst slovník `{}` ({code})", path.display()))?;
+            for line in text.lines() {
+                let line = line.trim();
+                if line.starts_with('#') {
+                    continue;
+                }
+                if let Some(word) = line.split_whitespace().next() {
+                    dictionary.words.insert(word.to_lowercase());
+                }
+            }
+            dictionary.wordlists.push((code.to_string(), path.clone()));
+        }
+        Ok((!dictionary.wordlists.is_empty()).then_some(dictionary))
+    }
+
+    pub fn hit_rate(&self, text: &str) -> DictionaryHitRate {
+        let mut words = 0;
+        let mut hits = 0;
+        for token in tokenize(text) {
+            words += 1;
+            if self.words.contains(&token) {
+                hits += 1;
+            }
+        }
+        DictionaryHitRate {
+            words,
+            hits,
+            hit_rate: ratio(hits, words),
+        }
+    }
+
+    /// Souhrn dávky ze stránkových výsledků
+    pub fn summarize(&self, pages: &[&DictionaryHitRate]) -> BatchDictionary {
+        let words = pages.iter().map(|p| p.words).sum();
+        let hits = pages.iter().map(|p| p.hits).sum();
+        BatchDictionary {
+            wordlists: self
+                .wordlists
+                .iter()
+                .map(|(lang, path)| (lang.clone(), path.to_string_lossy().to_string()))
+                .collect(),
+            missing_languages: self.missing.clone(),
+            words,
+            hits,
+            hit_rate: ratio(hits, words),
+        }
+    }
+}
+
+/// Slova textu malými písmeny: souvislé úseky písmen; slovo rozdělené
+/// na konci řádku spojovníkem (`-`, `¬`) se spojí
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    let mut chars = text.chars().peekable();
+    let mut current = String::new();
+    std::iter::from_fn(move || {
+        while let Some(c) = chars.next() {
+            if c.is_alphabetic() {
+                current.extend(c.to_lowercase());
+                continue;
+            }
+            if matches!(c, '-' | '¬') && !current.is_empty() && chars.peek() == Some(&'\n') {
+                chars.next();
+                continue;
+            }
+            if !current.is_empty() {
+                return Some(std::mem::take(&mut current));
+            }
+        }
+        (!current.is_empty()).then(|| std::mem::take(&mut current))
+    })
+}
+
+fn ratio(hits: usize, words: usize) -> f64 {
+    if words == 0 {
+        0.0
+    } else {
+        round4(hits as f64 / words as f64)
+    }
+}
+
+/// Statistiky kvality OCR načtené z manifest.json dávky (pro TUI)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BatchQuality {
+    #[serde(default)]
+    pub ocr_confidence: Option<BatchConfidence>,
+    #[serde(default)]
+    pub dictionary: Option<BatchDictionary>,
+    #[serde(default)]
+    pub pages: Vec<PageQuality>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PageQuality {
+    pub index: String,
+    #[serde(default)]
+    pub ocr_confidence: Option<PageConfidence>,
+    #[serde(default)]
+    pub dictionary: Option<DictionaryHitRate>,
+}
+
+impl BatchQuality {
+    /// Načte statistiky z `*_logs/manifest.json` (None, pokud manifest není)
+    pub fn load(logs_dir: &Path) -> Option<Self> {
+        let text = fs::read_to_string(logs_dir.join("manifest.json")).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Stránky k ruční kontrole s jejich konfidencí
+    pub fn review_pages(&self) -> impl Iterator<Item = (&str, &PageConfidence)> {
+        self.pages.iter().filter_map(|p| {
+            p.ocr_confidence
+                .as_ref()
+                .filter(|c| c.needs_review)
+                .map(|c| (p.index.as_str(), c))
+        })
+    }
+}
+
+/// Řazení / filtr stránek podle kvality OCR v detailu dávky (TUI)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PageSort {
+    #[default]
+    Index,
+    /// Od nejnižší shody se slovníkem
+    HitRate,
+    /// Od nejnižší průměrné konfidence
+    Confidence,
+    /// Jen stránky se shodou se slovníkem pod prahem, od nejnižší
+    LowHitRate,
+}
+
+impl PageSort {
+    pub fn label(self) -> &'static str {
+        match self {
+            PageSort::Index => "podle indexu",
+            PageSort::HitRate => "od nejnižší shody se slovníkem",
+            PageSort::Confidence => "od nejnižší konfidence",
+            PageSort::LowHitRate => "jen shoda se slovníkem pod prahem",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            PageSort::Index => PageSort::HitRate,
+            PageSort::HitRate => PageSort::Confidence,
+            PageSort::Confidence => PageSort::LowHitRate,
+            PageSort::LowHitRate => PageSort::Index,
+        }
+    }
+}
+
+impl BatchQuality {
+    /// Stránky se statistikami seřazené / vyfiltrované podle `sort`
+    pub fn sorted_pages(&self, sort: PageSort, threshold: f64) -> Vec<&PageQuality> {
+        let hit_rate = |p: &PageQuality| p.dictionary.as_ref().map(|d| d.hit_rate);
+        let mean = |p: &PageQuality| p.ocr_confidence.as_ref().map(|c| c.mean);
+        let mut pages: Vec<&PageQuality> = self
+            .pages
+            .iter()
+            .filter(|p| p.dictionary.is_some() || p.ocr_confidence.is_some())
+            .collect();
+        match sort {
+            PageSort::Index => {}
+            PageSort::HitRate => pages.sort_by(|a, b| cmp_missing_last(hit_rate(a), hit_rate(b))),
+            PageSort::Confidence => pages.sort_by(|a, b| cmp_missing_last(mean(a), mean(b))),
+            PageSort::LowHitRate => {
+                pages.retain(|p| {
+                    p.dictionary
+                        .as_ref()
+                        .is_some_and(|d| d.words > 0 && d.hit_rate < threshold)
+                });
+                pages.sort_by(|a, b| cmp_missing_last(hit_rate(a), hit_rate(b)));
+            }
+        }
+        pages
+    }
+}
+
+/// Vzestupně; stránky bez hodnoty na konec
+fn cmp_missing_last(a: Option<f64>, b: Option<f64>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, data: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("lazyproarc-{}-{name}", std::process::id()));
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn tokenize_joins_hyphenated_words() {
+        let words: Vec<_> = tokenize("Pří-\nliš žluť¬\noučký, kůň 1920 a-b -\nx").collect();
+        assert_eq!(words, vec!["příliš", "žluťoučký", "kůň", "a", "b", "x"]);
+        assert_eq!(tokenize("").count(), 0);
+        assert_eq!(tokenize("-\n12 ,.").count(), 0);
+    }
+
+    #[test]
+    fn summary_of_values() {
+        assert_eq!(summary(&[], 0.7), (0.0, 0.0, 0.0));
+        assert_eq!(summary(&[0.9, 0.5, 0.6], 0.7), (0.6667, 0.6, 0.6667));
+        assert_eq!(summary(&[0.2, 1.0, 0.8, 0.4], 0.5), (0.6, 0.6, 0.5));
+    }
+
+    #[test]
+    fn page_needs_review_below_threshold() {
+        assert!(PageConfidence::from_values(&[0.5, 0.6], 0.7).needs_review);
+        assert!(!PageConfidence::from_values(&[0.7, 0.7], 0.7).needs_review);
+        // Prázdná stránka se nekontroluje
+        let empty = PageConfidence::from_values(&[], 0.7);
+        assert!(!empty.needs_review);
+        assert_eq!(empty.words, 0);
+
+        let batch = BatchConfidence::from_values(&[0.5, 1.0], 0.7, vec!["3".to_string()]);
+        assert_eq!((batch.words, batch.mean, batch.low_share), (2, 0.75, 0.5));
+        assert_eq!(batch.review_pages, vec!["3"]);
+    }
+
+    #[test]
+    fn parse_threshold_range() {
+        assert_eq!(parse_threshold(" 0.5 "), Ok(0.5));
+        assert_eq!(parse_threshold("1"), Ok(1.0));
+        assert!(parse_threshold("1.5").is_err());
+        assert!(parse_threshold("-0.1").is_err());
+        assert!(parse_threshold("NaN").is_err());
+        assert!(parse_threshold("x").is_err());
+    }
+
+    #[test]
+    fn word_confidences_from_alto() {
+        let path = temp_file(
+            "wc.xml",
+            r#"<alto><Layout><TextLine><String CONTENT="a" WC="0.9"/><SP/>
+               <String CONTENT="b" WC="1.5"/><String CONTENT="c"/><String CONTENT="d" WC="x"/>
+               <Glyph WC="0.1"/></TextLine></Layout></alto>"#,
+        );
+        let values = read_word_confidences(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(values.unwrap(), vec![0.9, 1.0]);
+
+        let path = temp_file("wc-broken.xml", r#"<alto><String WC="0.9"></Strin></alto>"#);
+        let broken = read_word_confidences(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(broken.is_err());
+        assert!(read_word_confidences(Path::new("/nonexistent/alto.xml")).is_err());
+    }
+
+    #[test]
+    fn dictionary_hit_rate() {
+        let path = temp_file("ces.txt", "# komentář\nKůň 120\npes\n\n");
+        let wordlists = vec![("ces".to_string(), path.clone())];
+        let dictionary = Dictionary::load(&wordlists, "ces+eng").unwrap().unwrap();
+        let none = Dictionary::load(&wordlists, "deu").unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(none.is_none());
+
+        let page = dictionary.hit_rate("kůň a PES");
+        assert_eq!((page.words, page.hits, page.hit_rate), (3, 2, 0.6667));
+        let empty = dictionary.hit_rate("");
+        assert_eq!(empty.hit_rate, 0.0);
+        let batch = dictionary.summarize(&[&page, &empty]);
+        assert_eq!((batch.words, batch.hits), (3, 2));
+        assert_eq!(batch.missing_languages, vec!["eng"]);
+    }
+}
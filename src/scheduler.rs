@@ -0,0 +1,272 @@
+// src/scheduler.rs
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+
+use anyhow::{anyhow, Result};
+
+use crate::worker::{JobControl, JobLog};
+
+/// Jednoduchý počítací semafor (std žádný nemá)
+pub struct Semaphore {
+    permits: Mutex<usize>,
+    cv: Condvar,
+}
+
+pub struct SemaphoreGuard<'a> {
+    sem: &'a Semaphore,
+}
+
+impl Semaphore {
+    pub fn new(permits: usize) -> Self {
+        Self {
+            permits: Mutex::new(permits.max(1)),
+            cv: Condvar::new(),
+        }
+    }
+
+    pub fn acquire(&self) -> SemaphoreGuard<'_> {
+        let mut permits = self.permits.lock().unwrap_or_else(|e| e.into_inner());
+        while *permits == 0 {
+            permits = self.cv.wait(permits).unwrap_or_else(|e| e.into_inner());
+        }
+        *permits -= 1;
+        SemaphoreGuard { sem: self }
+    }
+}
+
+impl Drop for SemaphoreGuard<'_> {
+    fn drop(&mut self) {
+        let mut permits = self.sem.permits.lock().unwrap_or_else(|e| e.into_inner());
+        *permits += 1;
+        self.sem.cv.notify_one();
+    }
+}
+
+/// Chyba kroku, jehož externí proces se vůbec nespustil (zrušení během
+/// čekání na slot nebo pauzy, nespustitelný program). Výstupy kroku
+/// z dřívějšího běhu zůstávají platné a nemažou se.
+#[derive(Debug)]
+pub struct NotStarted(pub String);
+
+impl fmt::Display for NotStarted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotStarted {}
+
+/// Skončil krok dřív, než se spustil jeho externí proces?
+pub fn not_started(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| c.is::<NotStarted>())
+}
+
+/// Externí nástroj, pro který platí samostatný limit
+#[derive(Debug, Clone, Copy)]
+pub enum Tool {
+    Grok,
+    Tesseract,
+}
+
+/// Limity souběhu pro jednu dávku:
+/// - `total` = max. počet současně běžících externích procesů (`--jobs`)
+/// - `grok` / `tess` = max. počet procesů daného nástroje
+///
+/// Nese i `JobControl` dávky – nové kroky se během pauzy nespouští
+/// a po zrušení už vůbec.
+pub struct Limits {
+    jobs: usize,
+    total: Semaphore,
+    grok: Semaphore,
+    tess: Semaphore,
+    control: Arc<JobControl>,
+}
+
+/// Skutečné limity (celkem, Grok, Tesseract). Limit nástroje bez zadání
+/// = `--jobs` (jinak 1); celkový limit bez `--jobs` = součet limitů nástrojů,
+/// aby `--grok-jobs` / `--tess-jobs` samy o sobě souběh opravdu zvýšily.
+pub fn resolve_limits(
+    jobs: Option<usize>,
+    grok_jobs: Option<usize>,
+    tess_jobs: Option<usize>,
+) -> (usize, usize, usize) {
+    let base = jobs.unwrap_or(1).max(1);
+    let grok = grok_jobs.unwrap_or(base).max(1);
+    let tess = tess_jobs.unwrap_or(base).max(1);
+    let total = match jobs {
+        Some(jobs) => jobs.max(1),
+        None if grok_jobs.is_none() && tess_jobs.is_none() => 1,
+        None => grok + tess,
+    };
+    (total, grok, tess)
+}
+
+/// Upozornění, pokud zadané `--jobs` nedovolí využít limit některého nástroje
+pub fn capped_limits_warning(
+    jobs: Option<usize>,
+    grok_jobs: Option<usize>,
+    tess_jobs: Option<usize>,
+) -> Option<String> {
+    let jobs = jobs?.max(1);
+    let capped: Vec<String> = [("--grok-jobs", grok_jobs), ("--tess-jobs", tess_jobs)]
+        .into_iter()
+        .filter_map(|(name, limit)| limit.filter(|&l| l > jobs).map(|l| format!("{name} {l}")))
+        .collect();
+    (!capped.is_empty()).then(|| {
+        format!(
+            "Varování: --jobs {jobs} omezuje {} – celkem poběží nejvýš {jobs} procesů",
+            capped.join(" a ")
+        )
+    })
+}
+
+impl Limits {
+    pub fn new(
+        jobs: Option<usize>,
+        grok_jobs: Option<usize>,
+        tess_jobs: Option<usize>,
+        control: Arc<JobControl>,
+    ) -> Self {
+        let (jobs, grok, tess) = resolve_limits(jobs, grok_jobs, tess_jobs);
+        Self {
+            jobs,
+            total: Semaphore::new(jobs),
+            grok: Semaphore::new(grok),
+            tess: Semaphore::new(tess),
+            control,
+        }
+    }
+
+    pub fn control(&self) -> &JobControl {
+        &self.control
+    }
+
+    /// Spustí `f`, jakmile je volno pro daný nástroj i celkově.
+    /// Nejdřív se čeká na nástroj, aby čekající krok neblokoval globální slot.
+    /// Zrušení před spuštěním `f` vrací chybu `NotStarted`.
+    pub fn run<T>(&self, tool: Tool, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if !self.control.wait_if_paused() {
+            return Err(NotStarted("Zpracování zrušeno uživatelem".to_string()).into());
+        }
+        let _tool = match tool {
+            Tool::Grok => self.grok.acquire(),
+            Tool::Tesseract => self.tess.acquire(),
+        };
+        let _total = self.total.acquire();
+        // Během čekání na slot mohlo přijít zrušení nebo pauza
+        if !self.control.wait_if_paused() {
+            return Err(NotStarted("Zpracování zrušeno uživatelem".to_string()).into());
+        }
+        f()
+    }
+}
+
+/// Výsledek jedné stránky: její log (v pořadí kroků) a první chyba
+pub struct PageOutcome {
+    pub lines: Vec<String>,
+    pub result: Result<()>,
+}
+
+/// Zpracuje `count` stránek, nejvýše `--jobs` stránek současně.
+/// Logy stránek se do `logs` zapisují až v pořadí stránek (0, 1, 2, …),
+/// průběh se hlásí hned po dokončení každé stránky.
+/// Vrací výsledky ve stejném pořadí jako stránky; stránky, na které
+/// kvůli zrušení nedošlo, mají chybu.
+pub fn run_pages<F>(count: usize, limits: &Limits, logs: &mut JobLog, page_fn: F) -> Vec<Result<()>>
+where
+    F: Fn(usize) -> PageOutcome + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<()>>> = (0..count).map(|_| None).collect();
+
+    thread::scope(|s| {
+        let (tx, rx) = mpsc::channel::<(usize, PageOutcome)>();
+
+        for _ in 0..limits.jobs.min(count) {
+            let tx = tx.clone();
+            let next = &next;
+            let page_fn = &page_fn;
+            s.spawn(move || {
+                loop {
+                    // Během pauzy nebereme další stránky, po zrušení končíme
+                    if !limits.control.wait_if_paused() {
+                        break;
+                    }
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= count {
+                        break;
+                    }
+                    if tx.send((i, page_fn(i))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        // Stránky dobíhají v libovolném pořadí – logy držíme, dokud
+        // nejsou hotové všechny předchozí stránky.
+        let mut waiting: BTreeMap<usize, PageOutcome> = BTreeMap::new();
+        let mut next_flush = 0;
+        let mut done = 0;
+
+        for (i, outcome) in rx {
+            done += 1;
+            logs.progress(done);
+            waiting.insert(i, outcome);
+
+            while let Some(outcome) = waiting.remove(&next_flush) {
+                for line in outcome.lines {
+                    logs.push(line);
+                }
+                results[next_flush] = Some(outcome.result);
+                next_flush += 1;
+            }
+        }
+
+        // Po zrušení mohou v řadě chybět stránky – zbytek vypíšeme tak, jak je
+        for (i, outcome) in waiting {
+            for line in outcome.lines {
+                logs.push(line);
+            }
+            results[i] = Some(outcome.result);
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err(anyhow!("Stránka nebyla zpracována"))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn cancelled_run_is_not_started() {
+        let control = JobControl::new();
+        control.cancel();
+        let limits = Limits::new(Some(1), None, None, control);
+        let mut called = false;
+        let res = limits
+            .run(Tool::Grok, || {
+                called = true;
+                Ok(())
+            })
+            .context("Master JP2 selhalo");
+        assert!(!called);
+        assert!(not_started(&res.unwrap_err()));
+    }
+
+    #[test]
+    fn failed_run_is_started() {
+        let limits = Limits::new(Some(1), None, None, JobControl::new());
+        let res: Result<()> = limits.run(Tool::Tesseract, || Err(anyhow!("tesseract skončil s kódem 1")));
+        assert!(!not_started(&res.unwrap_err()));
+    }
+}
@@ -0,0 +1,182 @@
+// src/state.rs
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::ocr::OcrSettings;
+
+/// Název souboru se stavem dávky v `*_logs` adresáři
+pub const STATE_FILE: &str = "state.json";
+
+/// Krok zpracování jedné stránky
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Master, // AC JP2
+    User,   // UC JP2
+    Ocr,    // TXT + ALTO
+}
+
+impl Step {
+    pub fn label(self) -> &'static str {
+        match self {
+            Step::Master => "AC",
+            Step::User => "UC",
+            Step::Ocr => "OCR",
+        }
+    }
+}
+
+/// Výsledek kroku z posledního běhu
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", content = "error", rename_all = "snake_case")]
+pub enum StepStatus {
+    Done,
+    Failed(String),
+}
+
+/// Stav jedné stránky (klíčem v `BatchState::pages` je index, např. "0001")
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageState {
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ac: Option<StepStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uc: Option<StepStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<StepStatus>,
+}
+
+impl PageState {
+    fn step(&self, step: Step) -> Option<&StepStatus> {
+        match step {
+            Step::Master => self.ac.as_ref(),
+            Step::User => self.uc.as_ref(),
+            Step::Ocr => self.ocr.as_ref(),
+        }
+    }
+
+    fn step_mut(&mut self, step: Step) -> &mut Option<StepStatus> {
+        match step {
+            Step::Master => &mut self.ac,
+            Step::User => &mut self.uc,
+            Step::Ocr => &mut self.ocr,
+        }
+    }
+}
+
+/// Stav dávky po stránkách – umožňuje navázat po chybě nebo zrušení
+/// a znovu spustit jen chybějící / neúspěšné kroky.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchState {
+    pub updated_at: String,
+    /// Profil kódování, se kterým vznikly JP2 (jiný profil = JP2 znovu)
+    #[serde(default)]
+    pub profile: String,
+    /// Nastavení Tesseractu včetně použitého jazyka a verze ALTO,
+    /// se kterým vzniklo OCR (jiné = OCR znovu)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<OcrSettings>,
+    pub pages: BTreeMap<String, PageState>,
+}
+
+impl BatchState {
+    /// Načte stav z `logs_dir`; pokud neexistuje nebo je poškozený, vrátí None.
+    pub fn load(logs_dir: &Path) -> Option<Self> {
+        let text = fs::read_to_string(logs_dir.join(STATE_FILE)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn save(&mut self, logs_dir: &Path) -> Result<()> {
+        self.updated_at = Local::now().to_rfc3339();
+        let path = logs_dir.join(STATE_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)
+            .with_context(|| format!("Nelze zapsat stav dávky `{}`", path.display()))
+    }
+
+    /// Byl krok stránky v minulém běhu úspěšně dokončen?
+    pub fn is_done(&self, index: &str, step: Step) -> bool {
+        self.pages
+            .get(index)
+            .and_then(|p| p.step(step))
+            .is_some_and(|s| *s == StepStatus::Done)
+    }
+
+    /// `source` = zdroj stránky (cesta, u stránky vícestránkového TIFFu `cesta#N`)
+    pub fn record(&mut self, index: &str, source: &str, step: Step, result: &Result<()>) {
+        let page = self.pages.entry(index.to_string()).or_default();
+        page.source = source.to_string();
+        *page.step_mut(step) = Some(match result {
+            Ok(()) => StepStatus::Done,
+            Err(e) => StepStatus::Failed(format!("{e:#}")),
+        });
+    }
+
+    /// Zapomene stránky, jejichž index teď patří jinému zdrojovému souboru
+    /// (`sources`: index -> zdroj stránky). Vrací počet zapomenutých stránek.
+    pub fn forget_changed_sources(&mut self, sources: &BTreeMap<String, String>) -> usize {
+        let before = self.pages.len();
+        self.pages
+            .retain(|index, page| sources.get(index).is_some_and(|s| *s == page.source));
+        before - self.pages.len()
+    }
+
+    /// Zapomene výsledky jednoho kroku u všech stránek. Vrací počet stránek,
+    /// u kterých byl krok zaznamenán.
+    pub fn forget_step(&mut self, step: Step) -> usize {
+        self.pages
+            .values_mut()
+            .filter_map(|page| page.step_mut(step).take())
+            .count()
+    }
+
+    /// Neúspěšné kroky: (index, krok, důvod)
+    pub fn failures(&self) -> Vec<(String, &'static str, String)> {
+        let mut out = Vec::new();
+        for (index, page) in &self.pages {
+            for step in [Step::Master, Step::User, Step::Ocr] {
+                if let Some(StepStatus::Failed(reason)) = page.step(step) {
+                    out.push((index.clone(), step.label(), reason.clone()));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Stav dávky sdílený mezi paralelně zpracovávanými stránkami.
+/// Po každé stránce se ukládá na disk, aby přežil pád i zrušení.
+pub struct StateTracker {
+    state: Mutex<BatchState>,
+    logs_dir: PathBuf,
+}
+
+impl StateTracker {
+    pub fn new(state: BatchState, logs_dir: &Path) -> Self {
+        Self {
+            state: Mutex::new(state),
+            logs_dir: logs_dir.to_path_buf(),
+        }
+    }
+
+    pub fn is_done(&self, index: &str, step: Step) -> bool {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_done(index, step)
+    }
+
+    /// Zapíše výsledky kroků jedné stránky a uloží stav.
+    pub fn record_page(&self, index: &str, source: &str, results: &[(Step, &Result<()>)]) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        for (step, result) in results {
+            state.record(index, source, *step, result);
+        }
+        state.save(&self.logs_dir)
+    }
+}
@@ -0,0 +1,407 @@
+// src/verify.rs
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::hashing::{compute_digests, with_blake3, HashAlgorithm};
+
+/// Název zprávy o kontrole v `*_logs` adresáři
+pub const VERIFY_REPORT_FILE: &str = "verification.json";
+
+/// Soubor v manifestu (jen to, co kontrola potřebuje)
+#[derive(Debug, Deserialize)]
+struct ManifestFile {
+    path: String,
+    size: u64,
+    blake3: String,
+    #[serde(default)]
+    digests: BTreeMap<HashAlgorithm, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestPage {
+    index: String,
+    #[serde(alias = "original_tiff", rename = "tiff")]
+    tiff: ManifestFile,
+    ac_jp2: Option<ManifestFile>,
+    uc_jp2: Option<ManifestFile>,
+    txt: Option<ManifestFile>,
+    alto: Option<ManifestFile>,
+    #[serde(default)]
+    hocr: Option<ManifestFile>,
+    #[serde(default)]
+    pdf: Option<ManifestFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    batch_name: String,
+    output_dir: String,
+    logs_dir: String,
+    #[serde(default)]
+    checksum_algorithms: Vec<HashAlgorithm>,
+    pages: Vec<ManifestPage>,
+    #[serde(default)]
+    merged_pdf: Option<ManifestFile>,
+}
+
+/// Výsledek kontroly jednoho souboru
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Ok,
+    Missing,
+    /// Soubor existuje, ale nejde přečíst (chyba I/O při výpočtu hashe)
+    Unreadable(String),
+    Changed,
+    SizeMismatch,
+    /// Soubor ve výstupním adresáři, který v manifestu není
+    Extra,
+    /// manifest.json a checksums.txt uvádějí pro soubor jiný hash
+    Inconsistent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    /// "tiff", "ac_jp2", "uc_jp2", "txt", "alto", "hocr", "pdf", "merged_pdf",
+    /// "checksums" nebo "extra"
+    pub role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    pub path: String,
+    pub status: FileStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_blake3: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_blake3: Option<String>,
+    /// Algoritmy, jejichž hash nesouhlasí s manifestem / checksums soubory
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mismatched: Vec<HashAlgorithm>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifySummary {
+    pub ok: usize,
+    pub missing: usize,
+    pub unreadable: usize,
+    pub changed: usize,
+    pub size_mismatch: usize,
+    pub extra: usize,
+    pub inconsistent: usize,
+}
+
+impl VerifySummary {
+    pub fn is_ok(&self) -> bool {
+        self.missing + self.unreadable + self.changed + self.size_mismatch + self.extra + self.inconsistent == 0
+    }
+}
+
+/// Zpráva o kontrole jedné dávky (zapisuje se jako verification.json)
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub batch_name: String,
+    pub verified_at: String,
+    pub output_dir: String,
+    pub summary: VerifySummary,
+    pub files: Vec<FileResult>,
+}
+
+/// Znovu spočítá hashe všech souborů z manifest.json (a checksums souborů, pokud existují).
+///
+/// Výstupy se hledají relativně k současnému umístění `*_logs` adresáře,
+/// takže kontrola funguje i po přenesení výstupů jinam. Zdrojové TIFFy
+/// se kontrolují na původní cestě (`skip_sources` je vynechá).
+pub fn verify_batch(logs_dir: &Path, skip_sources: bool) -> Result<VerifyReport> {
+    let manifest_path = logs_dir.join("manifest.json");
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Nelze načíst `{}`", manifest_path.display()))?;
+    let manifest: Manifest = serde_json::from_str(&text)
+        .with_context(|| format!("Nelze parsovat `{}`", manifest_path.display()))?;
+
+    let output_dir = relocate_output_dir(&manifest, logs_dir);
+
+    // Očekávané soubory: skutečná cesta -> (role, index, původní záznam)
+    let mut expected: Vec<(PathBuf, &str, Option<&str>, &ManifestFile)> = Vec::new();
+    let mut sources_seen = BTreeSet::new();
+    for page in &manifest.pages {
+        // Stránky vícestránkového TIFFu sdílejí jeden zdrojový soubor
+        if !skip_sources && sources_seen.insert(page.tiff.path.as_str()) {
+            expected.push((PathBuf::from(&page.tiff.path), "tiff", Some(&page.index), &page.tiff));
+        }
+        for (role, file) in [
+            ("ac_jp2", &page.ac_jp2),
+            ("uc_jp2", &page.uc_jp2),
+            ("txt", &page.txt),
+            ("alto", &page.alto),
+            ("hocr", &page.hocr),
+            ("pdf", &page.pdf),
+        ] {
+            if let Some(f) = file {
+                expected.push((relocate(&f.path, &output_dir), role, Some(&page.index), f));
+            }
+        }
+    }
+    if let Some(f) = &manifest.merged_pdf {
+        expected.push((relocate(&f.path, &output_dir), "merged_pdf", None, f));
+    }
+
+    let checksums = read_checksums(&logs_dir.join(HashAlgorithm::Blake3.checksum_file_name()))?;
+    let mut other_checksums = BTreeMap::new();
+    for &algorithm in &manifest.checksum_algorithms {
+        if algorithm != HashAlgorithm::Blake3 {
+            let sums = read_checksums(&logs_dir.join(algorithm.checksum_file_name()))?;
+            other_checksums.insert(algorithm, sums);
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut listed: BTreeSet<String> = BTreeSet::new();
+
+    for (path, role, index, entry) in &expected {
+        listed.insert(entry.path.clone());
+        let mut result = check_file(
+            path,
+            role,
+            index.map(str::to_string),
+            Some(entry.size),
+            &entry.blake3,
+            &entry.digests,
+        );
+        if result.status == FileStatus::Ok
+            && let Some(sum) = checksums.get(&entry.path)
+            && !sum.eq_ignore_ascii_case(&entry.blake3)
+        {
+            result.status = FileStatus::Inconsistent;
+            result.expected_blake3 = Some(format!("{} (checksums.txt: {})", entry.blake3, sum));
+            result.mismatched.push(HashAlgorithm::Blake3);
+        }
+        // Soubor sedí s manifestem, ale checksums-<algoritmus>.txt uvádí něco jiného
+        if matches!(result.status, FileStatus::Ok | FileStatus::Inconsistent) {
+            for (algorithm, sums) in &other_checksums {
+                if let (Some(expected), Some(sum)) = (entry.digests.get(algorithm), sums.get(&entry.path))
+                    && !sum.eq_ignore_ascii_case(expected)
+                {
+                    result.status = FileStatus::Inconsistent;
+                    result.mismatched.push(*algorithm);
+                }
+            }
+        }
+        files.push(result);
+    }
+
+    // Soubory jen v checksums.txt (např. ručně doplněné)
+    for (recorded, sum) in &checksums {
+        if listed.contains(recorded) {
+            continue;
+        }
+        let is_source = !Path::new(recorded).starts_with(&manifest.output_dir);
+        if is_source && skip_sources {
+            continue;
+        }
+        let path = if is_source {
+            PathBuf::from(recorded)
+        } else {
+            relocate(recorded, &output_dir)
+        };
+        files.push(check_file(&path, "checksums", None, None, sum, &BTreeMap::new()));
+    }
+
+    // Soubory navíc ve výstupním adresáři dávky
+    let known: BTreeSet<PathBuf> = files.iter().map(|f| PathBuf::from(&f.path)).collect();
+    if output_dir.is_dir() {
+        let mut extra: Vec<PathBuf> = fs::read_dir(&output_dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && !known.contains(p))
+            .collect();
+        extra.sort();
+        for path in extra {
+            let actual_size = fs::metadata(&path).ok().map(|m| m.len());
+            files.push(FileResult {
+                role: "extra".to_string(),
+                index: None,
+                path: path.to_string_lossy().to_string(),
+                status: FileStatus::Extra,
+                expected_size: None,
+                actual_size,
+                expected_blake3: None,
+                actual_blake3: None,
+                mismatched: Vec::new(),
+            });
+        }
+    }
+
+    let mut summary = VerifySummary::default();
+    for f in &files {
+        match f.status {
+            FileStatus::Ok => summary.ok += 1,
+            FileStatus::Missing => summary.missing += 1,
+            FileStatus::Unreadable(_) => summary.unreadable += 1,
+            FileStatus::Changed => summary.changed += 1,
+            FileStatus::SizeMismatch => summary.size_mismatch += 1,
+            FileStatus::Extra => summary.extra += 1,
+            FileStatus::Inconsistent => summary.inconsistent += 1,
+        }
+    }
+
+    Ok(VerifyReport {
+        batch_name: manifest.batch_name,
+        verified_at: Local::now().to_rfc3339(),
+        output_dir: output_dir.to_string_lossy().to_string(),
+        summary,
+        files,
+    })
+}
+
+/// Zapíše zprávu do `*_logs/verification.json`
+pub fn write_verify_report(report: &VerifyReport, logs_dir: &Path) -> Result<PathBuf> {
+    let path = logs_dir.join(VERIFY_REPORT_FILE);
+    let json = serde_json::to_string_pretty(report)?;
+    fs::write(&path, json).with_context(|| format!("Nelze zapsat `{}`", path.display()))?;
+    Ok(path)
+}
+
+/// Zkontroluje velikost, BLAKE3 a případné další hashe (jedním čtením souboru)
+fn check_file(
+    path: &Path,
+    role: &str,
+    index: Option<String>,
+    expected_size: Option<u64>,
+    expected_blake3: &str,
+    expected_digests: &BTreeMap<HashAlgorithm, String>,
+) -> FileResult {
+    let mut result = FileResult {
+        role: role.to_string(),
+        index,
+        path: path.to_string_lossy().to_string(),
+        status: FileStatus::Ok,
+        expected_size,
+        actual_size: None,
+        expected_blake3: Some(expected_blake3.to_string()),
+        actual_blake3: None,
+        mismatched: Vec::new(),
+    };
+
+    let Ok(meta) = fs::metadata(path) else {
+        result.status = FileStatus::Missing;
+        return result;
+    };
+    result.actual_size = Some(meta.len());
+
+    let algorithms: Vec<HashAlgorithm> = expected_digests.keys().copied().collect();
+    match compute_digests(path, &with_blake3(&algorithms)) {
+        Ok(mut digests) => {
+            let hash = digests.remove(&HashAlgorithm::Blake3).unwrap_or_default();
+            if !hash.eq_ignore_ascii_case(expected_blake3) {
+                result.mismatched.push(HashAlgorithm::Blake3);
+            }
+            for (algorithm, actual) in &digests {
+                if expected_digests
+                    .get(algorithm)
+                    .is_some_and(|expected| !actual.eq_ignore_ascii_case(expected))
+                {
+                    result.mismatched.push(*algorithm);
+                }
+            }
+            if expected_size.is_some_and(|s| s != meta.len()) {
+                result.status = FileStatus::SizeMismatch;
+            } else if !result.mismatched.is_empty() {
+                result.status = FileStatus::Changed;
+            }
+            result.actual_blake3 = Some(hash);
+        }
+        Err(e) => result.status = FileStatus::Unreadable(format!("{e:#}")),
+    }
+    result
+}
+
+/// Výstupy stránek dané role ("alto", "txt", …) z manifest.json:
+/// (cesta zapsaná v manifestu, skutečná cesta po případném přenesení)
+pub fn manifest_outputs(logs_dir: &Path, role: &str) -> Result<Vec<(String, PathBuf)>> {
+    let manifest_path = logs_dir.join("manifest.json");
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Nelze načíst `{}`", manifest_path.display()))?;
+    let manifest: Manifest = serde_json::from_str(&text)
+        .with_context(|| format!("Nelze parsovat `{}`", manifest_path.display()))?;
+    let output_dir = relocate_output_dir(&manifest, logs_dir);
+
+    Ok(manifest
+        .pages
+        .iter()
+        .filter_map(|page| match role {
+            "ac_jp2" => page.ac_jp2.as_ref(),
+            "uc_jp2" => page.uc_jp2.as_ref(),
+            "txt" => page.txt.as_ref(),
+            "alto" => page.alto.as_ref(),
+            "hocr" => page.hocr.as_ref(),
+            "pdf" => page.pdf.as_ref(),
+            _ => None,
+        })
+        .map(|f| (f.path.clone(), relocate(&f.path, &output_dir)))
+        .collect())
+}
+
+/// Kde je výstupní adresář dávky teď. V manifestu je původní cesta;
+/// původní output root je rodič `logs_dir` z manifestu, relativní
+/// umístění vůči němu se přenese k současnému `logs_dir`.
+fn relocate_output_dir(manifest: &Manifest, logs_dir: &Path) -> PathBuf {
+    let current_root = logs_dir.parent().unwrap_or(Path::new(""));
+    let original_root = Path::new(&manifest.logs_dir).parent().unwrap_or(Path::new(""));
+    match Path::new(&manifest.output_dir).strip_prefix(original_root) {
+        Ok(rel) => current_root.join(rel),
+        Err(_) => PathBuf::from(&manifest.output_dir),
+    }
+}
+
+/// Výstupní soubor hledáme podle názvu v současném výstupním adresáři
+fn relocate(recorded: &str, output_dir: &Path) -> PathBuf {
+    match Path::new(recorded).file_name() {
+        Some(name) => output_dir.join(name),
+        None => PathBuf::from(recorded),
+    }
+}
+
+/// checksums.txt: "<hash>  <cesta>" na řádek. Chybějící soubor = prázdná mapa.
+fn read_checksums(path: &Path) -> Result<BTreeMap<String, String>> {
+    let mut map = BTreeMap::new();
+    if !path.exists() {
+        return Ok(map);
+    }
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Nelze načíst `{}`", path.display()))?;
+    for line in text.lines() {
+        if let Some((hash, file)) = line.split_once("  ") {
+            map.insert(file.trim().to_string(), hash.trim().to_string());
+        }
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_file_is_not_reported_missing() {
+        // Adresář má metadata, ale čtení selže
+        let dir = std::env::temp_dir().join(format!("lazyproarc-{}-verify-dir", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let result = check_file(&dir, "alto", None, None, "", &BTreeMap::new());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result.status, FileStatus::Unreadable(_)), "{:?}", result.status);
+    }
+
+    #[test]
+    fn absent_file_is_missing() {
+        let path = std::env::temp_dir().join(format!("lazyproarc-{}-verify-none", std::process::id()));
+        let result = check_file(&path, "alto", None, None, "", &BTreeMap::new());
+        assert_eq!(result.status, FileStatus::Missing);
+    }
+}
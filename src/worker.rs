@@ -0,0 +1,252 @@
+// src/worker.rs
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use crate::ocr::OcrSettings;
+use crate::profiles::EncodingProfile;
+use crate::{Args, JobStatus};
+
+/// Vše, co worker potřebuje ke zpracování jedné dávky.
+/// Hodnoty se zkopírují z `App` v okamžiku zařazení do fronty,
+/// pozdější změny v TUI už běžící dávku neovlivní.
+#[derive(Debug, Clone)]
+pub struct JobRequest {
+    /// Index dávky v `App::jobs`
+    pub job_index: usize,
+    /// Číslo zařazení do fronty (přidělí `Worker::submit`), nese ho každá
+    /// zpráva běhu – zprávy zrušeného běhu tak nepřepíšou nový
+    pub run: u64,
+    pub args: Args,
+    pub dir: PathBuf,
+    /// Název dávky (cesta od input_root), určuje i `*_logs` adresář
+    pub batch_name: String,
+    pub index_start: u32,
+    pub file_count: usize,
+    pub output_root: PathBuf,
+    pub input_root: PathBuf,
+    pub grok_path: PathBuf,
+    pub tess_path: PathBuf,
+    pub tess_source: String,
+    pub tessdata_dir: Option<PathBuf>,
+    pub do_master: bool,
+    pub do_user: bool,
+    pub do_txt: bool,
+    pub do_alto: bool,
+    pub do_hocr: bool,
+    pub do_pdf: bool,
+    /// Spojit PDF stránek do jednoho PDF dávky (jen s `do_pdf`)
+    pub merge_pdf: bool,
+    /// Jazyk a verze ALTO dávky (vlastní, jinak globální)
+    pub lang: String,
+    pub alto_version: String,
+    /// Navázat na stav z minulého běhu (false = vše znovu)
+    pub resume: bool,
+    /// Profil kódování Grok (kopie v okamžiku zařazení)
+    pub profile: EncodingProfile,
+    /// Nastavení Tesseractu dávky (kopie v okamžiku zařazení)
+    pub ocr: OcrSettings,
+    /// Řízení běhu (pauza / zrušení) sdílené s TUI
+    pub control: Arc<JobControl>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControlState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+/// Pauza / zrušení běžící dávky. TUI stav mění, worker ho kontroluje
+/// před každým krokem a během čekání na externí procesy.
+#[derive(Debug)]
+pub struct JobControl {
+    state: Mutex<ControlState>,
+    cv: Condvar,
+}
+
+impl JobControl {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(ControlState::Running),
+            cv: Condvar::new(),
+        })
+    }
+
+    fn set(&self, new_state: ControlState) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        // Zrušení je konečné
+        if *state != ControlState::Cancelled {
+            *state = new_state;
+        }
+        self.cv.notify_all();
+    }
+
+    fn get(&self) -> ControlState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn pause(&self) {
+        self.set(ControlState::Paused);
+    }
+
+    pub fn resume(&self) {
+        self.set(ControlState::Running);
+    }
+
+    pub fn cancel(&self) {
+        self.set(ControlState::Cancelled);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.get() == ControlState::Cancelled
+    }
+
+    /// Blokuje, dokud je dávka pozastavená. Vrací false, pokud byla zrušena.
+    pub fn wait_if_paused(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while *state == ControlState::Paused {
+            state = self.cv.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        *state != ControlState::Cancelled
+    }
+}
+
+/// Zprávy z workeru zpět do TUI
+#[derive(Debug)]
+pub enum WorkerEvent {
+    /// Worker začal dávku zpracovávat
+    Started { job: usize, run: u64 },
+    /// Jeden řádek logu
+    Log { job: usize, run: u64, line: String },
+    /// Počet hotových stránek
+    Progress { job: usize, run: u64, done: usize },
+    /// Dávka skončila (Done / Failed / Cancelled)
+    Finished { job: usize, run: u64, status: JobStatus },
+}
+
+/// Log jedné dávky: řádky se ukládají (pro log.txt) a zároveň
+/// se průběžně posílají do TUI.
+pub struct JobLog {
+    job: usize,
+    run: u64,
+    lines: Vec<String>,
+    sink: Option<Sender<WorkerEvent>>,
+}
+
+impl JobLog {
+    /// Log napojený na TUI kanál
+    pub fn streaming(job: usize, run: u64, sink: Sender<WorkerEvent>) -> Self {
+        Self {
+            job,
+            run,
+            lines: Vec::new(),
+            sink: Some(sink),
+        }
+    }
+
+    /// Samostatný log bez napojení na TUI (např. pro jednu stránku
+    /// při paralelním zpracování – do dávkového logu se přelije později)
+    pub fn buffered() -> Self {
+        Self {
+            job: 0,
+            run: 0,
+            lines: Vec::new(),
+            sink: None,
+        }
+    }
+
+    pub fn push(&mut self, line: String) {
+        if let Some(ref tx) = self.sink {
+            // Pokud TUI už neposlouchá, nevadí – řádky zůstanou v log.txt
+            let _ = tx.send(WorkerEvent::Log {
+                job: self.job,
+                run: self.run,
+                line: line.clone(),
+            });
+        }
+        self.lines.push(line);
+    }
+
+    pub fn progress(&self, done: usize) {
+        if let Some(ref tx) = self.sink {
+            let _ = tx.send(WorkerEvent::Progress {
+                job: self.job,
+                run: self.run,
+                done,
+            });
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines
+    }
+}
+
+/// Jedno vlákno na pozadí, které zpracovává frontu dávek postupně.
+pub struct Worker {
+    requests: Sender<JobRequest>,
+    events: Receiver<WorkerEvent>,
+    /// Poslední přidělené číslo zařazení
+    last_run: u64,
+}
+
+impl Worker {
+    pub fn spawn() -> Self {
+        let (req_tx, req_rx) = mpsc::channel::<JobRequest>();
+        let (ev_tx, ev_rx) = mpsc::channel::<WorkerEvent>();
+
+        thread::spawn(move || {
+            for req in req_rx {
+                let job = req.job_index;
+                let run = req.run;
+
+                // Dávka zrušená ještě ve frontě se vůbec nespouští
+                if req.control.is_cancelled() {
+                    let _ = ev_tx.send(WorkerEvent::Finished {
+                        job,
+                        run,
+                        status: JobStatus::Cancelled,
+                    });
+                    continue;
+                }
+
+                let _ = ev_tx.send(WorkerEvent::Started { job, run });
+
+                let mut logs = JobLog::streaming(job, run, ev_tx.clone());
+                let status = crate::execute_job(&req, &mut logs);
+
+                let _ = ev_tx.send(WorkerEvent::Finished { job, run, status });
+            }
+        });
+
+        Self {
+            requests: req_tx,
+            events: ev_rx,
+            last_run: 0,
+        }
+    }
+
+    /// Zařadí dávku do fronty a vrátí číslo zařazení.
+    /// None, pokud vlákno workeru už neběží.
+    pub fn submit(&mut self, mut req: JobRequest) -> Option<u64> {
+        self.last_run += 1;
+        req.run = self.last_run;
+        self.requests.send(req).ok().map(|_| self.last_run)
+    }
+
+    /// Počká na další zprávu (režim bez TUI). None = worker skončil.
+    pub fn recv(&self) -> Option<WorkerEvent> {
+        self.events.recv().ok()
+    }
+
+    /// Vybere všechny zprávy, které zatím dorazily (neblokuje).
+    pub fn drain(&self) -> Vec<WorkerEvent> {
+        self.events.try_iter().collect()
+    }
+}